@@ -1,9 +1,15 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // This is an example of a line comment
     // There are two slashes at the beginning of the line
     // And nothing written inside these will be read by the compiler
 
-    println!("Hello, world!");
+    writeln!(out, "Hello, world!")?;
 
     // Run it. See? Now try deleting the two slashes, and run it again.
 
@@ -25,5 +31,8 @@ fn main() {
     // than with line comments. Try deleting the comment delimiters
     // to change the result:
     let x = 5 + /* 90 + */ 5;
-    println!("Is `x` 10 or 100? x = {}", x);
+    writeln!(out, "Is `x` 10 or 100? x = {}", x)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
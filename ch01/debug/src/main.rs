@@ -1,28 +1,35 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Printing with `{:?}` is similar to with `{}`.
-    println!("{:?} months in a year.", 12);
-    println!(
+    writeln!(out, "{:?} months in a year.", 12)?;
+    writeln!(
+        out,
         "{1:?} {0:?} is the {actor:?} name.",
         "Slater",
         "Christian",
         actor = "actor's"
-    );
+    )?;
 
     // `Structure` is printable!
-    println!("Now {:?} will print!", Structure(3));
+    writeln!(out, "Now {:?} will print!", Structure(3))?;
 
     // The problem with `derive` is there is no control over how
     // the results look. What if I want this to just show a `7`?
-    println!("Now {:?} will print!", Deep(Structure(7)));
+    writeln!(out, "Now {:?} will print!", Deep(Structure(7)))?;
 
     let name = "Peter";
     let age = 27;
     let peter = Person { name, age };
 
     // Pretty print
-    println!("{:#?}", peter);
+    writeln!(out, "{:#?}", peter)?;
     // Normal print
-    println!("{:?}", peter);
+    writeln!(out, "{:?}", peter)
 }
 
 // This structure cannot be printed either with `fmt::Display` or
@@ -49,3 +56,6 @@ struct Person<'a> {
     name: &'a str,
     age: u8,
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,38 +1,54 @@
 // Import (via `use`) the `fmt` module to make it available.
 use std::fmt;
+use std::io::{self, Write};
 
-fn main() {
+use formatting::flags::{number, pad};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let minmax = MinMax(0, 14);
 
-    println!("Compare structures:");
-    println!("Display: {}", minmax);
-    println!("Debug: {:?}", minmax);
+    writeln!(out, "Compare structures:")?;
+    writeln!(out, "Display: {}", minmax)?;
+    writeln!(out, "Debug: {:?}", minmax)?;
 
     let big_range = MinMax(-300, 300);
     let small_range = MinMax(-3, 3);
 
-    println!(
+    writeln!(
+        out,
         "The big range is {big} and the small is {small}",
         small = small_range,
         big = big_range
-    );
+    )?;
 
     let point = Point2D { x: 3.3, y: 7.2 };
 
-    println!("Compare points:");
-    println!("Display: {}", point);
-    println!("Debug: {:?}", point);
+    writeln!(out, "Compare points:")?;
+    writeln!(out, "Display: {}", point)?;
+    writeln!(out, "Debug: {:?}", point)?;
 
     // Error. Both `Debug` and `Display` were implemented, but `{:b}`
     // requires `fmt::Binary` to be implemented. This will not work.
-    println!("What does Point2D look like in binary: {:b}?", point);
+    writeln!(out, "What does Point2D look like in binary: {:b}?", point)?;
 
     let point = Complex {
         real: 3.3,
         imag: 7.2,
     };
-    println!("Display: {}", point);
-    println!("Debug: {:?}", point);
+    writeln!(out, "Display: {}", point)?;
+    writeln!(out, "Debug: {:?}", point)?;
+
+    // The `Display` impls below respect the formatting options, such as
+    // width, fill and alignment, precision, sign and the alternate flag.
+    writeln!(out, "[{:>20}]", MinMax(-3, 3))?;
+    writeln!(out, "[{:*^20.2}]", Point2D { x: 3.3, y: 7.2 })?;
+    writeln!(out, "[{:<+20.1}]", point)?;
+    writeln!(out, "{:#}", MinMax(0, 14))?;
+    writeln!(out, "{:#.3}", point)
 }
 
 // Define a structure for which `fmt::Display` will be implemented. This is
@@ -61,7 +77,13 @@ struct MinMax(i64, i64);
 impl fmt::Display for MinMax {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Use `self.number` to refer to each positional data point.
-        write!(f, "({}, {})", self.0, self.1)
+        let (min, max) = (number(f, self.0), number(f, self.1));
+        // `{:#}` puts each number on a line of its own
+        if f.alternate() {
+            pad(f, &format!("(\n    {},\n    {},\n)", min, max))
+        } else {
+            pad(f, &format!("({}, {})", min, max))
+        }
     }
 }
 
@@ -76,7 +98,12 @@ struct Point2D {
 impl fmt::Display for Point2D {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Customize so only `x` and `y` are denoted.
-        write!(f, "x: {}, y: {}", self.x, self.y)
+        let (x, y) = (number(f, self.x), number(f, self.y));
+        if f.alternate() {
+            pad(f, &format!("x: {}\ny: {}", x, y))
+        } else {
+            pad(f, &format!("x: {}, y: {}", x, y))
+        }
     }
 }
 
@@ -87,6 +114,8 @@ impl fmt::Binary for Point2D {
 }
 
 // Define a structure where the fields are nameable for comparison.
+// It is not the `Complex<T>` of the FFI example, whose `Debug` prints
+// `3.3+7.2i` rather than the field names.
 #[derive(Debug)]
 struct Complex {
     real: f64,
@@ -96,7 +125,161 @@ struct Complex {
 // Similarly, implement `Display` for `Point2D`
 impl fmt::Display for Complex {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Customize so only `x` and `y` are denoted.
-        write!(f, "{} + {}i", self.real, self.imag)
+        // Customize so only `x` and `y` are denoted. The sign option only
+        // applies to the real part, the imaginary one follows a ` + `.
+        let real = number(f, self.real);
+        let imag = match f.precision() {
+            Some(precision) => format!("{:.*}", precision, self.imag),
+            None => format!("{}", self.imag),
+        };
+        if f.alternate() {
+            pad(f, &format!("real: {}\nimag: {}", real, imag))
+        } else {
+            pad(f, &format!("{} + {}i", real, imag))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point() -> Point2D {
+        Point2D { x: 3.3, y: -7.25 }
+    }
+
+    fn complex() -> Complex {
+        Complex {
+            real: 3.3,
+            imag: 7.25,
+        }
+    }
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(format!("{}", Structure(3)), "3");
+        assert_eq!(format!("{}", MinMax(0, 14)), "(0, 14)");
+        assert_eq!(format!("{}", point()), "x: 3.3, y: -7.25");
+        assert_eq!(format!("{}", complex()), "3.3 + 7.25i");
+    }
+
+    #[test]
+    fn test_width_fill_and_alignment() {
+        assert_eq!(format!("{:12}|", MinMax(0, 14)), "(0, 14)     |");
+        assert_eq!(format!("{:>12}", MinMax(0, 14)), "     (0, 14)");
+        assert_eq!(format!("{:-^11}", MinMax(0, 14)), "--(0, 14)--");
+
+        assert_eq!(format!("{:>20}", point()), "    x: 3.3, y: -7.25");
+        assert_eq!(format!("{:*<18}", point()), "x: 3.3, y: -7.25**");
+        assert_eq!(format!("{:^15}", complex()), "  3.3 + 7.25i  ");
+        assert_eq!(format!("{:_>13}", complex()), "__3.3 + 7.25i");
+
+        // A width smaller than the value changes nothing
+        assert_eq!(format!("{:3}", complex()), "3.3 + 7.25i");
     }
+
+    #[test]
+    fn test_precision() {
+        // Integers have no fractional part to show
+        assert_eq!(format!("{:.2}", MinMax(0, 14)), "(0, 14)");
+        assert_eq!(format!("{:.2}", point()), "x: 3.30, y: -7.25");
+        assert_eq!(format!("{:.0}", point()), "x: 3, y: -7");
+        assert_eq!(format!("{:.1}", complex()), "3.3 + 7.2i");
+        assert_eq!(format!("{:>14.3}", complex()), "3.300 + 7.250i");
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(format!("{:+}", MinMax(-3, 3)), "(-3, +3)");
+        assert_eq!(format!("{:+.1}", point()), "x: +3.3, y: -7.2");
+        assert_eq!(format!("{:+}", complex()), "+3.3 + 7.25i");
+    }
+
+    #[test]
+    fn test_alternate() {
+        assert_eq!(format!("{:#}", MinMax(0, 14)), "(\n    0,\n    14,\n)");
+        assert_eq!(format!("{:#}", point()), "x: 3.3\ny: -7.25");
+        assert_eq!(format!("{:#.1}", complex()), "real: 3.3\nimag: 7.2");
+        // Every line is padded on its own
+        assert_eq!(format!("{:>#9}", point()), "   x: 3.3\n y: -7.25");
+    }
+
+    // `#[derive(Display)]` from chapter 17 reproduces the impls above
+    mod derived {
+        use derive_macros::Display;
+
+        #[derive(Display)]
+        pub struct Structure(pub i32);
+
+        #[derive(Display)]
+        #[display("({0}, {1})", alternate = "(\n    {0},\n    {1},\n)")]
+        pub struct MinMax(pub i64, pub i64);
+
+        #[derive(Display)]
+        #[display("x: {x}, y: {y}", alternate = "x: {x}\ny: {y}")]
+        pub struct Point2D {
+            pub x: f64,
+            pub y: f64,
+        }
+
+        // The imaginary part takes the precision, but not the sign, through a
+        // named argument that reads the formatter `f` and `self`
+        #[derive(Display)]
+        #[display(
+            "{real} + {imag}i",
+            alternate = "real: {real}\nimag: {imag}",
+            imag = match f.precision() {
+                Some(precision) => format!("{:.*}", precision, self.imag),
+                None => format!("{}", self.imag),
+            },
+        )]
+        pub struct Complex {
+            pub real: f64,
+            pub imag: f64,
+        }
+    }
+
+    // Both values give the same output for each of the format strings
+    macro_rules! assert_same {
+        ($real:expr, $derived:expr, $($format:literal),+) => {$(
+            assert_eq!(format!($format, $real), format!($format, $derived), "{}", $format);
+        )+};
+    }
+
+    #[test]
+    fn test_derived() {
+        assert_same!(Structure(3), derived::Structure(3), "{}");
+        assert_same!(
+            MinMax(-3, 3),
+            derived::MinMax(-3, 3),
+            "{}",
+            "{:-^12}",
+            "{:.2}",
+            "{:+}",
+            "{:>#6}"
+        );
+        assert_same!(
+            point(),
+            derived::Point2D { x: 3.3, y: -7.25 },
+            "{}",
+            "{:*<18}",
+            "{:.0}",
+            "{:+.1}",
+            "{:>#9}"
+        );
+        assert_same!(
+            complex(),
+            derived::Complex {
+                real: 3.3,
+                imag: 7.25,
+            },
+            "{}",
+            "{:^15}",
+            "{:>14.3}",
+            "{:+}",
+            "{:#.1}"
+        );
+    }
+
+    testing::snapshot_test!(run);
 }
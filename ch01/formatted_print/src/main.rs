@@ -1,39 +1,47 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // In general, the `{}` will be automatically replaced with any
     // arguments. These will be stringified.
-    println!("{} days", 31);
+    writeln!(out, "{} days", 31)?;
 
     // Without a suffix, 31 becomes an i32. You can change what type 31 is
     // by providing a suffix. The number 31i64 for example has the type i64.
 
     // There are various optional patterns this works with. Positional
     // arguments can be used.
-    println!("{0}, this is {1}. {1}, this is {0}", "Alice", "Bob");
+    writeln!(out, "{0}, this is {1}. {1}, this is {0}", "Alice", "Bob")?;
 
     // As can named arguments.
-    println!(
+    writeln!(
+        out,
         "{subject} {verb} {object}",
         object = "the lazy dog",
         subject = "the quick brown fox",
         verb = "jumps over"
-    );
+    )?;
 
     // Special formatting can be specified after a `:`.
-    println!(
+    writeln!(
+        out,
         "{} of {:b} people know binary, the other half doesn't",
         1, 2
-    );
+    )?;
 
     // You can right-align text with a specified width. This will output
     // "     1". 5 white spaces and a "1".
-    println!("{number:>width$}", number = 1, width = 6);
+    writeln!(out, "{number:>width$}", number = 1, width = 6)?;
 
     // You can pad numbers with extra zeroes. This will output "000001".
-    println!("{number:0>width$}", number = 1, width = 6);
+    writeln!(out, "{number:0>width$}", number = 1, width = 6)?;
 
     // Rust even checks to make sure the correct number of arguments are
     // used.
-    println!("My name is {0}, {1} {0}", "Bond", "James");
+    writeln!(out, "My name is {0}, {1} {0}", "Bond", "James")?;
     // FIXME ^ Add the missing argument: "James"
 
     // Create a structure named `Structure` which contains an `i32`.
@@ -42,8 +50,11 @@ fn main() {
 
     // However, custom types such as this structure require more complicated
     // handling. This will not work.
-    // println!("This struct `{}` won't print...", Structure(3));
-    // FIXME ^ Comment out this line.
+    // writeln!(out, "This struct `{}` won't print...", Structure(3))?;
+    // FIXME ^ Comment out this line (error[E0277])
 
-    println!("Pi is roughly {:.3}", core::f32::consts::PI);
+    writeln!(out, "Pi is roughly {:.3}", core::f32::consts::PI)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -0,0 +1,82 @@
+// The width, fill and alignment of a `Formatter` apply to a value as a
+// whole, while the precision and sign are meant for the numbers inside it.
+// A `Display` impl formats its numbers with `number`, puts them together in
+// a `String`, and writes that with `pad`:
+//
+//     let (x, y) = (number(f, self.x), number(f, self.y));
+//     pad(f, &format!("x: {}, y: {}", x, y))
+use std::fmt;
+
+// Write `s` padded to the width in `f`, using its fill and alignment. Each
+// line is padded on its own, so that multi-line output stays aligned.
+// Unlike `Formatter::pad`, this doesn't cut `s` short when a precision is
+// given: the precision is meant for the numbers inside.
+pub fn pad(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    let width = f.width().unwrap_or(0);
+    for (i, line) in s.split('\n').enumerate() {
+        if i != 0 {
+            writeln!(f)?;
+        }
+        let padding = width.saturating_sub(line.chars().count());
+        // Like strings, values are aligned to the left by default
+        let (before, after) = match f.align() {
+            Some(fmt::Alignment::Right) => (padding, 0),
+            Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+            Some(fmt::Alignment::Left) | None => (0, padding),
+        };
+        let fill = f.fill();
+        for _ in 0..before {
+            write!(f, "{}", fill)?;
+        }
+        write!(f, "{}", line)?;
+        for _ in 0..after {
+            write!(f, "{}", fill)?;
+        }
+    }
+    Ok(())
+}
+
+// Format a single number with the precision and sign options of `f`
+pub fn number<T: fmt::Display>(f: &fmt::Formatter, x: T) -> String {
+    match (f.sign_plus(), f.precision()) {
+        (true, Some(precision)) => format!("{:+.*}", precision, x),
+        (true, None) => format!("{:+}", x),
+        (false, Some(precision)) => format!("{:.*}", precision, x),
+        (false, None) => format!("{}", x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pair(f64, f64);
+
+    impl fmt::Display for Pair {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let (a, b) = (number(f, self.0), number(f, self.1));
+            if f.alternate() {
+                pad(f, &format!("{}\n{}", a, b))
+            } else {
+                pad(f, &format!("({}, {})", a, b))
+            }
+        }
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(format!("{:12}|", Pair(1.0, 2.5)), "(1, 2.5)    |");
+        assert_eq!(format!("{:>12}", Pair(1.0, 2.5)), "    (1, 2.5)");
+        assert_eq!(format!("{:*^12}", Pair(1.0, 2.5)), "**(1, 2.5)**");
+        assert_eq!(format!("{:>#4}", Pair(1.0, 2.5)), "   1\n 2.5");
+        // A width smaller than the value changes nothing
+        assert_eq!(format!("{:3}", Pair(1.0, 2.5)), "(1, 2.5)");
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(format!("{:.2}", Pair(1.0, -2.5)), "(1.00, -2.50)");
+        assert_eq!(format!("{:+}", Pair(1.0, -2.5)), "(+1, -2.5)");
+        assert_eq!(format!("{:+.0}", Pair(1.0, 2.5)), "(+1, +2)");
+    }
+}
@@ -0,0 +1,517 @@
+// Parsing of `City` coordinates and great-circle calculations between them.
+// Every calculation is done in `f64`, with angles in degrees and distances
+// in metres.
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::City;
+
+// Mean radius of the earth, used by the spherical formulas
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+// The WGS-84 ellipsoid, used by Vincenty's formula
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    // Latitude, positive to the north
+    pub lat: f64,
+    // Longitude, positive to the east
+    pub lon: f64,
+}
+
+impl City {
+    pub fn coordinates(&self) -> Coordinates {
+        Coordinates {
+            lat: self.lat,
+            lon: self.lon,
+        }
+    }
+}
+
+// Bring a longitude back into `-180.0..=180.0`
+fn wrap_longitude(lon: f64) -> f64 {
+    (lon + 540.0).rem_euclid(360.0) - 180.0
+}
+
+impl Coordinates {
+    // Great-circle distance on a spherical earth. Quick, and within about
+    // 0.5% of the true distance.
+    pub fn haversine_distance(&self, other: &Coordinates) -> f64 {
+        let (phi1, phi2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_phi = phi2 - phi1;
+        let d_lambda = (other.lon - self.lon).to_radians();
+
+        let a =
+            (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().atan2((1.0 - a).sqrt())
+    }
+
+    // Distance on the WGS-84 ellipsoid, accurate to within a millimetre.
+    // The iteration may not converge for nearly antipodal points, in which
+    // case `None` is returned.
+    pub fn vincenty_distance(&self, other: &Coordinates) -> Option<f64> {
+        let b = WGS84_A * (1.0 - WGS84_F);
+        let l = (other.lon - self.lon).to_radians();
+        let u1 = ((1.0 - WGS84_F) * self.lat.to_radians().tan()).atan();
+        let u2 = ((1.0 - WGS84_F) * other.lat.to_radians().tan()).atan();
+        let (sin_u1, cos_u1) = u1.sin_cos();
+        let (sin_u2, cos_u2) = u2.sin_cos();
+
+        let mut lambda = l;
+        for _ in 0..200 {
+            let (sin_lambda, cos_lambda) = lambda.sin_cos();
+            let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+                + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+            .sqrt();
+            if sin_sigma == 0.0 {
+                // Both points are the same
+                return Some(0.0);
+            }
+            let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+            let sigma = sin_sigma.atan2(cos_sigma);
+            let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+            let cos2_alpha = 1.0 - sin_alpha.powi(2);
+            // Both points on the equator
+            let cos_2sigma_m = if cos2_alpha == 0.0 {
+                0.0
+            } else {
+                cos_sigma - 2.0 * sin_u1 * sin_u2 / cos2_alpha
+            };
+            let c = WGS84_F / 16.0 * cos2_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos2_alpha));
+
+            let previous = lambda;
+            lambda = l
+                + (1.0 - c)
+                    * WGS84_F
+                    * sin_alpha
+                    * (sigma
+                        + c * sin_sigma
+                            * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+
+            if (lambda - previous).abs() < 1e-12 {
+                let u_sq = cos2_alpha * (WGS84_A.powi(2) - b.powi(2)) / b.powi(2);
+                let big_a = 1.0
+                    + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+                let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+                let delta_sigma = big_b
+                    * sin_sigma
+                    * (cos_2sigma_m
+                        + big_b / 4.0
+                            * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                                - big_b / 6.0
+                                    * cos_2sigma_m
+                                    * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                    * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+                return Some(b * big_a * (sigma - delta_sigma));
+            }
+        }
+
+        None
+    }
+
+    // Compass direction to head in from `self` to reach `other` along a
+    // great circle, from 0 (north) clockwise to 360
+    pub fn initial_bearing(&self, other: &Coordinates) -> f64 {
+        let (phi1, phi2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lambda = (other.lon - self.lon).to_radians();
+
+        let y = d_lambda.sin() * phi2.cos();
+        let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    // The point halfway along the great circle between `self` and `other`
+    pub fn midpoint(&self, other: &Coordinates) -> Coordinates {
+        let (phi1, phi2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lambda = (other.lon - self.lon).to_radians();
+
+        let bx = phi2.cos() * d_lambda.cos();
+        let by = phi2.cos() * d_lambda.sin();
+        let phi = (phi1.sin() + phi2.sin()).atan2(((phi1.cos() + bx).powi(2) + by.powi(2)).sqrt());
+        let lambda = self.lon.to_radians() + by.atan2(phi1.cos() + bx);
+
+        Coordinates {
+            lat: phi.to_degrees(),
+            lon: wrap_longitude(lambda.to_degrees()),
+        }
+    }
+
+    // Where you end up after travelling `distance` metres along a great
+    // circle, starting out in direction `bearing`
+    pub fn destination(&self, bearing: f64, distance: f64) -> Coordinates {
+        let phi1 = self.lat.to_radians();
+        let theta = bearing.to_radians();
+        let delta = distance / EARTH_RADIUS;
+
+        let phi2 = (phi1.sin() * delta.cos() + phi1.cos() * delta.sin() * theta.cos()).asin();
+        let lambda = self.lon.to_radians()
+            + (theta.sin() * delta.sin() * phi1.cos()).atan2(delta.cos() - phi1.sin() * phi2.sin());
+
+        Coordinates {
+            lat: phi2.to_degrees(),
+            lon: wrap_longitude(lambda.to_degrees()),
+        }
+    }
+}
+
+// Same layout as `City`, without the name
+impl Display for Coordinates {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
+        let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
+        write!(
+            f,
+            "{:.3}°{} {:.3}°{}",
+            self.lat.abs(),
+            lat_c,
+            self.lon.abs(),
+            lon_c
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseCityError {
+    // There is no `name:` in front of the coordinates
+    MissingName,
+    // Latitude and longitude are needed, exactly one of each
+    WrongCoordinateCount(usize),
+    InvalidNumber(String),
+    // `N`/`S` on a longitude, `E`/`W` on a latitude, or any other letter
+    InvalidHemisphere(char),
+    // A sign and a hemisphere on the same coordinate
+    SignAndHemisphere,
+    // Latitude beyond 90°, longitude beyond 180°, or 60 or more minutes or
+    // seconds
+    OutOfRange(String),
+}
+
+impl Display for ParseCityError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseCityError::MissingName => write!(f, "expected `name: coordinates`"),
+            ParseCityError::WrongCoordinateCount(n) => {
+                write!(f, "expected a latitude and a longitude, found {} values", n)
+            }
+            ParseCityError::InvalidNumber(s) => write!(f, "invalid number `{}`", s),
+            ParseCityError::InvalidHemisphere(c) => write!(f, "unexpected hemisphere `{}`", c),
+            ParseCityError::SignAndHemisphere => {
+                write!(f, "a coordinate can't have both a sign and a hemisphere")
+            }
+            ParseCityError::OutOfRange(s) => write!(f, "`{}` is out of range", s),
+        }
+    }
+}
+
+impl error::Error for ParseCityError {}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    Latitude,
+    Longitude,
+}
+
+// Parse one coordinate in either of these forms:
+//
+// * decimal degrees: `-6.259722`, `6.260°W`
+// * degrees, minutes and seconds: `6°15'35"W`, `53° 20′ 52.0″ N`
+fn parse_angle(text: &str, axis: Axis) -> Result<f64, ParseCityError> {
+    let text = text.trim();
+    let (positive, negative, limit) = match axis {
+        Axis::Latitude => ('N', 'S', 90.0),
+        Axis::Longitude => ('E', 'W', 180.0),
+    };
+
+    // A trailing letter gives the hemisphere
+    let mut sign = 1.0;
+    let mut has_hemisphere = false;
+    let mut body = text;
+    if let Some(last) = text.chars().last().filter(char::is_ascii_alphabetic) {
+        let upper = last.to_ascii_uppercase();
+        if upper == negative {
+            sign = -1.0;
+        } else if upper != positive {
+            return Err(ParseCityError::InvalidHemisphere(last));
+        }
+        has_hemisphere = true;
+        body = text[..text.len() - 1].trim_end();
+    }
+
+    if let Some(rest) = body.strip_prefix('-') {
+        if has_hemisphere {
+            return Err(ParseCityError::SignAndHemisphere);
+        }
+        sign = -1.0;
+        body = rest;
+    } else if let Some(rest) = body.strip_prefix('+') {
+        if has_hemisphere {
+            return Err(ParseCityError::SignAndHemisphere);
+        }
+        body = rest;
+    }
+
+    // Collect the number in front of each of `°`, `'` and `"`. A number
+    // without a symbol after it is taken as degrees.
+    let mut parts = [None; 3];
+    let mut number = String::new();
+    let mut next = 0;
+    for c in body.chars().chain(std::iter::once('\0')) {
+        let unit = match c {
+            '°' => 0,
+            '\'' | '′' => 1,
+            '"' | '″' => 2,
+            '\0' if number.trim().is_empty() => break,
+            '\0' if next == 0 => 0,
+            c if c.is_whitespace() && number.trim().is_empty() => continue,
+            c if c.is_ascii_digit() || c == '.' => {
+                number.push(c);
+                continue;
+            }
+            _ => return Err(ParseCityError::InvalidNumber(text.to_owned())),
+        };
+        // Each unit may appear once, in order
+        if unit < next || number.is_empty() {
+            return Err(ParseCityError::InvalidNumber(text.to_owned()));
+        }
+        let value: f64 = number
+            .trim()
+            .parse()
+            .map_err(|_| ParseCityError::InvalidNumber(text.to_owned()))?;
+        parts[unit] = Some(value);
+        number.clear();
+        next = unit + 1;
+    }
+
+    let degrees = parts[0].ok_or_else(|| ParseCityError::InvalidNumber(text.to_owned()))?;
+    let minutes = parts[1].unwrap_or(0.0);
+    let seconds = parts[2].unwrap_or(0.0);
+    if minutes >= 60.0 || seconds >= 60.0 {
+        return Err(ParseCityError::OutOfRange(text.to_owned()));
+    }
+
+    let angle = degrees + minutes / 60.0 + seconds / 3600.0;
+    if angle > limit {
+        return Err(ParseCityError::OutOfRange(text.to_owned()));
+    }
+    Ok(sign * angle)
+}
+
+// Accepts the latitude and longitude separated by a comma, by the `N`/`S` of
+// the latitude, or by whitespace when neither is there
+impl FromStr for Coordinates {
+    type Err = ParseCityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (lat, lon) = if let Some(split) = s.split_once(',') {
+            split
+        } else if let Some(i) = s.find(|c: char| matches!(c.to_ascii_uppercase(), 'N' | 'S')) {
+            s.split_at(i + 1)
+        } else {
+            let values: Vec<&str> = s.split_whitespace().collect();
+            match values[..] {
+                [lat, lon] => (lat, lon),
+                _ => return Err(ParseCityError::WrongCoordinateCount(values.len())),
+            }
+        };
+
+        if lat.trim().is_empty() || lon.trim().is_empty() {
+            return Err(ParseCityError::WrongCoordinateCount(1));
+        }
+
+        Ok(Coordinates {
+            lat: parse_angle(lat, Axis::Latitude)?,
+            lon: parse_angle(lon, Axis::Longitude)?,
+        })
+    }
+}
+
+// Parses the output of `City`'s `Display`, such as
+// `Dublin: 53.348°N 6.260°W`, and the same with the coordinates in any of
+// the forms `Coordinates` accepts
+impl FromStr for City {
+    type Err = ParseCityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, coordinates) = s.split_once(':').ok_or(ParseCityError::MissingName)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ParseCityError::MissingName);
+        }
+        let Coordinates { lat, lon } = coordinates.parse()?;
+        Ok(City {
+            name: name.to_owned(),
+            lat,
+            lon,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    // Smallest angle between two directions
+    fn angle_difference(a: f64, b: f64) -> f64 {
+        let d = (a - b).rem_euclid(360.0);
+        d.min(360.0 - d)
+    }
+
+    fn city(s: &str) -> City {
+        s.parse().unwrap()
+    }
+
+    fn dublin() -> Coordinates {
+        Coordinates {
+            lat: 53.347778,
+            lon: -6.259722,
+        }
+    }
+
+    fn oslo() -> Coordinates {
+        Coordinates {
+            lat: 59.95,
+            lon: 10.75,
+        }
+    }
+
+    fn vancouver() -> Coordinates {
+        Coordinates {
+            lat: 49.25,
+            lon: -123.1,
+        }
+    }
+
+    #[test]
+    fn test_parse_display_output() {
+        let dublin = city("Dublin: 53.348°N 6.260°W");
+        assert_eq!(dublin.name, "Dublin");
+        assert_eq!(dublin.lat, 53.348);
+        assert_eq!(dublin.lon, -6.26);
+        assert_eq!(dublin.to_string(), "Dublin: 53.348°N 6.260°W");
+
+        let original = City {
+            name: "Vancouver".to_owned(),
+            lat: 49.25,
+            lon: -123.1,
+        };
+        assert_eq!(
+            city(&original.to_string()).to_string(),
+            original.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_decimal_and_dms() {
+        let decimal = city("Dublin: 53.347778, -6.259722");
+        assert_eq!(decimal.coordinates(), dublin());
+
+        let spaces = city("Dublin: 53.347778 -6.259722");
+        assert_eq!(spaces.coordinates(), dublin());
+
+        let dms = city("Dublin: 53°20'52\"N 6°15'35\"W");
+        assert!((dms.lat - 53.347778).abs() < 1e-4);
+        assert!((dms.lon + 6.259722).abs() < 1e-4);
+
+        let primes = city("Sydney: 33° 52′ 4″ S, 151° 12′ 36″ E");
+        assert!((primes.lat + 33.867778).abs() < 1e-6);
+        assert!((primes.lon - 151.21).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "53.348°N 6.260°W".parse::<City>().err(),
+            Some(ParseCityError::MissingName)
+        );
+        assert_eq!(
+            "X: 53.348°E 6.260°W".parse::<City>().err(),
+            Some(ParseCityError::InvalidHemisphere('E'))
+        );
+        assert_eq!(
+            "X: -53.348°S, 6.260°W".parse::<City>().err(),
+            Some(ParseCityError::SignAndHemisphere)
+        );
+        assert_eq!(
+            "X: 91, 0".parse::<City>().err(),
+            Some(ParseCityError::OutOfRange("91".to_owned()))
+        );
+        assert_eq!(
+            "X: 10°60'N, 0".parse::<City>().err(),
+            Some(ParseCityError::OutOfRange("10°60'N".to_owned()))
+        );
+        assert_eq!(
+            "X: 1 2 3".parse::<City>().err(),
+            Some(ParseCityError::WrongCoordinateCount(3))
+        );
+        assert_eq!(
+            "X: 1.2.3, 4".parse::<City>().err(),
+            Some(ParseCityError::InvalidNumber("1.2.3".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_distances_between_sample_cities() {
+        // (from, to, haversine, vincenty), in metres
+        let cases = [
+            (dublin(), oslo(), 1_266_962.5, 1_270_478.2),
+            (dublin(), vancouver(), 7_162_720.5, 7_185_049.2),
+            (oslo(), vancouver(), 7_180_692.4, 7_203_924.5),
+        ];
+        for (from, to, haversine, vincenty) in cases {
+            assert!((from.haversine_distance(&to) - haversine).abs() < 1.0);
+            assert!((from.vincenty_distance(&to).unwrap() - vincenty).abs() < 1.0);
+            // Distance doesn't depend on the direction
+            assert!((to.haversine_distance(&from) - haversine).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_vincenty_reference() {
+        // Flinders Peak to Buninyong, the worked example from Vincenty's
+        // 1975 paper: 54,972.271 m
+        let flinders: Coordinates = "37°57'03.72030\"S, 144°25'29.52440\"E".parse().unwrap();
+        let buninyong: Coordinates = "37°39'10.15610\"S, 143°55'35.38390\"E".parse().unwrap();
+        let distance = flinders.vincenty_distance(&buninyong).unwrap();
+        assert!((distance - 54_972.271).abs() < 0.001);
+        assert_eq!(flinders.vincenty_distance(&flinders), Some(0.0));
+    }
+
+    #[test]
+    fn test_bearing_midpoint_destination() {
+        let (dublin, oslo) = (dublin(), oslo());
+        assert!(angle_difference(dublin.initial_bearing(&oslo), 47.8596) < 1e-4);
+        assert!(angle_difference(oslo.initial_bearing(&vancouver()), 328.5864) < 1e-4);
+
+        // Due north along a meridian
+        let north = Coordinates { lat: 0.0, lon: 0.0 }.destination(0.0, PI * EARTH_RADIUS / 2.0);
+        assert!((north.lat - 90.0).abs() < 1e-9);
+
+        // Travelling half the distance along the initial bearing reaches the
+        // midpoint
+        let midpoint = dublin.midpoint(&oslo);
+        let halfway = dublin.destination(
+            dublin.initial_bearing(&oslo),
+            dublin.haversine_distance(&oslo) / 2.0,
+        );
+        assert!((midpoint.lat - halfway.lat).abs() < 1e-9);
+        assert!((midpoint.lon - halfway.lon).abs() < 1e-9);
+        assert!(
+            (midpoint.haversine_distance(&dublin) - midpoint.haversine_distance(&oslo)).abs()
+                < 1e-6
+        );
+
+        // Longitudes wrap around the antimeridian
+        let east = Coordinates {
+            lat: 0.0,
+            lon: 179.0,
+        }
+        .destination(90.0, 2.0 * PI * EARTH_RADIUS / 360.0);
+        assert!(angle_difference(east.lon, 180.0) < 1e-9);
+        assert!((-180.0..=180.0).contains(&east.lon));
+    }
+}
@@ -0,0 +1,3 @@
+// Helpers for `Display` impls that honour the options of the `Formatter`.
+// They live in a library so that the other examples can use them too.
+pub mod flags;
@@ -1,27 +1,72 @@
 use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
 
-fn main() {
-    for city in [
+use formatting::flags::pad;
+
+mod geo;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
+    let cities = [
         City {
-            name: "Dublin",
+            name: "Dublin".to_owned(),
             lat: 53.347778,
             lon: -6.259722,
         },
         City {
-            name: "Oslo",
+            name: "Oslo".to_owned(),
             lat: 59.95,
             lon: 10.75,
         },
         City {
-            name: "Vancouver",
+            name: "Vancouver".to_owned(),
             lat: 49.25,
             lon: -123.1,
         },
-    ]
-    .iter()
-    {
-        println!("{}", *city);
+    ];
+    for city in cities.iter() {
+        writeln!(out, "{}", *city)?;
+    }
+
+    // The output of `Display` can be parsed back, as can decimal degrees and
+    // degrees, minutes and seconds
+    for text in [
+        "Dublin: 53.348°N 6.260°W",
+        "Oslo: 59.95, 10.75",
+        "Vancouver: 49°15'N 123°6'W",
+    ] {
+        match text.parse::<City>() {
+            Ok(city) => writeln!(out, "parsed {}", city)?,
+            Err(e) => writeln!(out, "{}: {}", text, e)?,
+        }
     }
+
+    // Great-circle calculations between each pair of cities
+    for (i, from) in cities.iter().enumerate() {
+        for to in &cities[i + 1..] {
+            let (a, b) = (from.coordinates(), to.coordinates());
+            writeln!(
+                out,
+                "{} to {}: {:.1} km (haversine), {:.1} km (Vincenty), bearing {:.1}°, midpoint {}",
+                from.name,
+                to.name,
+                a.haversine_distance(&b) / 1000.0,
+                a.vincenty_distance(&b).unwrap_or(f64::NAN) / 1000.0,
+                a.initial_bearing(&b),
+                a.midpoint(&b)
+            )?;
+        }
+    }
+    let dublin = cities[0].coordinates();
+    writeln!(
+        out,
+        "500 km east of Dublin is {}",
+        dublin.destination(90.0, 500_000.0)
+    )?;
+
     for color in [
         Color {
             red: 128,
@@ -43,35 +88,53 @@ fn main() {
     {
         // Switch this to use {} once you've added an implementation
         // for fmt::Display.
-        println!("{}", *color);
+        writeln!(out, "{}", *color)?;
     }
+
+    // Both impls respect the formatting options, which lines up columns
+    for city in cities.iter() {
+        writeln!(out, "|{:>30.2}|", city)?;
+    }
+    writeln!(out, "{:+#}", cities[2])
 }
 
 struct City {
-    name: &'static str,
+    name: String,
     // Latitude
-    lat: f32,
+    lat: f64,
     // Longitude
-    lon: f32,
+    lon: f64,
 }
 
 impl Display for City {
     // `f` is a buffer, and this method must write the formatted string into it
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
-        let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
-
-        // `write!` is like `format!`, but it will write the formatted string
-        // into a buffer (the first argument)
-        write!(
-            f,
-            "{}: {:.3}°{} {:.3}°{}",
-            self.name,
-            self.lat.abs(),
-            lat_c,
-            self.lon.abs(),
-            lon_c
-        )
+        // Three decimals unless the caller asks for another precision
+        let precision = f.precision().unwrap_or(3);
+
+        // `format!` is like `write!`, but it returns the formatted string
+        // instead of writing it into a buffer. The string is padded to the
+        // requested width at the end.
+        let (lat, lon) = if f.sign_plus() {
+            // `{:+}` shows signed degrees instead of hemispheres
+            (
+                format!("{:+.*}°", precision, self.lat),
+                format!("{:+.*}°", precision, self.lon),
+            )
+        } else {
+            let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
+            let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
+            (
+                format!("{:.*}°{}", precision, self.lat.abs(), lat_c),
+                format!("{:.*}°{}", precision, self.lon.abs(), lon_c),
+            )
+        };
+
+        if f.alternate() {
+            pad(f, &format!("{}:\n    {}\n    {}", self.name, lat, lon))
+        } else {
+            pad(f, &format!("{}: {} {}", self.name, lat, lon))
+        }
     }
 }
 
@@ -83,11 +146,130 @@ struct Color {
 }
 
 impl Display for Color {
+    // The channels are whole numbers without a sign, so precision and `{:+}`
+    // don't apply here. `{:#}` puts the hex code on a line of its own.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(
-            f,
-            "RGB ({0}, {1}, {2}) 0x{0:0>2X}{1:0>2X}{2:0>2X}",
-            self.red, self.green, self.blue
-        )
+        let separator = if f.alternate() { '\n' } else { ' ' };
+        let s = format!(
+            "RGB ({0}, {1}, {2}){3}0x{0:0>2X}{1:0>2X}{2:0>2X}",
+            self.red, self.green, self.blue, separator
+        );
+        pad(f, &s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dublin() -> City {
+        City {
+            name: "Dublin".to_owned(),
+            lat: 53.347778,
+            lon: -6.259722,
+        }
+    }
+
+    fn color() -> Color {
+        Color {
+            red: 0,
+            green: 3,
+            blue: 254,
+        }
+    }
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(format!("{}", dublin()), "Dublin: 53.348°N 6.260°W");
+        assert_eq!(format!("{}", color()), "RGB (0, 3, 254) 0x0003FE");
+    }
+
+    #[test]
+    fn test_width_fill_and_alignment() {
+        assert_eq!(format!("{:>26}", dublin()), "  Dublin: 53.348°N 6.260°W");
+        assert_eq!(format!("{:26}|", dublin()), "Dublin: 53.348°N 6.260°W  |");
+        assert_eq!(format!("{:=^28}", dublin()), "==Dublin: 53.348°N 6.260°W==");
+        assert_eq!(format!("{:>26}", color()), "  RGB (0, 3, 254) 0x0003FE");
+        assert_eq!(format!("{:.<25}", color()), "RGB (0, 3, 254) 0x0003FE.");
+    }
+
+    #[test]
+    fn test_precision() {
+        assert_eq!(format!("{:.1}", dublin()), "Dublin: 53.3°N 6.3°W");
+        assert_eq!(format!("{:.5}", dublin()), "Dublin: 53.34778°N 6.25972°W");
+        // The channels have no fractional part
+        assert_eq!(format!("{:.1}", color()), "RGB (0, 3, 254) 0x0003FE");
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(format!("{:+}", dublin()), "Dublin: +53.348° -6.260°");
+        assert_eq!(format!("{:+.1}", dublin()), "Dublin: +53.3° -6.3°");
+        assert_eq!(format!("{:+}", color()), "RGB (0, 3, 254) 0x0003FE");
+    }
+
+    #[test]
+    fn test_alternate() {
+        assert_eq!(
+            format!("{:#}", dublin()),
+            "Dublin:\n    53.348°N\n    6.260°W"
+        );
+        assert_eq!(format!("{:#}", color()), "RGB (0, 3, 254)\n0x0003FE");
+        assert_eq!(
+            format!("{:>#16}", color()),
+            " RGB (0, 3, 254)\n        0x0003FE"
+        );
+    }
+
+    // `#[derive(Display)]` from chapter 17 reproduces the impl of `City`. The
+    // hemispheres come from named arguments, which call `degrees` with the
+    // formatter `f` and the fields of `self`, as the derive allows.
+    mod derived {
+        use derive_macros::Display;
+        use std::fmt::Formatter;
+
+        // A coordinate, with the hemisphere, or as signed degrees with `{:+}`
+        fn degrees(f: &Formatter, x: f64, positive: char, negative: char) -> String {
+            let precision = f.precision().unwrap_or(3);
+            if f.sign_plus() {
+                format!("{:+.*}°", precision, x)
+            } else {
+                let c = if x >= 0.0 { positive } else { negative };
+                format!("{:.*}°{}", precision, x.abs(), c)
+            }
+        }
+
+        #[derive(Display)]
+        #[display(
+            "{name}: {lat} {lon}",
+            alternate = "{name}:\n    {lat}\n    {lon}",
+            lat = degrees(f, self.lat, 'N', 'S'),
+            lon = degrees(f, self.lon, 'E', 'W'),
+        )]
+        pub struct City {
+            pub name: String,
+            pub lat: f64,
+            pub lon: f64,
+        }
+    }
+
+    // Both values give the same output for each of the format strings
+    macro_rules! assert_same {
+        ($real:expr, $derived:expr, $($format:literal),+) => {$(
+            assert_eq!(format!($format, $real), format!($format, $derived), "{}", $format);
+        )+};
     }
+
+    #[test]
+    fn test_derived() {
+        let city = dublin();
+        let derived = derived::City {
+            name: city.name.clone(),
+            lat: city.lat,
+            lon: city.lon,
+        };
+        assert_same!(city, derived, "{}", "{:=^28}", "{:.1}", "{:+}", "{:>#12}");
+    }
+
+    testing::snapshot_test!(run);
 }
@@ -5,11 +5,20 @@
 // This code is editable, feel free to hack it!
 // You can always return to the original code by clicking the "Reset" button ->
 
+use std::io::{self, Write};
+
 // This is the main function
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Statements here are executed when the compiled binary is called
 
     // Print text to the console
-    println!("Hello World!");
-    println!("I'm a Rustacean!");
+    writeln!(out, "Hello World!")?;
+    writeln!(out, "I'm a Rustacean!")
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
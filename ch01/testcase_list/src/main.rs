@@ -1,9 +1,22 @@
 // Import (via `use`) the `fmt` module to make it available.
 use std::fmt;
+use std::io::{self, Write};
 
-fn main() {
+use formatting::flags::{number, pad};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let v = List(vec![1, 2, 3]);
-    println!("{}", v);
+    writeln!(out, "{}", v)?;
+
+    // Formatting options apply to the list as a whole: the width pads it,
+    // the sign goes to each element, and `{:#}` lists one element per line
+    writeln!(out, "[{:>25}]", v)?;
+    writeln!(out, "{:+}", v)?;
+    writeln!(out, "{:#}", v)
 }
 
 // Define a structure named `List` containing a `Vec`.
@@ -15,20 +28,82 @@ impl fmt::Display for List {
         // and create a reference to `vec`.
         let vec = &self.0;
 
-        write!(f, "[")?;
+        // The list is built in a `String` first, so that it can be padded
+        // as a whole afterwards.
+        let mut s = String::from("[");
 
         // Iterate over `v` in `vec` while enumerating the iteration
         // count in `count`.
         for (count, v) in vec.iter().enumerate() {
-            // For every element except the first, add a comma.
-            // Use the ? operator to return on errors.
-            if count != 0 {
-                write!(f, ", ")?;
+            if f.alternate() {
+                // `{:#}` puts every element on a line of its own.
+                s += &format!("\n    {}: {},", count, number(f, v));
+            } else {
+                // For every element except the first, add a comma.
+                if count != 0 {
+                    s += ", ";
+                }
+                s += &format!("{}: {}", count, number(f, v));
             }
-            write!(f, "{}: {}", count, v)?;
         }
 
-        // Close the opened bracket and return a fmt::Result value.
-        write!(f, "]")
+        if f.alternate() && !vec.is_empty() {
+            s += "\n";
+        }
+
+        // Close the opened bracket and pad the result.
+        s += "]";
+        pad(f, &s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(format!("{}", List(vec![1, 2, 3])), "[0: 1, 1: 2, 2: 3]");
+        assert_eq!(format!("{}", List(vec![])), "[]");
     }
+
+    #[test]
+    fn test_width_fill_and_alignment() {
+        let v = List(vec![1, -2]);
+        assert_eq!(format!("{:15}|", v), "[0: 1, 1: -2]  |");
+        assert_eq!(format!("{:>15}", v), "  [0: 1, 1: -2]");
+        assert_eq!(format!("{:.^17}", v), "..[0: 1, 1: -2]..");
+        assert_eq!(format!("{:2}", v), "[0: 1, 1: -2]");
+    }
+
+    #[test]
+    fn test_precision() {
+        // The elements are integers, so there is nothing to round, and
+        // unlike strings the list is not cut short either
+        assert_eq!(format!("{:.1}", List(vec![1, 2])), "[0: 1, 1: 2]");
+    }
+
+    #[test]
+    fn test_sign() {
+        // Only the elements get a sign, not their indices
+        assert_eq!(
+            format!("{:+}", List(vec![1, -2, 0])),
+            "[0: +1, 1: -2, 2: +0]"
+        );
+    }
+
+    #[test]
+    fn test_alternate() {
+        assert_eq!(
+            format!("{:#}", List(vec![1, 2])),
+            "[\n    0: 1,\n    1: 2,\n]"
+        );
+        assert_eq!(format!("{:#}", List(vec![])), "[]");
+        assert_eq!(
+            format!("{:>#8}", List(vec![1])),
+            "       [\n    0: 1,\n       ]"
+        );
+    }
+
+    testing::snapshot_test!(run);
 }
@@ -1,6 +1,11 @@
+use std::io::{self, Write};
 use std::mem;
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Fixed-size array (type signature is superfluous)
     let xs: [i32; 5] = [1, 2, 3, 4, 5];
 
@@ -8,32 +13,37 @@ fn main() {
     let ys: [i32; 500] = [0; 500];
 
     // Indexing starts at 0
-    println!("first element of the array: {}", xs[0]);
-    println!("second element of the array: {}", xs[1]);
+    writeln!(out, "first element of the array: {}", xs[0])?;
+    writeln!(out, "second element of the array: {}", xs[1])?;
 
     // `len` returns the count of elements in the array
-    println!("number of elements in array: {}", xs.len());
+    writeln!(out, "number of elements in array: {}", xs.len())?;
 
     // Arrays are stack allocated
-    println!("array occupies {} bytes", mem::size_of_val(&xs));
+    writeln!(out, "array occupies {} bytes", mem::size_of_val(&xs))?;
 
     // Arrays can be automatically borrowed as slices
-    println!("borrow the whole array as a slice");
-    analyze_slice(&xs);
+    writeln!(out, "borrow the whole array as a slice")?;
+    analyze_slice(out, &xs)?;
 
     // Slices can point to a section of an array
     // They are of the form [starting_index..ending_index]
     // starting_index is the first position in the slice
     // ending_index is one more than the last position in the slice
-    println!("borrow a section of the array as a slice");
-    analyze_slice(&ys[1..4]);
+    writeln!(out, "borrow a section of the array as a slice")?;
+    analyze_slice(out, &ys[1..4])?;
 
     // Out of bound indexing causes compile error
-    // println!("{}", xs[5]);
+    // writeln!(out, "{}", xs[5])?;
+
+    Ok(())
 }
 
 // This function borrows a slice
-fn analyze_slice(slice: &[i32]) {
-    println!("first element of the slice: {}", slice[0]);
-    println!("the slice has {} elements", slice.len());
+fn analyze_slice(out: &mut impl Write, slice: &[i32]) -> io::Result<()> {
+    writeln!(out, "first element of the slice: {}", slice[0])?;
+    writeln!(out, "the slice has {} elements", slice.len())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,48 +1,82 @@
-use std::fmt;
+use std::io::{self, Write};
 
-fn main() {
+use matrix::Matrix;
+
+mod matrix;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // A tuple with a bunch of different types
     let long_tuple = (
         1u8, 2u16, 3u32, 4u64, -1i8, -2i16, -3i32, -4i64, 0.1f32, 0.2f64, 'a', true,
     );
 
     // Values can be extracted from the tuple using tuple indexing
-    println!("long tuple first value: {}", long_tuple.0);
-    println!("long tuple second value: {}", long_tuple.1);
+    writeln!(out, "long tuple first value: {}", long_tuple.0)?;
+    writeln!(out, "long tuple second value: {}", long_tuple.1)?;
 
     // Tuples can be tuple members
     let tuple_of_tuples = ((1u8, 2u16, 2u32), (4u64, -1i8), -2i16);
 
     // Tuples are printable
-    println!("tuple of tuples: {:?}", tuple_of_tuples);
+    writeln!(out, "tuple of tuples: {:?}", tuple_of_tuples)?;
 
     // But long Tuples cannot be printed
     let too_long_tuple = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13);
-    // println!("too long tuple: {:?}", too_long_tuple);
-    // TODO ^ Uncomment the above 2 lines to see the compiler error
+    // writeln!(out, "too long tuple: {:?}", too_long_tuple)?;
+    // TODO ^ Uncomment the above 2 lines to see the compiler error (error[E0277])
 
     let pair = (1, true);
-    println!("pair is {:?}", pair);
+    writeln!(out, "pair is {:?}", pair)?;
 
-    println!("the reversed pair is {:?}", reverse(pair));
+    writeln!(out, "the reversed pair is {:?}", reverse(pair))?;
 
     // To create one element tuples, the comma is required to tell them apart
     // from a literal surrounded by parentheses
-    println!("one element tuple: {:?}", (5u32,));
-    println!("just an integer: {:?}", (5u32));
+    writeln!(out, "one element tuple: {:?}", (5u32,))?;
+    writeln!(out, "just an integer: {:?}", (5u32))?;
 
     //tuples can be destructured to create bindings
     let tuple = (1, "hello", 4.5, true);
 
     let (a, b, c, d) = tuple;
-    println!("{:?}, {:?}, {:?}, {:?}", a, b, c, d);
-
-    let matrix = Matrix(1.1, 1.2, 2.1, 2.2);
-    println!("{:?}", matrix);
-    println!("{}", matrix);
-
-    let matrix_t = transpose(matrix);
-    println!("{}", matrix_t);
+    writeln!(out, "{:?}, {:?}, {:?}, {:?}", a, b, c, d)?;
+
+    // The matrix for the activity. Its dimensions are part of its type:
+    // this one is a `Matrix<f32, 2, 2>`.
+    let matrix = Matrix::new([[1.1f32, 1.2], [2.1, 2.2]]);
+    writeln!(out, "{:?}", matrix)?;
+    writeln!(out, "{}", matrix)?;
+
+    let matrix_t = matrix.transpose();
+    writeln!(out, "{}", matrix_t)?;
+
+    // Formatting options are passed on: the precision and sign go to every
+    // element, the width pads every row, and `{:#}` aligns the columns
+    let matrix = Matrix::new([[1.0, -12.5], [100.25, 2.0]]);
+    writeln!(out, "{:+.1}", matrix)?;
+    writeln!(out, "{:>#20}", matrix)?;
+
+    // A 2x3 matrix times a 3x2 matrix is a 2x2 matrix. Multiplying two 2x3
+    // matrices would not compile.
+    let a = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+    let b = Matrix::new([[7, 8], [9, 10], [11, 12]]);
+    writeln!(out, "{}", a * b)?;
+    writeln!(out, "{}", a + a)?;
+
+    // Floating point square matrices can be inverted, and used to solve
+    // linear systems
+    let m = Matrix::new([[2.0, 1.0, 1.0], [1.0, 3.0, 2.0], [1.0, 0.0, 0.0]]);
+    writeln!(out, "determinant: {}", m.determinant())?;
+    if let Some(inverse) = m.inverse() {
+        writeln!(out, "inverse:\n{:#.3}", inverse)?;
+        writeln!(out, "product:\n{:#.1}", m * inverse)?;
+    }
+    writeln!(out, "solution: {:?}", m.solve([4.0, 5.0, 6.0]))?;
+    writeln!(out, "identity:\n{}", Matrix::<u8, 3, 3>::identity())
 }
 
 // Tuples can be used as function arguments and as return values
@@ -53,16 +87,5 @@ fn reverse(pair: (i32, bool)) -> (bool, i32) {
     (boolean, integer)
 }
 
-// The following struct is for the activity.
-#[derive(Debug)]
-struct Matrix(f32, f32, f32, f32);
-
-impl fmt::Display for Matrix {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "( {} {} )\n( {} {} )", self.0, self.1, self.2, self.3)
-    }
-}
-
-fn transpose(matrix: Matrix) -> Matrix {
-    Matrix(matrix.0, matrix.2, matrix.1, matrix.3)
-}
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -0,0 +1,416 @@
+// A matrix with `R` rows and `C` columns, with the dimensions checked at
+// compile time: adding matrices of different shapes, or multiplying them
+// when the columns of one don't match the rows of the other, doesn't build.
+use std::fmt;
+use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
+
+use formatting::flags::{number, pad};
+
+// The element types a `Matrix` can hold
+pub trait Number:
+    Copy + PartialOrd + fmt::Display + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+// Element types that can be divided without losing the remainder, which
+// the LU decomposition relies on
+pub trait Float: Number + Div<Output = Self> + Neg<Output = Self> {
+    const EPSILON: Self;
+
+    fn abs(self) -> Self;
+    fn from_usize(n: usize) -> Self;
+}
+
+macro_rules! impl_number {
+    ($zero:expr, $one:expr; $($t:ty),*) => {
+        $(
+            impl Number for $t {
+                const ZERO: Self = $zero;
+                const ONE: Self = $one;
+            }
+        )*
+    };
+}
+
+impl_number!(0, 1; i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_number!(0.0, 1.0; f32, f64);
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl Float for $t {
+                const EPSILON: Self = <$t>::EPSILON;
+
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                fn from_usize(n: usize) -> Self {
+                    n as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+// The elements are stored row by row
+#[derive(Clone, Copy, PartialEq)]
+pub struct Matrix<T, const R: usize, const C: usize>(pub [[T; C]; R]);
+
+impl<T: Number, const R: usize, const C: usize> Matrix<T, R, C> {
+    pub fn new(rows: [[T; C]; R]) -> Self {
+        Matrix(rows)
+    }
+
+    pub fn zero() -> Self {
+        Matrix([[T::ZERO; C]; R])
+    }
+
+    // Rows become columns; note how the dimensions swap in the type too
+    pub fn transpose(&self) -> Matrix<T, C, R> {
+        let mut out = Matrix::zero();
+        for (i, row) in self.0.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                out.0[j][i] = x;
+            }
+        }
+        out
+    }
+}
+
+impl<T: Number, const N: usize> Matrix<T, N, N> {
+    pub fn identity() -> Self {
+        let mut out = Matrix::zero();
+        for i in 0..N {
+            out.0[i][i] = T::ONE;
+        }
+        out
+    }
+}
+
+// An LU decomposition with partial pivoting, `P * A = L * U`. `L` (below the
+// diagonal, with an implicit diagonal of ones) and `U` (on and above it)
+// are stored together in `lu`.
+struct Lu<T, const N: usize> {
+    lu: [[T; N]; N],
+    // Row `i` of `P * A` is row `perm[i]` of `A`
+    perm: [usize; N],
+    // Whether `P` swaps an odd number of rows
+    odd: bool,
+}
+
+impl<T: Float, const N: usize> Matrix<T, N, N> {
+    // Returns `None` if the matrix is singular, that is when some pivot is
+    // zero up to rounding error
+    fn lu(&self) -> Option<Lu<T, N>> {
+        let mut lu = self.0;
+        let mut perm = [0; N];
+        for (i, p) in perm.iter_mut().enumerate() {
+            *p = i;
+        }
+        let mut odd = false;
+
+        // Pivots smaller than this are taken as zero
+        let largest = lu
+            .iter()
+            .flatten()
+            .fold(T::ZERO, |max, &x| if x.abs() > max { x.abs() } else { max });
+        let tolerance = T::EPSILON * T::from_usize(N) * largest;
+
+        for k in 0..N {
+            // Partial pivoting: swap the row with the largest entry in
+            // column `k` into place, which keeps rounding errors small
+            let pivot_row = (k..N)
+                .max_by(|&a, &b| {
+                    lu[a][k]
+                        .abs()
+                        .partial_cmp(&lu[b][k].abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(k);
+            if lu[pivot_row][k].abs() <= tolerance {
+                return None;
+            }
+            if pivot_row != k {
+                lu.swap(pivot_row, k);
+                perm.swap(pivot_row, k);
+                odd = !odd;
+            }
+
+            let pivot = lu[k];
+            for row in lu[k + 1..].iter_mut() {
+                let factor = row[k] / pivot[k];
+                row[k] = factor;
+                for j in k + 1..N {
+                    row[j] = row[j] - factor * pivot[j];
+                }
+            }
+        }
+
+        Some(Lu { lu, perm, odd })
+    }
+
+    pub fn determinant(&self) -> T {
+        match self.lu() {
+            Some(Lu { lu, odd, .. }) => {
+                let product = (0..N).fold(T::ONE, |product, i| product * lu[i][i]);
+                if odd {
+                    -product
+                } else {
+                    product
+                }
+            }
+            None => T::ZERO,
+        }
+    }
+
+    // Solve `self * x = b` for `x`, or `None` if the matrix is singular
+    pub fn solve(&self, b: [T; N]) -> Option<[T; N]> {
+        self.lu().map(|lu| lu.solve(b))
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let lu = self.lu()?;
+        // Column `j` of the inverse solves `self * x = e_j`
+        let identity = Matrix::<T, N, N>::identity();
+        let mut columns = Matrix::zero();
+        for (j, e) in identity.0.iter().enumerate() {
+            columns.0[j] = lu.solve(*e);
+        }
+        Some(columns.transpose())
+    }
+}
+
+impl<T: Float, const N: usize> Lu<T, N> {
+    fn solve(&self, b: [T; N]) -> [T; N] {
+        // Forward substitution with `L`, on the permuted right-hand side
+        let mut x = [T::ZERO; N];
+        for i in 0..N {
+            let sum = (0..i).fold(b[self.perm[i]], |sum, j| sum - self.lu[i][j] * x[j]);
+            x[i] = sum;
+        }
+        // Back substitution with `U`
+        for i in (0..N).rev() {
+            let sum = (i + 1..N).fold(x[i], |sum, j| sum - self.lu[i][j] * x[j]);
+            x[i] = sum / self.lu[i][i];
+        }
+        x
+    }
+}
+
+impl<T, const R: usize, const C: usize> Index<(usize, usize)> for Matrix<T, R, C> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        &self.0[row][col]
+    }
+}
+
+impl<T, const R: usize, const C: usize> IndexMut<(usize, usize)> for Matrix<T, R, C> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        &mut self.0[row][col]
+    }
+}
+
+impl<T: Number, const R: usize, const C: usize> Add for Matrix<T, R, C> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        for (row, rhs_row) in self.0.iter_mut().zip(rhs.0.iter()) {
+            for (x, &y) in row.iter_mut().zip(rhs_row.iter()) {
+                *x = *x + y;
+            }
+        }
+        self
+    }
+}
+
+impl<T: Number, const R: usize, const C: usize> Sub for Matrix<T, R, C> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self {
+        for (row, rhs_row) in self.0.iter_mut().zip(rhs.0.iter()) {
+            for (x, &y) in row.iter_mut().zip(rhs_row.iter()) {
+                *x = *x - y;
+            }
+        }
+        self
+    }
+}
+
+// An `R`x`C` matrix can only be multiplied by one with `C` rows
+impl<T: Number, const R: usize, const C: usize, const K: usize> Mul<Matrix<T, C, K>>
+    for Matrix<T, R, C>
+{
+    type Output = Matrix<T, R, K>;
+
+    fn mul(self, rhs: Matrix<T, C, K>) -> Matrix<T, R, K> {
+        let mut out = Matrix::zero();
+        for (out_row, row) in out.0.iter_mut().zip(self.0.iter()) {
+            for (j, out) in out_row.iter_mut().enumerate() {
+                *out = row
+                    .iter()
+                    .zip(rhs.0.iter())
+                    .fold(T::ZERO, |sum, (&x, rhs_row)| sum + x * rhs_row[j]);
+            }
+        }
+        out
+    }
+}
+
+// Every row is written as `( a b c )`, on a line of its own. The precision
+// and sign go to every element, the width pads every row, and `{:#}` aligns
+// the columns by padding each element to the widest in its column.
+impl<T: Number, const R: usize, const C: usize> fmt::Display for Matrix<T, R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells = self.0.map(|row| row.map(|x| number(f, x)));
+
+        let mut widths = [0; C];
+        if f.alternate() {
+            for row in &cells {
+                for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+        }
+
+        let rows: Vec<String> = cells
+            .iter()
+            .map(|row| {
+                let mut line = String::from("(");
+                for (cell, &width) in row.iter().zip(widths.iter()) {
+                    line += &format!(" {:>1$}", cell, width);
+                }
+                line + " )"
+            })
+            .collect();
+        pad(f, &rows.join("\n"))
+    }
+}
+
+// All the elements in one flat list, row after row, as in
+// `Matrix(1.1, 1.2, 2.1, 2.2)`: the way the 2x2 tuple struct printed
+impl<T: fmt::Debug, const R: usize, const C: usize> fmt::Debug for Matrix<T, R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut tuple = f.debug_tuple("Matrix");
+        for elem in self.0.iter().flatten() {
+            tuple.field(elem);
+        }
+        tuple.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> Matrix<f64, 2, 2> {
+        Matrix::new([[1.0, -12.5], [100.25, 2.0]])
+    }
+
+    fn close<const R: usize, const C: usize>(a: Matrix<f64, R, C>, b: Matrix<f64, R, C>) -> bool {
+        a.0.iter()
+            .flatten()
+            .zip(b.0.iter().flatten())
+            .all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(
+            format!("{}", Matrix::new([[1.1f32, 1.2], [2.1, 2.2]])),
+            "( 1.1 1.2 )\n( 2.1 2.2 )"
+        );
+        assert_eq!(format!("{}", matrix()), "( 1 -12.5 )\n( 100.25 2 )");
+        assert_eq!(format!("{}", Matrix::new([[1, 2, 3]])), "( 1 2 3 )");
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(
+            format!("{:?}", Matrix::new([[1.1f32, 1.2], [2.1, 2.2]])),
+            "Matrix(1.1, 1.2, 2.1, 2.2)"
+        );
+        assert_eq!(format!("{:?}", Matrix::new([[1, 2, 3]])), "Matrix(1, 2, 3)");
+    }
+
+    #[test]
+    fn test_width_fill_and_alignment() {
+        let m = Matrix::new([[1.1, 1.2], [2.1, 2.2]]);
+        // Each row is padded on its own
+        assert_eq!(format!("{:13}|", m), "( 1.1 1.2 )  \n( 2.1 2.2 )  |");
+        assert_eq!(format!("{:>13}", m), "  ( 1.1 1.2 )\n  ( 2.1 2.2 )");
+        assert_eq!(format!("{:~^13}", m), "~( 1.1 1.2 )~\n~( 2.1 2.2 )~");
+    }
+
+    #[test]
+    fn test_precision() {
+        assert_eq!(
+            format!("{:.2}", matrix()),
+            "( 1.00 -12.50 )\n( 100.25 2.00 )"
+        );
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(format!("{:+}", matrix()), "( +1 -12.5 )\n( +100.25 +2 )");
+    }
+
+    #[test]
+    fn test_alternate() {
+        assert_eq!(
+            format!("{:#}", matrix()),
+            "(      1 -12.5 )\n( 100.25     2 )"
+        );
+        assert_eq!(
+            format!("{:>+#18.1}", matrix()),
+            "  (   +1.0 -12.5 )\n  ( +100.2  +2.0 )"
+        );
+    }
+
+    #[test]
+    fn test_add_mul_transpose() {
+        let a = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+        let b = Matrix::new([[7, 8], [9, 10], [11, 12]]);
+        assert_eq!(a * b, Matrix::new([[58, 64], [139, 154]]));
+        assert_eq!(a.transpose(), Matrix::new([[1, 4], [2, 5], [3, 6]]));
+        assert_eq!(a + a, Matrix::new([[2, 4, 6], [8, 10, 12]]));
+        assert_eq!(a - a, Matrix::zero());
+        assert_eq!(Matrix::<i32, 3, 3>::identity() * b, b);
+        assert_eq!(a[(1, 2)], 6);
+    }
+
+    #[test]
+    fn test_determinant() {
+        assert_eq!(Matrix::new([[3.0, 8.0], [4.0, 6.0]]).determinant(), -14.0);
+        let m = Matrix::new([[6.0, 1.0, 1.0], [4.0, -2.0, 5.0], [2.0, 8.0, 7.0]]);
+        assert!((m.determinant() + 306.0).abs() < 1e-9);
+        // Needs a row swap to find a non-zero pivot
+        assert_eq!(Matrix::new([[0.0, 1.0], [1.0, 0.0]]).determinant(), -1.0);
+        // Singular, even though rounding makes the last pivot tiny instead
+        // of zero
+        let singular = Matrix::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        assert_eq!(singular.determinant(), 0.0);
+        assert_eq!(singular.inverse(), None);
+    }
+
+    #[test]
+    fn test_inverse_and_solve() {
+        let m = Matrix::new([[2.0, 1.0, 1.0], [1.0, 3.0, 2.0], [1.0, 0.0, 0.0]]);
+        let inverse = m.inverse().unwrap();
+        assert!(close(m * inverse, Matrix::identity()));
+        assert!(close(inverse * m, Matrix::identity()));
+
+        // 2x + y + z = 4, x + 3y + 2z = 5, x = 6
+        let x = m.solve([4.0, 5.0, 6.0]).unwrap();
+        assert!(close(Matrix::new([x]), Matrix::new([[6.0, 15.0, -23.0]])));
+        assert_eq!(
+            Matrix::new([[1.0, 2.0], [2.0, 4.0]]).solve([1.0, 2.0]),
+            None
+        );
+    }
+}
@@ -1,21 +1,32 @@
+use std::io::{self, Write};
+
 // Globals are declared outside all other scopes.
 static LANGUAGE: &str = "Rust";
 const THRESHOLD: i32 = 10;
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let n = 16;
 
     // Access constant in the main thread
-    println!("This is {}", LANGUAGE);
-    println!("The threshold is {}", THRESHOLD);
-    println!("{} is {}", n, if is_big(n) { "big" } else { "small" });
+    writeln!(out, "This is {}", LANGUAGE)?;
+    writeln!(out, "The threshold is {}", THRESHOLD)?;
+    writeln!(out, "{} is {}", n, if is_big(n) { "big" } else { "small" })?;
 
     // Error! Cannot modify a `const`.
     // THRESHOLD = 5;
-    // FIXME ^ Comment out this line
+    // FIXME ^ Comment out this line (error[E0070])
+
+    Ok(())
 }
 
 fn is_big(n: i32) -> bool {
     // Access constant in some function
     n > THRESHOLD
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -0,0 +1,424 @@
+// A line based text format for `WebEvent`s, one event per line:
+//
+//     pageload
+//     keypress x
+//     keypress ' '
+//     paste "my text"
+//     click 20 80
+//     pageunload
+//
+// A line may start with `@<milliseconds>`, the time since the start of the
+// recording at which the event happened. Empty lines and lines starting with
+// `#` are ignored.
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::WebEvent;
+
+// An event together with the moment it was recorded, if known
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recorded {
+    pub at: Option<Duration>,
+    pub event: WebEvent,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnknownEvent(String),
+    MissingArgument(&'static str),
+    InvalidInteger(String),
+    InvalidTimestamp(String),
+    TimestampOutOfOrder,
+    // `keypress` takes exactly one character
+    NotOneChar(String),
+    InvalidEscape(char),
+    UnterminatedString,
+    TrailingInput(String),
+}
+
+// Line and column are counted from 1, columns in characters
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnknownEvent(name) => write!(f, "unknown event `{}`", name),
+            ErrorKind::MissingArgument(what) => write!(f, "missing {}", what),
+            ErrorKind::InvalidInteger(s) => write!(f, "invalid integer `{}`", s),
+            ErrorKind::InvalidTimestamp(s) => write!(f, "invalid timestamp `{}`", s),
+            ErrorKind::TimestampOutOfOrder => {
+                write!(f, "timestamp is earlier than the previous one")
+            }
+            ErrorKind::NotOneChar(s) => write!(f, "expected a single character, found `{}`", s),
+            ErrorKind::InvalidEscape(c) => write!(f, "invalid escape `\\{}`", c),
+            ErrorKind::UnterminatedString => write!(f, "unterminated quoted string"),
+            ErrorKind::TrailingInput(s) => write!(f, "unexpected `{}` after event", s),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+impl error::Error for ParseError {}
+
+// Walks over the characters of a single line, keeping track of the column
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Cursor {
+    fn new(line: &str, number: usize) -> Cursor {
+        Cursor {
+            chars: line.chars().collect(),
+            pos: 0,
+            line: number,
+        }
+    }
+
+    fn column(&self) -> usize {
+        self.pos + 1
+    }
+
+    fn error_at(&self, column: usize, kind: ErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column,
+            kind,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    // Read up to the next whitespace, returning the starting column too
+    fn word(&mut self) -> (usize, String) {
+        self.skip_whitespace();
+        let column = self.column();
+        let mut word = String::new();
+        while let Some(c) = self.peek().filter(|c| !c.is_whitespace()) {
+            word.push(c);
+            self.pos += 1;
+        }
+        (column, word)
+    }
+
+    // Read a string delimited by `quote`, resolving escapes. The cursor must
+    // be on the opening quote.
+    fn quoted(&mut self, quote: char) -> Result<String, ParseError> {
+        let start = self.column();
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| self.error_at(start, ErrorKind::UnterminatedString))?;
+            self.pos += 1;
+            match c {
+                '\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error_at(start, ErrorKind::UnterminatedString))?;
+                    text.push(match escape {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        '\\' | '"' | '\'' => escape,
+                        _ => {
+                            return Err(
+                                self.error_at(self.column() - 1, ErrorKind::InvalidEscape(escape))
+                            )
+                        }
+                    });
+                    self.pos += 1;
+                }
+                c if c == quote => return Ok(text),
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn integer(&mut self, what: &'static str) -> Result<i64, ParseError> {
+        let (column, word) = self.word();
+        if word.is_empty() {
+            return Err(self.error_at(column, ErrorKind::MissingArgument(what)));
+        }
+        word.parse()
+            .map_err(|_| self.error_at(column, ErrorKind::InvalidInteger(word)))
+    }
+
+    fn key(&mut self) -> Result<char, ParseError> {
+        self.skip_whitespace();
+        let column = self.column();
+        let key = match self.peek() {
+            Some('\'') => self.quoted('\'')?,
+            Some(_) => self.word().1,
+            None => return Err(self.error_at(column, ErrorKind::MissingArgument("key"))),
+        };
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(self.error_at(column, ErrorKind::NotOneChar(key))),
+        }
+    }
+
+    fn text(&mut self) -> Result<String, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => self.quoted('"'),
+            _ => Err(self.error_at(self.column(), ErrorKind::MissingArgument("quoted text"))),
+        }
+    }
+
+    fn event(&mut self) -> Result<WebEvent, ParseError> {
+        let (column, name) = self.word();
+        let event = match name.as_str() {
+            "pageload" => WebEvent::PageLoad,
+            "pageunload" => WebEvent::PageUnload,
+            "keypress" => WebEvent::KeyPress(self.key()?),
+            "paste" => WebEvent::Paste(self.text()?),
+            "click" => WebEvent::Click {
+                x: self.integer("x coordinate")?,
+                y: self.integer("y coordinate")?,
+            },
+            "" => return Err(self.error_at(column, ErrorKind::MissingArgument("event"))),
+            _ => return Err(self.error_at(column, ErrorKind::UnknownEvent(name))),
+        };
+
+        let (column, rest) = self.word();
+        if rest.is_empty() {
+            Ok(event)
+        } else {
+            Err(self.error_at(column, ErrorKind::TrailingInput(rest)))
+        }
+    }
+
+    fn recorded(&mut self) -> Result<Recorded, ParseError> {
+        self.skip_whitespace();
+        let at = if self.peek() == Some('@') {
+            let (column, word) = self.word();
+            let millis = word[1..]
+                .parse()
+                .map_err(|_| self.error_at(column, ErrorKind::InvalidTimestamp(word)))?;
+            Some(Duration::from_millis(millis))
+        } else {
+            None
+        };
+        let event = self.event()?;
+        Ok(Recorded { at, event })
+    }
+}
+
+// Parses a single event, without timestamp
+impl FromStr for WebEvent {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Cursor::new(s, 1).event()
+    }
+}
+
+// Parse a whole recording. Timestamps, where given, may not go backwards.
+pub fn parse_log(input: &str) -> Result<Vec<Recorded>, ParseError> {
+    let mut session = Vec::new();
+    let mut last = Duration::ZERO;
+
+    for (index, line) in input.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let mut cursor = Cursor::new(line, index + 1);
+        let recorded = cursor.recorded()?;
+        if let Some(at) = recorded.at {
+            if at < last {
+                let column = line.chars().position(|c| c == '@').unwrap_or(0) + 1;
+                return Err(cursor.error_at(column, ErrorKind::TimestampOutOfOrder));
+            }
+            last = at;
+        }
+        session.push(recorded);
+    }
+
+    Ok(session)
+}
+
+// The inverse of `parse_log`
+pub fn write_log(session: &[Recorded]) -> String {
+    session
+        .iter()
+        .map(|recorded| format!("{}\n", recorded))
+        .collect()
+}
+
+fn write_escaped(f: &mut fmt::Formatter, text: &str, quote: char) -> fmt::Result {
+    write!(f, "{}", quote)?;
+    for c in text.chars() {
+        match c {
+            '\n' => write!(f, "\\n")?,
+            '\t' => write!(f, "\\t")?,
+            '\r' => write!(f, "\\r")?,
+            '\\' => write!(f, "\\\\")?,
+            c if c == quote => write!(f, "\\{}", c)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "{}", quote)
+}
+
+// Writes the event in the text format, so that `to_string().parse()` gives
+// back the same event
+impl fmt::Display for WebEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WebEvent::PageLoad => write!(f, "pageload"),
+            WebEvent::PageUnload => write!(f, "pageunload"),
+            WebEvent::KeyPress(c) => {
+                write!(f, "keypress ")?;
+                // Keys that would be read differently bare have to be quoted
+                if c.is_whitespace() || c.is_control() || matches!(c, '\'' | '\\') {
+                    write_escaped(f, &c.to_string(), '\'')
+                } else {
+                    write!(f, "{}", c)
+                }
+            }
+            WebEvent::Paste(s) => {
+                write!(f, "paste ")?;
+                write_escaped(f, s, '"')
+            }
+            WebEvent::Click { x, y } => write!(f, "click {} {}", x, y),
+        }
+    }
+}
+
+impl fmt::Display for Recorded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(at) = self.at {
+            write!(f, "@{} ", at.as_millis())?;
+        }
+        write!(f, "{}", self.event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let session = vec![
+            Recorded {
+                at: Some(Duration::from_millis(0)),
+                event: WebEvent::PageLoad,
+            },
+            Recorded {
+                at: Some(Duration::from_millis(120)),
+                event: WebEvent::KeyPress(' '),
+            },
+            Recorded {
+                at: None,
+                event: WebEvent::KeyPress('\''),
+            },
+            Recorded {
+                at: Some(Duration::from_millis(250)),
+                event: WebEvent::Paste("my \"quoted\"\ttext\\".to_owned()),
+            },
+            Recorded {
+                at: None,
+                event: WebEvent::Click { x: -20, y: 80 },
+            },
+            Recorded {
+                at: Some(Duration::from_millis(900)),
+                event: WebEvent::PageUnload,
+            },
+        ];
+        let text = write_log(&session);
+        assert_eq!(parse_log(&text), Ok(session));
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines() {
+        let session = parse_log("# a comment\n\n  pageload\n   # indented\n").unwrap();
+        assert_eq!(session.len(), 1);
+        assert_eq!(session[0].event, WebEvent::PageLoad);
+    }
+
+    // The line, column and kind of the error in `input`
+    fn error(input: &str) -> (usize, usize, ErrorKind) {
+        let e = parse_log(input).unwrap_err();
+        (e.line, e.column, e.kind)
+    }
+
+    #[test]
+    fn test_error_positions() {
+        assert_eq!(
+            error("pageload\nclick 20 eighty"),
+            (2, 10, ErrorKind::InvalidInteger("eighty".to_owned()))
+        );
+        assert_eq!(
+            error("  scroll 3"),
+            (1, 3, ErrorKind::UnknownEvent("scroll".to_owned()))
+        );
+        assert_eq!(
+            error("click 20"),
+            (1, 9, ErrorKind::MissingArgument("y coordinate"))
+        );
+        assert_eq!(
+            error("keypress abc"),
+            (1, 10, ErrorKind::NotOneChar("abc".to_owned()))
+        );
+        assert_eq!(
+            error("paste \"a\\qb\""),
+            (1, 9, ErrorKind::InvalidEscape('q'))
+        );
+        assert_eq!(
+            error("pageload\npaste \"open"),
+            (2, 7, ErrorKind::UnterminatedString)
+        );
+        assert_eq!(
+            error("pageunload now"),
+            (1, 12, ErrorKind::TrailingInput("now".to_owned()))
+        );
+        assert_eq!(
+            error("@x pageload"),
+            (1, 1, ErrorKind::InvalidTimestamp("@x".to_owned()))
+        );
+        assert_eq!(
+            error("@200 pageload\n  @100 pageunload"),
+            (2, 3, ErrorKind::TimestampOutOfOrder)
+        );
+    }
+
+    #[test]
+    fn test_error_display() {
+        let e = "keypress abc".parse::<WebEvent>().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 10: expected a single character, found `abc`"
+        );
+    }
+}
@@ -1,7 +1,20 @@
 // An attribute to hide warnings for unused code.
 #![allow(dead_code)]
 
-fn main() {
+use std::cell::RefCell;
+use std::io::{self, Write};
+
+use event_log::Recorded;
+use replay::{EventKind, Replayer, Timing};
+
+mod event_log;
+mod replay;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let pressed = WebEvent::KeyPress('x');
     // `to_owned()` creates an owned `String` from a string slice.
     let pasted = WebEvent::Paste("my text".to_owned());
@@ -9,11 +22,11 @@ fn main() {
     let load = WebEvent::PageLoad;
     let unload = WebEvent::PageUnload;
 
-    inspect(pressed);
-    inspect(pasted);
-    inspect(click);
-    inspect(load);
-    inspect(unload);
+    inspect(out, pressed)?;
+    inspect(out, pasted)?;
+    inspect(out, click)?;
+    inspect(out, load)?;
+    inspect(out, unload)?;
 
     // We can refer to each variant via its alias, not its long and inconvenient
     // name.
@@ -32,28 +45,88 @@ fn main() {
 
     match status {
         // Note the lack of scoping because of the explicit `use` above.
-        Rich => println!("The rich have lots of money!"),
-        Poor => println!("The poor have no money..."),
+        Rich => writeln!(out, "The rich have lots of money!")?,
+        Poor => writeln!(out, "The poor have no money...")?,
     }
 
     match work {
         // Note again the lack of scoping.
-        Civilian => println!("Civilians work!"),
-        Soldier => println!("Soldiers fight!"),
+        Civilian => writeln!(out, "Civilians work!")?,
+        Soldier => writeln!(out, "Soldiers fight!")?,
     }
 
     // `enums` can be cast as integers.
-    println!("zero is {}", Number::Zero as i32);
-    println!("one is {}", Number::One as i32);
+    writeln!(out, "zero is {}", Number::Zero as i32)?;
+    writeln!(out, "one is {}", Number::One as i32)?;
+
+    writeln!(out, "roses are #{:06x}", Color::Red as i32)?;
+    writeln!(out, "violets are #{:06x}", Color::Blue as i32)?;
+
+    // Events can be written as text, one per line, and parsed back.
+    let events = vec![
+        WebEvent::KeyPress('x'),
+        WebEvent::Paste("my \"quoted\" text".to_owned()),
+        WebEvent::Click { x: 20, y: 80 },
+        WebEvent::PageLoad,
+    ];
+    let session: Vec<Recorded> = events
+        .into_iter()
+        .map(|event| Recorded { at: None, event })
+        .collect();
+    let text = event_log::write_log(&session);
+    write!(out, "{}", text)?;
+
+    // Parse errors point at the offending line and column.
+    match event_log::parse_log("pageload\nclick 20 eighty") {
+        Ok(_) => writeln!(out, "parsed?!")?,
+        Err(e) => writeln!(out, "error: {}", e)?,
+    }
+    match "keypress abc".parse::<WebEvent>() {
+        Ok(_) => writeln!(out, "parsed?!")?,
+        Err(e) => writeln!(out, "error: {}", e)?,
+    }
 
-    println!("roses are #{:06x}", Color::Red as i32);
-    println!("violets are #{:06x}", Color::Blue as i32);
+    // Replay a recorded session through handlers registered per variant.
+    // Both text handlers keep what they get, so they share the list.
+    let typed = RefCell::new(Vec::new());
+    let mut clicks = Vec::new();
+    let stats = {
+        let mut replayer = Replayer::new();
+        replayer
+            .on(EventKind::KeyPress, |event| {
+                typed.borrow_mut().push(event.clone())
+            })
+            .on(EventKind::Paste, |event| {
+                typed.borrow_mut().push(event.clone())
+            })
+            .on(EventKind::Click, |event| {
+                if let WebEvent::Click { x, y } = event {
+                    clicks.push((*x, *y));
+                }
+            });
+        // The session is kept next to `Cargo.toml`, wherever this is run from
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/session.log");
+        replayer.replay_file(path, Timing::Recorded { speed: 10.0 })
+    };
+    for event in typed.into_inner() {
+        inspect(out, event)?;
+    }
+    match stats {
+        Ok(stats) => {
+            writeln!(out, "clicks: {:?}", clicks)?;
+            writeln!(out, "{}", stats)?;
+        }
+        Err(e) => writeln!(out, "{}", e)?,
+    }
+
+    Ok(())
 }
 
 // Create an `enum` to classify a web event. Note how both
 // names and type information together specify the variant:
 // `PageLoad != PageUnload` and `KeyPress(char) != Paste(String)`.
 // Each is different and independent.
+#[derive(Debug, Clone, PartialEq)]
 enum WebEvent {
     // An `enum` may either be `unit-like`,
     PageLoad,
@@ -66,19 +139,20 @@ enum WebEvent {
 }
 
 // A function which takes a `WebEvent` enum as an argument and
-// returns nothing.
-fn inspect(event: WebEvent) {
+// returns nothing but whether writing it out failed.
+fn inspect(out: &mut impl Write, event: WebEvent) -> io::Result<()> {
     match event {
-        WebEvent::PageLoad => println!("page loaded"),
-        WebEvent::PageUnload => println!("page unloaded"),
+        WebEvent::PageLoad => writeln!(out, "page loaded")?,
+        WebEvent::PageUnload => writeln!(out, "page unloaded")?,
         // Destructure `c` from inside the `enum`.
-        WebEvent::KeyPress(c) => println!("pressed '{}'.", c),
-        WebEvent::Paste(s) => println!("pasted \"{}\".", s),
+        WebEvent::KeyPress(c) => writeln!(out, "pressed '{}'.", c)?,
+        WebEvent::Paste(s) => writeln!(out, "pasted \"{}\".", s)?,
         // Destructure `Click` into `x` and `y`.
         WebEvent::Click { x, y } => {
-            println!("clicked at x={}, y={}.", x, y);
+            writeln!(out, "clicked at x={}, y={}.", x, y)?;
         }
     }
+    Ok(())
 }
 
 enum VeryVerboseEnumOfThingsToDoWithNumbers {
@@ -121,3 +195,19 @@ enum Color {
     Green = 0x00ff00,
     Blue = 0x0000ff,
 }
+
+#[cfg(test)]
+mod tests {
+    // How long the replay took changes from run to run
+    fn redact_elapsed(output: &str) -> String {
+        output
+            .lines()
+            .map(|line| match line.starts_with("replayed in ") {
+                true => "replayed in <elapsed>\n".to_owned(),
+                false => format!("{}\n", line),
+            })
+            .collect()
+    }
+
+    testing::snapshot_test!(super::run, |snapshot| snapshot.filter(redact_elapsed));
+}
@@ -0,0 +1,265 @@
+// Replays a recorded session of `WebEvent`s through registered handlers, to
+// reproduce what a user did step by step.
+use std::collections::{BTreeMap, HashMap};
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::event_log::{self, ParseError, Recorded};
+use crate::WebEvent;
+
+// The variants of `WebEvent`, without their data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EventKind {
+    PageLoad,
+    PageUnload,
+    KeyPress,
+    Paste,
+    Click,
+}
+
+impl WebEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            WebEvent::PageLoad => EventKind::PageLoad,
+            WebEvent::PageUnload => EventKind::PageUnload,
+            WebEvent::KeyPress(_) => EventKind::KeyPress,
+            WebEvent::Paste(_) => EventKind::Paste,
+            WebEvent::Click { .. } => EventKind::Click,
+        }
+    }
+}
+
+pub enum Timing {
+    // Replay every event right after the previous one
+    Immediate,
+    // Wait for the recorded timestamps. A `speed` of 2.0 replays twice as
+    // fast as recorded; it has to be above zero. Events without a timestamp
+    // don't wait.
+    Recorded { speed: f64 },
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KindStats {
+    // How many events of this kind were replayed
+    pub events: usize,
+    // How many handler calls they caused
+    pub handler_calls: usize,
+    // How many of them had no handler at all
+    pub unhandled: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Stats {
+    pub per_kind: BTreeMap<EventKind, KindStats>,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<12}{:>8}{:>8}{:>11}",
+            "event", "count", "calls", "unhandled"
+        )?;
+        for (kind, stats) in &self.per_kind {
+            writeln!(
+                f,
+                "{:<12}{:>8}{:>8}{:>11}",
+                format!("{:?}", kind),
+                stats.events,
+                stats.handler_calls,
+                stats.unhandled
+            )?;
+        }
+        write!(f, "replayed in {:?}", self.elapsed)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse(ParseError),
+    // `Timing::Recorded` with a speed that is zero, negative or NaN
+    InvalidSpeed(f64),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "could not read session: {}", e),
+            ReplayError::Parse(e) => write!(f, "invalid session: {}", e),
+            ReplayError::InvalidSpeed(speed) => {
+                write!(f, "invalid replay speed {}, it must be above zero", speed)
+            }
+        }
+    }
+}
+
+impl error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ReplayError::Io(e) => Some(e),
+            ReplayError::Parse(e) => Some(e),
+            ReplayError::InvalidSpeed(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+impl From<ParseError> for ReplayError {
+    fn from(err: ParseError) -> Self {
+        ReplayError::Parse(err)
+    }
+}
+
+type Handler<'a> = Box<dyn FnMut(&WebEvent) + 'a>;
+
+#[derive(Default)]
+pub struct Replayer<'a> {
+    handlers: HashMap<EventKind, Vec<Handler<'a>>>,
+}
+
+impl<'a> Replayer<'a> {
+    pub fn new() -> Self {
+        Replayer::default()
+    }
+
+    // Register a handler for one variant. Several handlers may be registered
+    // for the same variant; they run in the order they were added.
+    pub fn on<F>(&mut self, kind: EventKind, handler: F) -> &mut Self
+    where
+        F: FnMut(&WebEvent) + 'a,
+    {
+        self.handlers
+            .entry(kind)
+            .or_default()
+            .push(Box::new(handler));
+        self
+    }
+
+    // Feed every event of `session` to its handlers, in order
+    pub fn replay(&mut self, session: &[Recorded], timing: Timing) -> Result<Stats, ReplayError> {
+        // Checked before any handler runs, rather than halfway through
+        if let Timing::Recorded { speed } = timing {
+            if speed.is_nan() || speed <= 0.0 {
+                return Err(ReplayError::InvalidSpeed(speed));
+            }
+        }
+
+        let start = Instant::now();
+        let mut stats = Stats::default();
+
+        for recorded in session {
+            if let (Timing::Recorded { speed }, Some(at)) = (&timing, recorded.at) {
+                // A very slow speed puts the event further away than a
+                // `Duration` reaches
+                let due =
+                    Duration::try_from_secs_f64(at.as_secs_f64() / speed).unwrap_or(Duration::MAX);
+                if let Some(wait) = due.checked_sub(start.elapsed()) {
+                    thread::sleep(wait);
+                }
+            }
+
+            let kind = recorded.event.kind();
+            let entry = stats.per_kind.entry(kind).or_default();
+            entry.events += 1;
+            match self.handlers.get_mut(&kind) {
+                Some(handlers) if !handlers.is_empty() => {
+                    for handler in handlers.iter_mut() {
+                        handler(&recorded.event);
+                        entry.handler_calls += 1;
+                    }
+                }
+                _ => entry.unhandled += 1,
+            }
+        }
+
+        stats.elapsed = start.elapsed();
+        Ok(stats)
+    }
+
+    // Read a session written in the `event_log` format and replay it
+    pub fn replay_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        timing: Timing,
+    ) -> Result<Stats, ReplayError> {
+        let text = fs::read_to_string(path)?;
+        let session = event_log::parse_log(&text)?;
+        self.replay(&session, timing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Vec<Recorded> {
+        event_log::parse_log("@0 pageload\n@1 keypress a\n@2 click 1 2\n@3 keypress b\n").unwrap()
+    }
+
+    #[test]
+    fn test_handlers() {
+        let mut keys = Vec::new();
+        let mut calls = 0;
+        let stats = {
+            let mut replayer = Replayer::new();
+            replayer
+                .on(EventKind::KeyPress, |event| {
+                    if let WebEvent::KeyPress(c) = event {
+                        keys.push(*c);
+                    }
+                })
+                .on(EventKind::KeyPress, |_| calls += 1);
+            replayer.replay(&session(), Timing::Immediate).unwrap()
+        };
+        assert_eq!(keys, ['a', 'b']);
+        assert_eq!(calls, 2);
+
+        let key_presses = &stats.per_kind[&EventKind::KeyPress];
+        assert_eq!(key_presses.events, 2);
+        assert_eq!(key_presses.handler_calls, 4);
+        assert_eq!(key_presses.unhandled, 0);
+        assert_eq!(stats.per_kind[&EventKind::Click].unhandled, 1);
+        assert!(!stats.per_kind.contains_key(&EventKind::Paste));
+    }
+
+    #[test]
+    fn test_recorded_timing() {
+        let stats = Replayer::new()
+            .replay(&session(), Timing::Recorded { speed: 0.5 })
+            .unwrap();
+        // The last event was recorded at 3ms, so waits until 6ms
+        assert!(stats.elapsed >= Duration::from_millis(6));
+    }
+
+    #[test]
+    fn test_invalid_speed() {
+        for speed in [0.0, -1.0, f64::NAN] {
+            let mut called = false;
+            let result = {
+                let mut replayer = Replayer::new();
+                replayer.on(EventKind::PageLoad, |_| called = true);
+                replayer.replay(&session(), Timing::Recorded { speed })
+            };
+            assert!(matches!(result, Err(ReplayError::InvalidSpeed(_))));
+            assert!(!called);
+        }
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let result = Replayer::new().replay_file("no such session.log", Timing::Immediate);
+        assert!(matches!(result, Err(ReplayError::Io(_))));
+    }
+}
@@ -1,19 +1,26 @@
 use std::fmt::Debug;
+use std::io::{self, Write};
 
-fn main() {
+use derive_macros::Builder;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Create struct with field init shorthand
     let name = String::from("Peter");
     let age = 27;
     let peter = Person { name, age };
 
     // Print debug struct
-    println!("{:?}", peter);
+    writeln!(out, "{:?}", peter)?;
 
     // Instantiate a `Point`
     let point: Point = Point { x: 10.3, y: 0.4 };
 
     // Access the fields of the point
-    println!("point coordinates: ({}, {})", point.x, point.y);
+    writeln!(out, "point coordinates: ({}, {})", point.x, point.y)?;
 
     // Make a new point by using struct update syntax to use the fields of our
     // other one
@@ -21,7 +28,11 @@ fn main() {
 
     // `bottom_right.y` will be the same as `point.y` because we used that field
     // from `point`
-    println!("second point: ({}, {})", bottom_right.x, bottom_right.y);
+    writeln!(
+        out,
+        "second point: ({}, {})",
+        bottom_right.x, bottom_right.y
+    )?;
 
     // Destructure the point using a `let` binding
     let Point {
@@ -45,18 +56,35 @@ fn main() {
     let pair = Pair(1, 0.1);
 
     // Access the fields of a tuple struct
-    println!("pair contains {:?} and {:?}", pair.0, pair.1);
+    writeln!(out, "pair contains {:?} and {:?}", pair.0, pair.1)?;
 
     // Destructure a tuple struct
     let Pair(integer, decimal) = pair;
 
-    println!("pair contains {:?} and {:?}", integer, decimal);
-
-    println!("Area of rectangle: {}", rect_area(rectangle));
+    writeln!(out, "pair contains {:?} and {:?}", integer, decimal)?;
+
+    writeln!(out, "Area of rectangle: {}", rect_area(rectangle))?;
+
+    // The same rectangle from a builder, which fills in each corner with a
+    // builder of its own
+    let rectangle = Rectangle::builder()
+        .top_left_with(|point| point.x(left_edge).y(top_edge))
+        .bottom_right_with(|point| point.x(5.2).y(0.4))
+        .build()
+        .unwrap();
+    writeln!(out, "Area of built rectangle: {}", rect_area(rectangle))?;
+
+    // A builder reports every corner coordinate that was forgotten
+    let forgotten = Rectangle::builder()
+        .top_left_with(|point| point.x(left_edge))
+        .build();
+    if let Err(e) = forgotten {
+        writeln!(out, "{}", e)?;
+    }
 
     let bottom_left = Point { x: 1.2, y: 3.4 };
     let square = square(bottom_left, 5.6);
-    println!("{:?}", square);
+    writeln!(out, "{:?}", square)
 }
 
 #[derive(Debug)]
@@ -72,7 +100,7 @@ struct Unit;
 struct Pair(i32, f32);
 
 // A struct with two fields
-#[derive(Debug)]
+#[derive(Debug, Builder)]
 struct Point {
     x: f32,
     y: f32,
@@ -80,11 +108,13 @@ struct Point {
 
 // Structs can be reused as fields of another struct
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Builder)]
 struct Rectangle {
     // A rectangle can be specified by where the top left and bottom right
     // corners are in space.
+    #[builder(nested)]
     top_left: Point,
+    #[builder(nested)]
     bottom_right: Point,
 }
 
@@ -110,3 +140,6 @@ fn square(point: Point, side_length: f32) -> Rectangle {
         },
     }
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,6 +1,16 @@
-use crate::List::*;
+use std::fmt;
+use std::io::{self, Write};
+use std::thread;
 
-fn main() {
+use persistent::{ArcList, RcList};
+
+mod persistent;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Create an empty linked list
     let mut list = List::new();
 
@@ -10,59 +20,335 @@ fn main() {
     list = list.prepend(3);
 
     // Show the final state of the list
-    println!("linked list has length: {}", list.len());
-    println!("{}", list.stringify());
+    writeln!(out, "linked list has length: {}", list.len())?;
+    writeln!(out, "{}", list.stringify())?;
+
+    // The list is generic over its element type
+    let mut words: List<String> = ["linked", "lists", "are", "fun"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    for word in words.iter_mut() {
+        word.make_ascii_uppercase();
+    }
+    words.reverse();
+    writeln!(out, "{:?}", words)?;
+
+    // Join two lists together; `other` is left empty
+    let mut other: List<u32> = (4..=6).collect();
+    list.append(&mut other);
+    list.extend(vec![7, 8]);
+    writeln!(out, "{} (other is empty: {})", list, other.is_empty())?;
+    writeln!(out, "front: {:?}", list.pop_front())?;
+
+    // None of the operations recurse, so very long lists are fine too.
+    // Neither counting, printing nor dropping them overflows the stack.
+    let mut long: List<u64> = (0..1_000_000).collect();
+    long.reverse();
+    let sum: u64 = long.iter().sum();
+    writeln!(out, "long list has length {} and sum {}", long.len(), sum)?;
+
+    // A persistent list never changes once built. Every version below is a
+    // cheap snapshot, and they all share the nodes of `base`.
+    let base: RcList<&str> = RcList::new().prepend("dark").prepend("en_GB");
+    let history = [
+        base.prepend("font=12"),
+        base.prepend("font=14"),
+        base.prepend("font=14").prepend("tabs=4"),
+    ];
+    for (version, config) in history.iter().enumerate() {
+        writeln!(out, "version {}: {}", version, config)?;
+    }
+
+    // Undo by going back to the tail of the latest version
+    let undone = history[2].tail();
+    writeln!(
+        out,
+        "undo: {:?} (equal to version 1: {})",
+        undone,
+        undone == history[1]
+    )?;
+    writeln!(
+        out,
+        "shares nodes with version 1: {}",
+        undone.ptr_eq(&history[1])
+    )?;
+    writeln!(out, "first setting: {:?}", undone.head())?;
+    writeln!(
+        out,
+        "versions of length {} share the base of length {}",
+        undone.len(),
+        base.len()
+    )?;
+
+    // `ArcList` can be shared between threads
+    let shared: ArcList<u32> = ArcList::new().prepend(1).prepend(2);
+    let handle = {
+        let shared = shared.clone();
+        thread::spawn(move || shared.prepend(3).iter().sum::<u32>())
+    };
+    writeln!(out, "sum in thread: {}", handle.join().unwrap())?;
+    writeln!(out, "original is unchanged: {}", shared)
 }
-enum List {
-    // Cons: Tuple struct that wraps an element and a pointer to the next node
-    Cons(u32, Box<List>),
-    // Nil: A node that signifies the end of the linked list
-    Nil,
+
+// A link is either empty, or owns the next node on the heap
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+struct List<T> {
+    head: Link<T>,
 }
 
-// Methods can be attached to an enum
-impl List {
+// Methods can be attached to a generic struct
+impl<T> List<T> {
     // Create an empty list
-    fn new() -> List {
-        // `Nil` has type `List`
-        Nil
+    fn new() -> List<T> {
+        List { head: None }
     }
 
     // Consume a list, and return the same list with a new element at its front
-    fn prepend(self, elem: u32) -> List {
-        // `Cons` also has type List
-        Cons(elem, Box::new(self))
+    fn prepend(mut self, elem: T) -> List<T> {
+        let next = self.head.take();
+        self.head = Some(Box::new(Node { elem, next }));
+        self
+    }
+
+    // Remove the element at the front of the list, if there is one
+    fn pop_front(&mut self) -> Option<T> {
+        self.head.take().map(|node| {
+            self.head = node.next;
+            node.elem
+        })
+    }
+
+    // Check whether the list contains no elements
+    fn is_empty(&self) -> bool {
+        self.head.is_none()
     }
 
     // Return the length of the list
-    fn len(&self) -> u32 {
-        // `self` has to be matched, because the behavior of this method
-        // depends on the variant of `self`
-        // `self` has type `&List`, and `*self` has type `List`, matching on a
-        // concrete type `T` is preferred over a match on a reference `&T`
-        // after Rust 2018 you can use self here and tail (with no ref) below as well,
-        // rust will infer &s and ref tail.
-        // See https://doc.rust-lang.org/edition-guide/rust-2018/ownership-and-lifetimes/default-match-bindings.html
-        match *self {
-            // Can't take ownership of the tail, because `self` is borrowed;
-            // instead take a reference to the tail
-            Cons(_, ref tail) => 1 + tail.len(),
-            // Base Case: An empty list has zero length
-            Nil => 0,
-        }
+    fn len(&self) -> usize {
+        // Walking the list with an iterator uses a loop rather than
+        // recursion, so the stack doesn't grow with the list
+        self.iter().count()
     }
 
     // Return representation of the list as a (heap allocated) string
-    fn stringify(&self) -> String {
-        match *self {
-            Cons(head, ref tail) => {
-                // `format!` is similar to `print!`, but returns a heap
-                // allocated string instead of printing to the console
-                format!("{}, {}", head, tail.stringify())
-            }
-            Nil => {
-                format!("Nil")
-            }
+    fn stringify(&self) -> String
+    where
+        T: fmt::Display,
+    {
+        // `to_string` is provided by the `Display` implementation below
+        self.to_string()
+    }
+
+    // Reverse the list in place by relinking its nodes one at a time
+    fn reverse(&mut self) {
+        let mut reversed = None;
+        let mut current = self.head.take();
+
+        while let Some(mut node) = current {
+            current = node.next.take();
+            node.next = reversed;
+            reversed = Some(node);
+        }
+
+        self.head = reversed;
+    }
+
+    // Move all elements of `other` to the end of `self`, leaving `other` empty
+    fn append(&mut self, other: &mut List<T>) {
+        *self.last_link() = other.head.take();
+    }
+
+    // Return the empty link at the end of the list
+    fn last_link(&mut self) -> &mut Link<T> {
+        let mut link = &mut self.head;
+        while let Some(node) = link {
+            link = &mut node.next;
+        }
+        link
+    }
+
+    fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_deref_mut(),
+        }
+    }
+}
+
+// The default `Drop` would drop the head node, which drops the next node and
+// so on, recursing once per element. Unlinking the nodes in a loop instead
+// keeps the stack flat.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut current = self.head.take();
+        while let Some(mut node) = current {
+            current = node.next.take();
+        }
+    }
+}
+
+// Borrowing iterator, yields `&T` from front to back
+struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+// Mutably borrowing iterator, yields `&mut T` from front to back
+struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|node| {
+            self.next = node.next.as_deref_mut();
+            &mut node.elem
+        })
+    }
+}
+
+// Owning iterator, hands out the elements by popping them off the front
+struct IntoIter<T>(List<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Elements are added at the back, so the list keeps the order of the iterator
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // Find the end once, then keep a cursor on it while appending
+        let mut link = self.last_link();
+        for elem in iter {
+            let node = link.insert(Box::new(Node { elem, next: None }));
+            link = &mut node.next;
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// Same layout as the old `stringify`: `3, 2, 1, Nil`
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for elem in self {
+            write!(f, "{}, ", elem)?;
+        }
+        write!(f, "Nil")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    testing::snapshot_test!(run);
+
+    #[test]
+    fn test_push_pop() {
+        let mut list = List::new().prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_iteration_order() {
+        let mut list: List<i32> = (1..=3).collect();
+        list.extend(vec![4, 5]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+        for elem in list.iter_mut() {
+            *elem *= 10;
         }
+        list.reverse();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [50, 40, 30, 20, 10]);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut list: List<i32> = (1..=2).collect();
+        let mut other: List<i32> = (3..=4).collect();
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(list.to_string(), "1, 2, 3, 4, Nil");
+    }
+
+    #[test]
+    fn test_drop_long_list() {
+        // Would overflow the stack if dropping recursed once per node
+        let list: List<u32> = (0..1_000_000).collect();
+        assert_eq!(list.len(), 1_000_000);
+        drop(list);
     }
 }
@@ -0,0 +1,277 @@
+// A persistent (immutable) counterpart of `List`. Nodes are never modified
+// once created, so any number of lists can point at the same tail: `prepend`
+// and `tail` create a new list in O(1) without copying the elements.
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+// The reference counted pointer used to share nodes. `Rc` is cheaper, `Arc`
+// can be sent to other threads.
+pub trait Sharing {
+    type Ptr<U>: Clone + Deref<Target = U>;
+
+    fn new<U>(value: U) -> Self::Ptr<U>;
+    // The value if `ptr` was its last owner. Unlike `try_unwrap`, two
+    // threads dropping the last two owners at once can't both fail.
+    fn into_inner<U>(ptr: Self::Ptr<U>) -> Option<U>;
+    fn ptr_eq<U>(a: &Self::Ptr<U>, b: &Self::Ptr<U>) -> bool;
+}
+
+pub struct RcSharing;
+
+impl Sharing for RcSharing {
+    type Ptr<U> = Rc<U>;
+
+    fn new<U>(value: U) -> Rc<U> {
+        Rc::new(value)
+    }
+
+    fn into_inner<U>(ptr: Rc<U>) -> Option<U> {
+        Rc::into_inner(ptr)
+    }
+
+    fn ptr_eq<U>(a: &Rc<U>, b: &Rc<U>) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+}
+
+pub struct ArcSharing;
+
+impl Sharing for ArcSharing {
+    type Ptr<U> = Arc<U>;
+
+    fn new<U>(value: U) -> Arc<U> {
+        Arc::new(value)
+    }
+
+    fn into_inner<U>(ptr: Arc<U>) -> Option<U> {
+        Arc::into_inner(ptr)
+    }
+
+    fn ptr_eq<U>(a: &Arc<U>, b: &Arc<U>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+}
+
+pub type RcList<T> = SharedList<T, RcSharing>;
+pub type ArcList<T> = SharedList<T, ArcSharing>;
+
+type Link<T, S> = Option<<S as Sharing>::Ptr<Node<T, S>>>;
+
+struct Node<T, S: Sharing> {
+    elem: T,
+    next: Link<T, S>,
+}
+
+pub struct SharedList<T, S: Sharing = RcSharing> {
+    head: Link<T, S>,
+    // Every node knows nothing about its predecessors, so the length is
+    // stored next to the head to keep `len` O(1)
+    len: usize,
+}
+
+impl<T, S: Sharing> SharedList<T, S> {
+    // Create an empty list
+    pub fn new() -> Self {
+        SharedList { head: None, len: 0 }
+    }
+
+    // Return a new list with `elem` in front of `self`. `self` is untouched
+    // and both lists share the same nodes from then on.
+    pub fn prepend(&self, elem: T) -> Self {
+        SharedList {
+            head: Some(S::new(Node {
+                elem,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    // Return the list without its first element, sharing all of its nodes.
+    // The tail of an empty list is empty.
+    pub fn tail(&self) -> Self {
+        match self.head.as_deref() {
+            Some(node) => SharedList {
+                head: node.next.clone(),
+                len: self.len - 1,
+            },
+            None => SharedList::new(),
+        }
+    }
+
+    // Borrow the first element
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_deref().map(|node| &node.elem)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // Check whether two lists are the very same nodes, without looking at
+    // the elements
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => S::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    // Iterate over references to the elements; payloads are never cloned
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T, S: Sharing> Default for SharedList<T, S> {
+    fn default() -> Self {
+        SharedList::new()
+    }
+}
+
+// Cloning a list only bumps the reference count of its head
+impl<T, S: Sharing> Clone for SharedList<T, S> {
+    fn clone(&self) -> Self {
+        SharedList {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+// Nodes are released front to back for as long as this list is their only
+// owner. As soon as a node is shared with another list, the rest of the
+// chain still belongs to that list and the loop stops. This keeps dropping
+// a long list from recursing once per node.
+impl<T, S: Sharing> Drop for SharedList<T, S> {
+    fn drop(&mut self) {
+        let mut current = self.head.take();
+        while let Some(ptr) = current {
+            match S::into_inner(ptr) {
+                Some(mut node) => current = node.next.take(),
+                None => break,
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T, S: Sharing> {
+    next: Option<&'a Node<T, S>>,
+}
+
+impl<'a, T, S: Sharing> Iterator for Iter<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T, S: Sharing> IntoIterator for &'a SharedList<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Lists are equal when they hold equal elements in the same order, no matter
+// whether they share any nodes
+impl<T: PartialEq, S: Sharing> PartialEq for SharedList<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && (self.ptr_eq(other) || self.iter().eq(other.iter()))
+    }
+}
+
+impl<T: Eq, S: Sharing> Eq for SharedList<T, S> {}
+
+impl<T: fmt::Display, S: Sharing> fmt::Display for SharedList<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for elem in self {
+            write!(f, "{}, ", elem)?;
+        }
+        write!(f, "Nil")
+    }
+}
+
+impl<T: fmt::Debug, S: Sharing> fmt::Debug for SharedList<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_sharing() {
+        let base: RcList<i32> = RcList::new().prepend(1);
+        let a = base.prepend(2);
+        let b = base.prepend(3);
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(b.iter().copied().collect::<Vec<_>>(), [3, 1]);
+        // Prepending left `base` as it was
+        assert_eq!(base.len(), 1);
+        assert_eq!(base.head(), Some(&1));
+    }
+
+    #[test]
+    fn test_structural_reuse() {
+        let base: RcList<i32> = RcList::new().prepend(1).prepend(2);
+        let longer = base.prepend(3);
+        assert!(longer.tail().ptr_eq(&base));
+        assert!(longer.clone().ptr_eq(&longer));
+
+        // Equal elements, but built separately
+        let copy = RcList::new().prepend(1).prepend(2);
+        assert_eq!(copy, base);
+        assert!(!copy.ptr_eq(&base));
+    }
+
+    #[test]
+    fn test_drop_long_list() {
+        // Would overflow the stack if dropping recursed once per node
+        let mut list: RcList<u32> = RcList::new();
+        for i in 0..1_000_000 {
+            list = list.prepend(i);
+        }
+        let shared = list.tail();
+        drop(list);
+        assert_eq!(shared.len(), 999_999);
+        drop(shared);
+    }
+
+    #[test]
+    fn test_drop_from_threads() {
+        let mut list: ArcList<u32> = ArcList::new();
+        for i in 0..100_000 {
+            list = list.prepend(i);
+        }
+        // Whichever thread drops its list last frees the nodes
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let list = list.clone();
+                thread::spawn(move || drop(list))
+            })
+            .collect();
+        drop(list);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
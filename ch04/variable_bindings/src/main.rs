@@ -1,4 +1,10 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let an_integer = 1u32;
     let a_boolean = true;
     let unit = ();
@@ -6,9 +12,9 @@ fn main() {
     // copy `an_integer` into `copied_integer`
     let copied_integer = an_integer;
 
-    println!("An integer: {:?}", copied_integer);
-    println!("A boolean: {:?}", a_boolean);
-    println!("Meet the unit value: {:?}", unit);
+    writeln!(out, "An integer: {:?}", copied_integer)?;
+    writeln!(out, "A boolean: {:?}", a_boolean)?;
+    writeln!(out, "Meet the unit value: {:?}", unit)?;
 
     // The compiler warns about unused variable bindings; these warnings can
     // be silenced by prefixing the variable name with an underscore
@@ -20,16 +26,16 @@ fn main() {
     let _immutable_binding = 1;
     let mut mutable_binding = 1;
 
-    println!("Before mutation: {}", mutable_binding);
+    writeln!(out, "Before mutation: {}", mutable_binding)?;
 
     // Ok
     mutable_binding += 1;
 
-    println!("After mutation: {}", mutable_binding);
+    writeln!(out, "After mutation: {}", mutable_binding)?;
 
     // Error!
     // _immutable_binding += 1;
-    // FIXME ^ Comment out this line
+    // FIXME ^ Comment out this line (error[E0384])
 
     // This binding lives in the main function
     let long_lived_binding = 1;
@@ -39,31 +45,31 @@ fn main() {
         // This binding only exists in this block
         let short_lived_binding = 2;
 
-        println!("inner short: {}", short_lived_binding);
+        writeln!(out, "inner short: {}", short_lived_binding)?;
     }
     // End of the block
 
     // Error! `short_lived_binding` doesn't exist in this scope
-    // println!("outer short: {}", short_lived_binding);
-    // FIXME ^ Comment out this line
+    // writeln!(out, "outer short: {}", short_lived_binding)?;
+    // FIXME ^ Comment out this line (error[E0425])
 
-    println!("outer long: {}", long_lived_binding);
+    writeln!(out, "outer long: {}", long_lived_binding)?;
 
     let shadowed_binding = 1;
 
     {
-        println!("before being shadowed: {}", shadowed_binding);
+        writeln!(out, "before being shadowed: {}", shadowed_binding)?;
 
         // This binding *shadows* the outer one
         let shadowed_binding = "abc";
 
-        println!("shadowed in inner block: {}", shadowed_binding);
+        writeln!(out, "shadowed in inner block: {}", shadowed_binding)?;
     }
-    println!("outside inner block: {}", shadowed_binding);
+    writeln!(out, "outside inner block: {}", shadowed_binding)?;
 
     // This binding *shadows* the previous binding
     let shadowed_binding = 2;
-    println!("shadowed in outer block: {}", shadowed_binding);
+    writeln!(out, "shadowed in outer block: {}", shadowed_binding)?;
 
     // Declare a variable binding
     let a_binding;
@@ -75,17 +81,17 @@ fn main() {
         a_binding = x * x;
     }
 
-    println!("a binding: {}", a_binding);
+    writeln!(out, "a binding: {}", a_binding)?;
 
     let another_binding;
 
     // Error! Use of uninitialized binding
-    // println!("another binding: {}", another_binding);
-    // FIXME ^ Comment out this line
+    // writeln!(out, "another binding: {}", another_binding)?;
+    // FIXME ^ Comment out this line (error[E0381])
 
     another_binding = 1;
 
-    println!("another binding: {}", another_binding);
+    writeln!(out, "another binding: {}", another_binding)?;
 
     let mut _mutable_integer = 7i32;
 
@@ -95,11 +101,16 @@ fn main() {
 
         // Error! `_mutable_integer` is frozen in this scope
         // _mutable_integer = 50;
-        // FIXME ^ Comment out this line
+        // FIXME ^ Comment out this line (error[E0384])
 
         // `_mutable_integer` goes out of scope
     }
 
     // Ok! `_mutable_integer` is not frozen in this scope
     _mutable_integer = 3;
+
+    Ok(())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
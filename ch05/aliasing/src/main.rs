@@ -1,16 +1,25 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // `NanoSecond` = `Inch` = `u64_t` = `u64`.
     let nanoseconds: NanoSecond = 5 as u64_t;
     let inches: Inch = 2 as u64_t;
 
     // Note that type aliases *don't* provide any extra type safety, because
     // aliases are *not* new types
-    println!(
+    writeln!(
+        out,
         "{} nanoseconds + {} inches = {} unit?",
         nanoseconds,
         inches,
         nanoseconds + inches
-    );
+    )?;
+
+    Ok(())
 }
 
 // `NanoSecond` is a new name for `u64`.
@@ -21,3 +30,6 @@ type Inch = u64;
 #[allow(non_camel_case_types)]
 type u64_t = u64;
 // TODO ^ Try removing the attribute
+
+#[cfg(test)]
+testing::snapshot_test!(run);
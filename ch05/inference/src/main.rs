@@ -1,4 +1,10 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Because of the annotation, the compiler knows that `elem` has type u8.
     let elem = 5u8;
 
@@ -12,5 +18,8 @@ fn main() {
     // Aha! Now the compiler knows that `vec` is a vector of `u8`s (`Vec<u8>`)
     // TODO ^ Try commenting out the `vec.push(elem)` line
 
-    println!("{:?}", vec);
+    writeln!(out, "{:?}", vec)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,4 +1,10 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Suffixed literals, their types are known at initialization
     let x = 1u8;
     let y = 2u32;
@@ -9,9 +15,12 @@ fn main() {
     let f = 1.0;
 
     // `size_of_val` returns the size of a variable in bytes
-    println!("size of `x` in bytes: {}", std::mem::size_of_val(&x));
-    println!("size of `y` in bytes: {}", std::mem::size_of_val(&y));
-    println!("size of `z` in bytes: {}", std::mem::size_of_val(&z));
-    println!("size of `i` in bytes: {}", std::mem::size_of_val(&i));
-    println!("size of `f` in bytes: {}", std::mem::size_of_val(&f));
+    writeln!(out, "size of `x` in bytes: {}", std::mem::size_of_val(&x))?;
+    writeln!(out, "size of `y` in bytes: {}", std::mem::size_of_val(&y))?;
+    writeln!(out, "size of `z` in bytes: {}", std::mem::size_of_val(&z))?;
+    writeln!(out, "size of `i` in bytes: {}", std::mem::size_of_val(&i))?;
+    writeln!(out, "size of `f` in bytes: {}", std::mem::size_of_val(&f))
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
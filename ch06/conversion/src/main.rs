@@ -2,18 +2,23 @@ use std::convert::From;
 use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::fmt;
+use std::io::{self, Write};
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let my_str = "hello";
     let my_string = String::from(my_str);
 
     let num = Number::from(30);
-    println!("My number is {:?}", num);
+    writeln!(out, "My number is {:?}", num)?;
 
     let int = 5;
     // Try removing the type declaration
     let num: Number = int.into();
-    println!("My number is {:?}", num);
+    writeln!(out, "My number is {:?}", num)?;
 
     // TryFrom
 
@@ -28,13 +33,13 @@ fn main() {
     assert_eq!(result, Err(()));
 
     let circle = Circle { radius: 6 };
-    println!("{}", circle.to_string());
+    writeln!(out, "{}", circle.to_string())?;
 
     let parsed: i32 = "5".parse().unwrap();
     let turbo_parsed = "10".parse::<i32>().unwrap();
 
     let sum = parsed + turbo_parsed;
-    println!("Sum: {:?}", sum);
+    writeln!(out, "Sum: {:?}", sum)
 }
 
 #[derive(Debug)]
@@ -72,3 +77,26 @@ impl fmt::Display for Circle {
         write!(f, "Circle of radius {}", self.radius)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `#[derive(Display)]` from chapter 17 reproduces the impl of `Circle`
+    #[derive(derive_macros::Display)]
+    #[display("Circle of radius {radius}")]
+    struct Derived {
+        radius: i32,
+    }
+
+    #[test]
+    fn test_circle() {
+        assert_eq!(Circle { radius: 6 }.to_string(), "Circle of radius 6");
+        assert_eq!(
+            Derived { radius: 6 }.to_string(),
+            Circle { radius: 6 }.to_string()
+        );
+    }
+
+    testing::snapshot_test!(run);
+}
@@ -1,4 +1,10 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // statement
     // statement
     // statement
@@ -26,7 +32,10 @@ fn main() {
         2 * x;
     };
 
-    println!("x is {:?}", x);
-    println!("y is {:?}", y);
-    println!("z is {:?}", z);
+    writeln!(out, "x is {:?}", x)?;
+    writeln!(out, "y is {:?}", y)?;
+    writeln!(out, "z is {:?}", z)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,21 +1,27 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let n = 5;
 
     if n < 0 {
-        print!("{} is negative", n);
+        write!(out, "{} is negative", n)?;
     } else if n > 0 {
-        print!("{} is positive", n);
+        write!(out, "{} is positive", n)?;
     } else {
-        print!("{} is zero", n);
+        write!(out, "{} is zero", n)?;
     }
 
     let big_n = if n < 10 && n > -10 {
-        println!(", and is a small number, increase ten-fold");
+        writeln!(out, ", and is a small number, increase ten-fold")?;
 
         // This expression returns an `i32`.
         10 * n
     } else {
-        println!(", and is a big number, halve the number");
+        writeln!(out, ", and is a big number, halve the number")?;
 
         // This expression must return an `i32` as well.
         n / 2
@@ -23,5 +29,8 @@ fn main() {
     };
     //   ^ Don't forget to put a semicolon here! All `let` bindings need it.
 
-    println!("{} -> {}", n, big_n);
+    writeln!(out, "{} -> {}", n, big_n)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,10 +1,16 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Make `optional` of type `Option<i32>`
     let optional = Some(7);
 
     match optional {
         Some(i) => {
-            println!("This is a really long string and `{:?}`", i);
+            writeln!(out, "This is a really long string and `{:?}`", i)?;
             // ^ Needed 2 indentations just so we could destructure
             // `i` from the option.
         }
@@ -20,29 +26,29 @@ fn main() {
     // The `if let` construct reads: "if `let` destructures `number` into
     // `Some(i)`, evaluate the block (`{}`).
     if let Some(i) = number {
-        println!("Matched {:?}!", i);
+        writeln!(out, "Matched {:?}!", i)?;
     }
 
     // If you need to specify a failure, use an else:
     if let Some(i) = letter {
-        println!("Matched {:?}!", i);
+        writeln!(out, "Matched {:?}!", i)?;
     } else {
         // Destructure failed. Change to the failure case.
-        println!("Didn't match a number. Let's go with a letter!");
+        writeln!(out, "Didn't match a number. Let's go with a letter!")?;
     }
 
     // Provide an altered failing condition.
     let i_like_letters = false;
 
     if let Some(i) = emoticon {
-        println!("Matched {:?}!", i);
+        writeln!(out, "Matched {:?}!", i)?;
     // Destructure failed. Evaluate an `else if` condition to see if the
     // alternate failure branch should be taken:
     } else if i_like_letters {
-        println!("Didn't match a number. Let's go with a letter!");
+        writeln!(out, "Didn't match a number. Let's go with a letter!")?;
     } else {
         // The condition evaluated false. This branch is the default:
-        println!("I don't like letters. Let's go with an emoticon :)!");
+        writeln!(out, "I don't like letters. Let's go with an emoticon :)!")?;
     }
 
     // Create example variables
@@ -52,24 +58,24 @@ fn main() {
 
     // Variable a matches Foo::Bar
     if let Foo::Bar = a {
-        println!("a is foobar");
+        writeln!(out, "a is foobar")?;
     }
 
     // Variable b does not match Foo::Bar
     // So this will print nothing
     if let Foo::Bar = b {
-        println!("b is foobar");
+        writeln!(out, "b is foobar")?;
     }
 
     // Variable c matches Foo::Qux which has a value
     // Similar to Some() in the previous example
     if let Foo::Qux(value) = c {
-        println!("c is {}", value);
+        writeln!(out, "c is {}", value)?;
     }
 
     // Binding also works with `if let`
     if let Foo::Qux(value @ 100) = c {
-        println!("c is one hundred");
+        writeln!(out, "c is one hundred")?;
     }
 
     let a = FooNoPartialEq::Bar;
@@ -78,8 +84,10 @@ fn main() {
     // if FooNoPartialEq::Bar == a {
     // ^-- this causes a compile-time error. Use `if let` instead.
     if let FooNoPartialEq::Bar = a {
-        println!("a is foobar");
+        writeln!(out, "a is foobar")?;
     }
+
+    Ok(())
 }
 
 // Our example enum
@@ -94,3 +102,6 @@ enum Foo {
 enum FooNoPartialEq {
     Bar,
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
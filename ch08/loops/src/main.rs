@@ -1,25 +1,31 @@
 #![allow(unreachable_code)]
 
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let mut count = 0u32;
 
-    println!("Let's count until infinity!");
+    writeln!(out, "Let's count until infinity!")?;
 
     // Infinite loop
     loop {
         count += 1;
 
         if count == 3 {
-            println!("three");
+            writeln!(out, "three")?;
 
             // Skip the rest of this iteration
             continue;
         }
 
-        println!("{}", count);
+        writeln!(out, "{}", count)?;
 
         if count == 5 {
-            println!("OK, that's enough");
+            writeln!(out, "OK, that's enough")?;
 
             // Exit this loop
             break;
@@ -27,10 +33,10 @@ fn main() {
     }
 
     'outer: loop {
-        println!("Entered the outer loop");
+        writeln!(out, "Entered the outer loop")?;
 
         'inner: loop {
-            println!("Entered the inner loop");
+            writeln!(out, "Entered the inner loop")?;
 
             // This would break only the inner loop
             //break;
@@ -39,10 +45,10 @@ fn main() {
             break 'outer;
         }
 
-        println!("This point will never be reached");
+        writeln!(out, "This point will never be reached")?;
     }
 
-    println!("Exited the outer loop");
+    writeln!(out, "Exited the outer loop")?;
 
     let mut counter = 0;
 
@@ -62,13 +68,13 @@ fn main() {
     // Loop while `n` is less than 101
     while n < 101 {
         if n % 15 == 0 {
-            println!("fizzbuzz");
+            writeln!(out, "fizzbuzz")?;
         } else if n % 3 == 0 {
-            println!("fizz");
+            writeln!(out, "fizz")?;
         } else if n % 5 == 0 {
-            println!("buzz");
+            writeln!(out, "buzz")?;
         } else {
-            println!("{}", n);
+            writeln!(out, "{}", n)?;
         }
 
         // Increment counter
@@ -78,26 +84,26 @@ fn main() {
     // `n` will take the values: 1, 2, ..., 100 in each iteration
     for n in 1..101 {
         if n % 15 == 0 {
-            println!("fizzbuzz");
+            writeln!(out, "fizzbuzz")?;
         } else if n % 3 == 0 {
-            println!("fizz");
+            writeln!(out, "fizz")?;
         } else if n % 5 == 0 {
-            println!("buzz");
+            writeln!(out, "buzz")?;
         } else {
-            println!("{}", n);
+            writeln!(out, "{}", n)?;
         }
     }
 
     // `n` will take the values: 1, 2, ..., 100 in each iteration
     for n in 1..=100 {
         if n % 15 == 0 {
-            println!("fizzbuzz");
+            writeln!(out, "fizzbuzz")?;
         } else if n % 3 == 0 {
-            println!("fizz");
+            writeln!(out, "fizz")?;
         } else if n % 5 == 0 {
-            println!("buzz");
+            writeln!(out, "buzz")?;
         } else {
-            println!("{}", n);
+            writeln!(out, "{}", n)?;
         }
     }
 
@@ -105,25 +111,25 @@ fn main() {
 
     for name in names.iter() {
         match name {
-            &"Ferris" => println!("There is a rustacean among us!"),
+            &"Ferris" => writeln!(out, "There is a rustacean among us!")?,
             // TODO ^ Try deleting the & and matching just "Ferris"
-            _ => println!("Hello {}", name),
+            _ => writeln!(out, "Hello {}", name)?,
         }
     }
 
-    println!("names: {:?}", names);
+    writeln!(out, "names: {:?}", names)?;
 
     let names = vec!["Bob", "Frank", "Ferris"];
 
     for name in names.into_iter() {
         match name {
-            "Ferris" => println!("There is a rustacean among us!"),
-            _ => println!("Hello {}", name),
+            "Ferris" => writeln!(out, "There is a rustacean among us!")?,
+            _ => writeln!(out, "Hello {}", name)?,
         }
     }
 
-    // println!("names: {:?}", names);
-    // FIXME ^ Comment out this line
+    // writeln!(out, "names: {:?}", names)?;
+    // FIXME ^ Comment out this line (error[E0382])
 
     let mut names = vec!["Bob", "Frank", "Ferris"];
 
@@ -134,5 +140,8 @@ fn main() {
         }
     }
 
-    println!("names: {:?}", names);
+    writeln!(out, "names: {:?}", names)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
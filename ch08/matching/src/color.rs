@@ -0,0 +1,583 @@
+// Conversions between the color models of `Color`, and parsing of CSS-like
+// color strings.
+//
+// The integer components of `Color` use these scales:
+//
+// * `RGB`: red, green and blue from 0 to 255
+// * `HSV` and `HSL`: hue in degrees from 0 to 359, the other two in percent
+// * `CMY` and `CMYK`: every component in percent
+//
+// Every conversion goes through the `f64` models below, where each
+// component lies between 0.0 and 1.0 (the hue between 0.0 and 360.0). In
+// that form all conversions are exact up to floating point error. Only the
+// final rounding to the integer scales above loses information, so an `RGB`
+// color sent through `HSV`, `HSL`, `CMY` or `CMYK` comes back within a few
+// units of where it started (see the tests for the exact tolerances).
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    pub h: f64,
+    pub s: f64,
+    pub v: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cmy {
+    pub c: f64,
+    pub m: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cmyk {
+    pub c: f64,
+    pub m: f64,
+    pub y: f64,
+    pub k: f64,
+}
+
+// Bring a hue into `0.0..360.0`
+fn normalize_hue(h: f64) -> f64 {
+    let h = h.rem_euclid(360.0);
+    // `rem_euclid` can round up to exactly 360.0 for tiny negative inputs
+    if h >= 360.0 {
+        0.0
+    } else {
+        h
+    }
+}
+
+// The hue shared by HSV and HSL, together with the largest and smallest
+// channel it was computed from
+fn hue_max_min(rgb: Rgb) -> (f64, f64, f64) {
+    let max = rgb.r.max(rgb.g).max(rgb.b);
+    let min = rgb.r.min(rgb.g).min(rgb.b);
+    let delta = max - min;
+
+    let h = if delta == 0.0 {
+        // Greys have no hue, 0 is the conventional choice
+        0.0
+    } else if max == rgb.r {
+        60.0 * ((rgb.g - rgb.b) / delta)
+    } else if max == rgb.g {
+        60.0 * ((rgb.b - rgb.r) / delta + 2.0)
+    } else {
+        60.0 * ((rgb.r - rgb.g) / delta + 4.0)
+    };
+
+    (normalize_hue(h), max, min)
+}
+
+impl From<Rgb> for Hsv {
+    fn from(rgb: Rgb) -> Hsv {
+        let (h, max, min) = hue_max_min(rgb);
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        Hsv { h, s, v: max }
+    }
+}
+
+impl From<Hsv> for Rgb {
+    fn from(hsv: Hsv) -> Rgb {
+        let h = normalize_hue(hsv.h);
+        let f = |n: f64| {
+            let k = (n + h / 60.0) % 6.0;
+            hsv.v - hsv.v * hsv.s * k.min(4.0 - k).clamp(0.0, 1.0)
+        };
+        Rgb {
+            r: f(5.0),
+            g: f(3.0),
+            b: f(1.0),
+        }
+    }
+}
+
+impl From<Rgb> for Hsl {
+    fn from(rgb: Rgb) -> Hsl {
+        let (h, max, min) = hue_max_min(rgb);
+        let l = (max + min) / 2.0;
+        let s = if l == 0.0 || l == 1.0 {
+            0.0
+        } else {
+            (max - l) / l.min(1.0 - l)
+        };
+        Hsl { h, s, l }
+    }
+}
+
+// This is the algorithm given in the CSS Color Module Level 4, so that
+// `hsl(…)` strings give the same colors as in a browser
+impl From<Hsl> for Rgb {
+    fn from(hsl: Hsl) -> Rgb {
+        let h = normalize_hue(hsl.h);
+        let a = hsl.s * hsl.l.min(1.0 - hsl.l);
+        let f = |n: f64| {
+            let k = (n + h / 30.0) % 12.0;
+            hsl.l - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+        };
+        Rgb {
+            r: f(0.0),
+            g: f(8.0),
+            b: f(4.0),
+        }
+    }
+}
+
+impl From<Rgb> for Cmy {
+    fn from(rgb: Rgb) -> Cmy {
+        Cmy {
+            c: 1.0 - rgb.r,
+            m: 1.0 - rgb.g,
+            y: 1.0 - rgb.b,
+        }
+    }
+}
+
+impl From<Cmy> for Rgb {
+    fn from(cmy: Cmy) -> Rgb {
+        Rgb {
+            r: 1.0 - cmy.c,
+            g: 1.0 - cmy.m,
+            b: 1.0 - cmy.y,
+        }
+    }
+}
+
+impl From<Rgb> for Cmyk {
+    fn from(rgb: Rgb) -> Cmyk {
+        let k = 1.0 - rgb.r.max(rgb.g).max(rgb.b);
+        if k == 1.0 {
+            // Pure black is all key, the other inks are undefined
+            return Cmyk {
+                c: 0.0,
+                m: 0.0,
+                y: 0.0,
+                k,
+            };
+        }
+        Cmyk {
+            c: (1.0 - rgb.r - k) / (1.0 - k),
+            m: (1.0 - rgb.g - k) / (1.0 - k),
+            y: (1.0 - rgb.b - k) / (1.0 - k),
+            k,
+        }
+    }
+}
+
+impl From<Cmyk> for Rgb {
+    fn from(cmyk: Cmyk) -> Rgb {
+        Rgb {
+            r: (1.0 - cmyk.c) * (1.0 - cmyk.k),
+            g: (1.0 - cmyk.m) * (1.0 - cmyk.k),
+            b: (1.0 - cmyk.y) * (1.0 - cmyk.k),
+        }
+    }
+}
+
+// Turn an integer component into a fraction of `scale`, clamping values that
+// are out of range
+fn unit(value: u32, scale: f64) -> f64 {
+    (value as f64 / scale).min(1.0)
+}
+
+// And back again
+fn scaled(value: f64, scale: f64) -> u32 {
+    (value.clamp(0.0, 1.0) * scale).round() as u32
+}
+
+fn degrees(h: f64) -> u32 {
+    // 359.6 rounds to 360, which is the same hue as 0
+    h.round() as u32 % 360
+}
+
+// Exact value of any `Color` in the `f64` RGB model
+impl From<&Color> for Rgb {
+    fn from(color: &Color) -> Rgb {
+        match *color {
+            // The named colors are the same as in `ch03/enums`:
+            // `Red = 0xff0000`, `Green = 0x00ff00` and `Blue = 0x0000ff`
+            Color::Red => Rgb {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+            },
+            Color::Green => Rgb {
+                r: 0.0,
+                g: 1.0,
+                b: 0.0,
+            },
+            Color::Blue => Rgb {
+                r: 0.0,
+                g: 0.0,
+                b: 1.0,
+            },
+            Color::RGB(r, g, b) => Rgb {
+                r: unit(r, 255.0),
+                g: unit(g, 255.0),
+                b: unit(b, 255.0),
+            },
+            Color::HSV(h, s, v) => Hsv {
+                h: h as f64,
+                s: unit(s, 100.0),
+                v: unit(v, 100.0),
+            }
+            .into(),
+            Color::HSL(h, s, l) => Hsl {
+                h: h as f64,
+                s: unit(s, 100.0),
+                l: unit(l, 100.0),
+            }
+            .into(),
+            Color::CMY(c, m, y) => Cmy {
+                c: unit(c, 100.0),
+                m: unit(m, 100.0),
+                y: unit(y, 100.0),
+            }
+            .into(),
+            Color::CMYK(c, m, y, k) => Cmyk {
+                c: unit(c, 100.0),
+                m: unit(m, 100.0),
+                y: unit(y, 100.0),
+                k: unit(k, 100.0),
+            }
+            .into(),
+        }
+    }
+}
+
+impl From<Rgb> for Color {
+    fn from(rgb: Rgb) -> Color {
+        Color::RGB(
+            scaled(rgb.r, 255.0),
+            scaled(rgb.g, 255.0),
+            scaled(rgb.b, 255.0),
+        )
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(hsv: Hsv) -> Color {
+        Color::HSV(degrees(hsv.h), scaled(hsv.s, 100.0), scaled(hsv.v, 100.0))
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(hsl: Hsl) -> Color {
+        Color::HSL(degrees(hsl.h), scaled(hsl.s, 100.0), scaled(hsl.l, 100.0))
+    }
+}
+
+impl From<Cmy> for Color {
+    fn from(cmy: Cmy) -> Color {
+        Color::CMY(
+            scaled(cmy.c, 100.0),
+            scaled(cmy.m, 100.0),
+            scaled(cmy.y, 100.0),
+        )
+    }
+}
+
+impl From<Cmyk> for Color {
+    fn from(cmyk: Cmyk) -> Color {
+        Color::CMYK(
+            scaled(cmyk.c, 100.0),
+            scaled(cmyk.m, 100.0),
+            scaled(cmyk.y, 100.0),
+            scaled(cmyk.k, 100.0),
+        )
+    }
+}
+
+impl Color {
+    pub fn to_rgb(self) -> Color {
+        Rgb::from(&self).into()
+    }
+
+    pub fn to_hsv(self) -> Color {
+        Hsv::from(Rgb::from(&self)).into()
+    }
+
+    pub fn to_hsl(self) -> Color {
+        Hsl::from(Rgb::from(&self)).into()
+    }
+
+    pub fn to_cmy(self) -> Color {
+        Cmy::from(Rgb::from(&self)).into()
+    }
+
+    pub fn to_cmyk(self) -> Color {
+        Cmyk::from(Rgb::from(&self)).into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseColorError {
+    // Not a hex code, `rgb(…)`, `hsl(…)` or a known name
+    UnknownFormat(String),
+    // A `#` not followed by exactly six hex digits
+    InvalidHex(String),
+    // `rgb(…)` and `hsl(…)` take exactly three components
+    WrongComponentCount(usize),
+    InvalidComponent(String),
+    ComponentOutOfRange(String),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseColorError::UnknownFormat(s) => write!(f, "unknown color `{}`", s),
+            ParseColorError::InvalidHex(s) => {
+                write!(f, "`{}` is not a `#rrggbb` hex color", s)
+            }
+            ParseColorError::WrongComponentCount(n) => {
+                write!(f, "expected 3 components, found {}", n)
+            }
+            ParseColorError::InvalidComponent(s) => write!(f, "invalid component `{}`", s),
+            ParseColorError::ComponentOutOfRange(s) => {
+                write!(f, "component `{}` is out of range", s)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseColorError {}
+
+// Split the inside of `name(…)` into its components, if `s` has that form
+fn arguments<'a>(s: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let rest = s.strip_prefix(name)?.trim_start();
+    let inner = rest.strip_prefix('(')?.strip_suffix(')')?;
+    Some(inner.split(',').map(str::trim).collect())
+}
+
+fn component(s: &str, max: u32) -> Result<u32, ParseColorError> {
+    let value: u32 = s
+        .parse()
+        .map_err(|_| ParseColorError::InvalidComponent(s.to_owned()))?;
+    if value > max {
+        return Err(ParseColorError::ComponentOutOfRange(s.to_owned()));
+    }
+    Ok(value)
+}
+
+// CSS allows any angle, negative or fractional, and wraps it around
+fn hue(s: &str) -> Result<u32, ParseColorError> {
+    let s = s.strip_suffix("deg").unwrap_or(s);
+    match s.parse::<f64>() {
+        Ok(h) if h.is_finite() => Ok(degrees(normalize_hue(h))),
+        _ => Err(ParseColorError::InvalidComponent(s.to_owned())),
+    }
+}
+
+fn three<'a>(components: &[&'a str]) -> Result<[&'a str; 3], ParseColorError> {
+    match *components {
+        [a, b, c] => Ok([a, b, c]),
+        _ => Err(ParseColorError::WrongComponentCount(components.len())),
+    }
+}
+
+// Accepts `#rrggbb`, `rgb(r, g, b)`, `hsl(h, s%, l%)` and the names `Red`,
+// `Green` and `Blue`. Hex codes and names are returned as `RGB` and the
+// named variants, `hsl(…)` as `HSL` without any conversion.
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ParseColorError::InvalidHex(s.to_owned()));
+            }
+            // Six hex digits always fit, so these can't fail
+            let channel = |i: usize| u32::from_str_radix(&hex[i..i + 2], 16).unwrap();
+            return Ok(Color::RGB(channel(0), channel(2), channel(4)));
+        }
+
+        if let Some(components) = arguments(s, "rgb") {
+            let [r, g, b] = three(&components)?;
+            return Ok(Color::RGB(
+                component(r, 255)?,
+                component(g, 255)?,
+                component(b, 255)?,
+            ));
+        }
+
+        if let Some(components) = arguments(s, "hsl") {
+            let [h, s, l] = three(&components)?;
+            let percent = |c: &str| component(c.strip_suffix('%').unwrap_or(c), 100);
+            return Ok(Color::HSL(hue(h)?, percent(s)?, percent(l)?));
+        }
+
+        match s {
+            "Red" => Ok(Color::Red),
+            "Green" => Ok(Color::Green),
+            "Blue" => Ok(Color::Blue),
+            _ => Err(ParseColorError::UnknownFormat(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Largest difference between two colors, both taken as `RGB`
+    fn rgb_distance(a: &Color, b: &Color) -> u32 {
+        match (a.to_rgb(), b.to_rgb()) {
+            (Color::RGB(r1, g1, b1), Color::RGB(r2, g2, b2)) => {
+                r1.abs_diff(r2).max(g1.abs_diff(g2)).max(b1.abs_diff(b2))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn close(a: Rgb, b: Rgb) -> bool {
+        (a.r - b.r).abs() < 1e-9 && (a.g - b.g).abs() < 1e-9 && (a.b - b.b).abs() < 1e-9
+    }
+
+    // A coarse grid over the RGB cube, including all corners
+    fn rgb_grid() -> Vec<Color> {
+        let steps = [0, 1, 17, 64, 127, 128, 200, 254, 255];
+        let mut colors = Vec::new();
+        for &r in &steps {
+            for &g in &steps {
+                for &b in &steps {
+                    colors.push(Color::RGB(r, g, b));
+                }
+            }
+        }
+        colors
+    }
+
+    #[test]
+    fn test_float_models_round_trip() {
+        for color in rgb_grid() {
+            let rgb = Rgb::from(&color);
+            assert!(close(Rgb::from(Hsv::from(rgb)), rgb), "{:?}", color);
+            assert!(close(Rgb::from(Hsl::from(rgb)), rgb), "{:?}", color);
+            assert!(close(Rgb::from(Cmy::from(rgb)), rgb), "{:?}", color);
+            assert!(close(Rgb::from(Cmyk::from(rgb)), rgb), "{:?}", color);
+        }
+    }
+
+    #[test]
+    fn test_integer_models_round_trip() {
+        // Percent steps are 2.55 RGB units wide, and the hue is rounded to
+        // whole degrees on top of that for HSV and HSL
+        for color in rgb_grid() {
+            assert!(rgb_distance(&color.to_cmy(), &color) <= 2, "{:?}", color);
+            assert!(rgb_distance(&color.to_cmyk(), &color) <= 2, "{:?}", color);
+            assert!(rgb_distance(&color.to_hsv(), &color) <= 3, "{:?}", color);
+            assert!(rgb_distance(&color.to_hsl(), &color) <= 3, "{:?}", color);
+        }
+    }
+
+    #[test]
+    fn test_cmy_round_trip_is_exact() {
+        for color in rgb_grid() {
+            let rgb = Rgb::from(&color);
+            assert_eq!(Color::from(Rgb::from(Cmy::from(rgb))), color);
+        }
+    }
+
+    #[test]
+    fn test_hsl_matches_css() {
+        // Reference values as computed by browsers
+        let cases = [
+            ("hsl(0, 100%, 50%)", Color::RGB(255, 0, 0)),
+            ("hsl(120, 100%, 25%)", Color::RGB(0, 128, 0)),
+            ("hsl(240, 100%, 50%)", Color::RGB(0, 0, 255)),
+            ("hsl(210, 50%, 40%)", Color::RGB(51, 102, 153)),
+            ("hsl(30, 100%, 50%)", Color::RGB(255, 128, 0)),
+            ("hsl(180, 100%, 25%)", Color::RGB(0, 128, 128)),
+            ("hsl(0, 0%, 50%)", Color::RGB(128, 128, 128)),
+            ("hsl(420, 100%, 50%)", Color::RGB(255, 255, 0)),
+        ];
+        for (css, rgb) in cases {
+            let color: Color = css.parse().unwrap();
+            assert_eq!(color.to_rgb(), rgb, "{}", css);
+        }
+    }
+
+    #[test]
+    fn test_known_conversions() {
+        let orange = Color::RGB(255, 128, 0);
+        assert_eq!(orange.to_hsv(), Color::HSV(30, 100, 100));
+        assert_eq!(orange.to_hsl(), Color::HSL(30, 100, 50));
+        assert_eq!(orange.to_cmy(), Color::CMY(0, 50, 100));
+        assert_eq!(orange.to_cmyk(), Color::CMYK(0, 50, 100, 0));
+        assert_eq!(Color::RGB(0, 0, 0).to_cmyk(), Color::CMYK(0, 0, 0, 100));
+        assert_eq!(Color::HSV(0, 0, 100).to_rgb(), Color::RGB(255, 255, 255));
+    }
+
+    #[test]
+    fn test_named_colors() {
+        assert_eq!(
+            "Red".parse::<Color>().unwrap().to_rgb(),
+            Color::RGB(255, 0, 0)
+        );
+        assert_eq!(
+            "Green".parse::<Color>().unwrap().to_rgb(),
+            Color::RGB(0, 255, 0)
+        );
+        assert_eq!(
+            "Blue".parse::<Color>().unwrap().to_rgb(),
+            Color::RGB(0, 0, 255)
+        );
+        assert_eq!(Color::Blue.to_hsl(), Color::HSL(240, 100, 50));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("#7a1128".parse(), Ok(Color::RGB(122, 17, 40)));
+        assert_eq!("#FFfF00".parse(), Ok(Color::RGB(255, 255, 0)));
+        assert_eq!("rgb( 1, 2 ,3 )".parse(), Ok(Color::RGB(1, 2, 3)));
+        assert_eq!("hsl(210deg, 50%, 40)".parse(), Ok(Color::HSL(210, 50, 40)));
+        assert_eq!("hsl(-30, 50%, 40%)".parse(), Ok(Color::HSL(330, 50, 40)));
+        assert_eq!("hsl(12.5deg, 50%, 40%)".parse(), Ok(Color::HSL(13, 50, 40)));
+        assert_eq!("hsl(-0.2, 50%, 40%)".parse(), Ok(Color::HSL(0, 50, 40)));
+
+        assert_eq!(
+            "#12345".parse::<Color>(),
+            Err(ParseColorError::InvalidHex("#12345".to_owned()))
+        );
+        assert_eq!(
+            "rgb(1, 2)".parse::<Color>(),
+            Err(ParseColorError::WrongComponentCount(2))
+        );
+        assert_eq!(
+            "rgb(1, 2, 256)".parse::<Color>(),
+            Err(ParseColorError::ComponentOutOfRange("256".to_owned()))
+        );
+        assert_eq!(
+            "hsl(1, x, 2)".parse::<Color>(),
+            Err(ParseColorError::InvalidComponent("x".to_owned()))
+        );
+        assert_eq!(
+            "hsl(infdeg, 2%, 3%)".parse::<Color>(),
+            Err(ParseColorError::InvalidComponent("inf".to_owned()))
+        );
+        assert_eq!(
+            "red".parse::<Color>(),
+            Err(ParseColorError::UnknownFormat("red".to_owned()))
+        );
+    }
+}
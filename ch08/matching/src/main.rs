@@ -1,18 +1,26 @@
-fn main() {
+use std::io::{self, Write};
+
+mod color;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let number = 13;
     // TODO ^ Try different values for `number`
 
-    println!("Tell me about {}", number);
+    writeln!(out, "Tell me about {}", number)?;
     match number {
         // Match a single value
-        1 => println!("One!"),
+        1 => writeln!(out, "One!")?,
         // Match several values
-        2 | 3 | 5 | 7 | 11 => println!("This is a prime"),
+        2 | 3 | 5 | 7 | 11 => writeln!(out, "This is a prime")?,
         // TODO ^ Try adding 13 to the list of prime values
         // Match an inclusive range
-        13..=19 => println!("A teen"),
+        13..=19 => writeln!(out, "A teen")?,
         // Handle the rest of cases
-        _ => println!("Ain't special"),
+        _ => writeln!(out, "Ain't special")?,
         // TODO ^ Try commenting out this catch-all arm
     }
 
@@ -25,42 +33,63 @@ fn main() {
         // TODO ^ Try commenting out one of these arms
     };
 
-    println!("{} -> {}", boolean, binary);
+    writeln!(out, "{} -> {}", boolean, binary)?;
 
     let triple = (0, -2, 3);
     // TODO ^ Try different values for `triple`
 
-    println!("Tell me about {:?}", triple);
+    writeln!(out, "Tell me about {:?}", triple)?;
     // Match can be used to destructure a tuple
     match triple {
         // Destructure the second and third elements
-        (0, y, z) => println!("First is `0`, `y` is {:?}, and `z` is {:?}", y, z),
-        (1, ..) => println!("First is `1` and the rest doesn't matter"),
+        (0, y, z) => writeln!(out, "First is `0`, `y` is {:?}, and `z` is {:?}", y, z)?,
+        (1, ..) => writeln!(out, "First is `1` and the rest doesn't matter")?,
         // `..` can be used to ignore the rest of the tuple
-        _ => println!("It doesn't matter what they are"),
+        _ => writeln!(out, "It doesn't matter what they are")?,
         // `_` means don't bind the value to a variable
     }
 
     let color = Color::RGB(122, 17, 40);
     // TODO ^ Try different variants for `color`
 
-    println!("What color is it?");
+    writeln!(out, "What color is it?")?;
     // An `enum` can be destructured using a `match`.
     match color {
-        Color::Red => println!("The color is Red!"),
-        Color::Blue => println!("The color is Blue!"),
-        Color::Green => println!("The color is Green!"),
-        Color::RGB(r, g, b) => println!("Red: {}, green: {}, and blue: {}!", r, g, b),
-        Color::HSV(h, s, v) => println!("Hue: {}, saturation: {}, value: {}!", h, s, v),
-        Color::HSL(h, s, l) => println!("Hue: {}, saturation: {}, lightness: {}!", h, s, l),
-        Color::CMY(c, m, y) => println!("Cyan: {}, magenta: {}, yellow: {}!", c, m, y),
-        Color::CMYK(c, m, y, k) => println!(
+        Color::Red => writeln!(out, "The color is Red!")?,
+        Color::Blue => writeln!(out, "The color is Blue!")?,
+        Color::Green => writeln!(out, "The color is Green!")?,
+        Color::RGB(r, g, b) => writeln!(out, "Red: {}, green: {}, and blue: {}!", r, g, b)?,
+        Color::HSV(h, s, v) => writeln!(out, "Hue: {}, saturation: {}, value: {}!", h, s, v)?,
+        Color::HSL(h, s, l) => writeln!(out, "Hue: {}, saturation: {}, lightness: {}!", h, s, l)?,
+        Color::CMY(c, m, y) => writeln!(out, "Cyan: {}, magenta: {}, yellow: {}!", c, m, y)?,
+        Color::CMYK(c, m, y, k) => writeln!(
+            out,
             "Cyan: {}, magenta: {}, yellow: {}, key (black): {}!",
             c, m, y, k
-        ),
+        )?,
         // Don't need another arm because all variants have been examined
     }
 
+    // Any variant can be converted to any of the color models
+    writeln!(out, "As HSV: {:?}", color.to_hsv())?;
+    writeln!(out, "As HSL: {:?}", color.to_hsl())?;
+    writeln!(out, "As CMY: {:?}", color.to_cmy())?;
+    writeln!(out, "As CMYK: {:?}", color.to_cmyk())?;
+
+    // Colors can also be parsed from strings
+    for text in [
+        "#7a1128",
+        "rgb(122, 17, 40)",
+        "hsl(210, 50%, 40%)",
+        "Green",
+        "pink",
+    ] {
+        match text.parse::<Color>() {
+            Ok(parsed) => writeln!(out, "{} is {:?}", text, parsed.to_rgb())?,
+            Err(e) => writeln!(out, "{}: {}", text, e)?,
+        }
+    }
+
     // Assign a reference of type `i32`. The `&` signifies there
     // is a reference being assigned.
     let reference = &4;
@@ -72,12 +101,12 @@ fn main() {
         // `&val`
         // ^ We see that if the matching `&`s are dropped, then the `i32`
         // should be assigned to `val`.
-        &val => println!("Got a value via destructuring: {:?}", val),
+        &val => writeln!(out, "Got a value via destructuring: {:?}", val)?,
     }
 
     // To avoid the `&`, you dereference before matching.
     match *reference {
-        val => println!("Got a value via dereferencing: {:?}", val),
+        val => writeln!(out, "Got a value via dereferencing: {:?}", val)?,
     }
 
     // What if you don't start with a reference? `reference` was a `&`
@@ -97,7 +126,7 @@ fn main() {
 
     // Use `ref` keyword to create a reference.
     match value {
-        ref r => println!("Got a reference to a value: {:?}", r),
+        ref r => writeln!(out, "Got a reference to a value: {:?}", r)?,
     }
 
     // Use `ref mut` similarly.
@@ -106,7 +135,7 @@ fn main() {
             // Got a reference. Gotta dereference it before we can
             // add anything to it.
             *m += 10;
-            println!("We added 10. `mut_value`: {:?}", m);
+            writeln!(out, "We added 10. `mut_value`: {:?}", m)?;
         }
     }
 
@@ -119,65 +148,68 @@ fn main() {
     let foo = Foo { x: (1, 2), y: 3 };
 
     match foo {
-        Foo { x: (1, b), y } => println!("First of x is 1, b = {},  y = {} ", b, y),
+        Foo { x: (1, b), y } => writeln!(out, "First of x is 1, b = {},  y = {} ", b, y)?,
 
         // you can destructure structs and rename the variables,
         // the order is not important
-        Foo { y: 2, x: i } => println!("y is 2, i = {:?}", i),
+        Foo { y: 2, x: i } => writeln!(out, "y is 2, i = {:?}", i)?,
 
         // and you can also ignore some variables:
-        Foo { y, .. } => println!("y = {}, we don't care about x", y),
+        Foo { y, .. } => writeln!(out, "y = {}, we don't care about x", y)?,
         // this will give an error: pattern does not mention field `x`
-        //Foo { y } => println!("y = {}", y),
+        //Foo { y } => writeln!(out, "y = {}", y)?,
     }
 
     let pair = (2, -2);
     // TODO ^ Try different values for `pair`
 
-    println!("Tell me about {:?}", pair);
+    writeln!(out, "Tell me about {:?}", pair)?;
     match pair {
-        (x, y) if x == y => println!("These are twins"),
+        (x, y) if x == y => writeln!(out, "These are twins")?,
         // The ^ `if condition` part is a guard
-        (x, y) if x + y == 0 => println!("Antimatter, kaboom!"),
-        (x, _) if x % 2 == 1 => println!("The first one is odd"),
-        _ => println!("No correlation..."),
+        (x, y) if x + y == 0 => writeln!(out, "Antimatter, kaboom!")?,
+        (x, _) if x % 2 == 1 => writeln!(out, "The first one is odd")?,
+        _ => writeln!(out, "No correlation...")?,
     }
 
     let number: u8 = 4;
 
     match number {
-        i if i == 0 => println!("Zero"),
-        i if i > 0 => println!("Greater than zero"),
-        _ => println!("Fell through"), // This should not be possible to reach
+        i if i == 0 => writeln!(out, "Zero")?,
+        i if i > 0 => writeln!(out, "Greater than zero")?,
+        _ => writeln!(out, "Fell through")?, // This should not be possible to reach
     }
 
-    println!("Tell me what type of person you are");
+    writeln!(out, "Tell me what type of person you are")?;
 
     match age() {
-        0 => println!("I haven't celebrated my first birthday yet"),
+        0 => writeln!(out, "I haven't celebrated my first birthday yet")?,
         // Could `match` 1 ..= 12 directly but then what age
         // would the child be? Instead, bind to `n` for the
         // sequence of 1 ..= 12. Now the age can be reported.
-        n @ 1..=12 => println!("I'm a child of age {:?}", n),
-        n @ 13..=19 => println!("I'm a teen of age {:?}", n),
+        n @ 1..=12 => writeln!(out, "I'm a child of age {:?}", n)?,
+        n @ 13..=19 => writeln!(out, "I'm a teen of age {:?}", n)?,
         // Nothing bound. Return the result.
-        n => println!("I'm an old person of age {:?}", n),
+        n => writeln!(out, "I'm an old person of age {:?}", n)?,
     }
 
     match some_number() {
         // Got `Some` variant, match if its value, bound to `n`,
         // is equal to 42.
-        Some(n @ 42) => println!("The Answer: {}!", n),
+        Some(n @ 42) => writeln!(out, "The Answer: {}!", n)?,
         // Match any other number.
-        Some(n) => println!("Not interesting... {}", n),
+        Some(n) => writeln!(out, "Not interesting... {}", n)?,
         // Match anything else (`None` variant).
         _ => (),
     }
+
+    Ok(())
 }
 
 // `allow` required to silence warnings because only
 // one variant is used.
 #[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Color {
     // These 3 are specified solely by their name.
     Red,
@@ -199,3 +231,6 @@ fn age() -> u32 {
 fn some_number() -> Option<u32> {
     Some(42)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
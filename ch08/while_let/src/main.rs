@@ -1,4 +1,10 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Make `optional` of type `Option<i32>`
     let mut optional = Some(0);
 
@@ -8,10 +14,10 @@ fn main() {
             // If `optional` destructures, evaluate the block.
             Some(i) => {
                 if i > 9 {
-                    println!("Greater than 9, quit!");
+                    writeln!(out, "Greater than 9, quit!")?;
                     optional = None;
                 } else {
-                    println!("`i` is `{:?}`. Try again.", i);
+                    writeln!(out, "`i` is `{:?}`. Try again.", i)?;
                     optional = Some(i + 1);
                 }
                 // ^ Requires 3 indentations!
@@ -30,10 +36,10 @@ fn main() {
     // `Some(i)`, evaluate the block (`{}`). Else `break`.
     while let Some(i) = optional {
         if i > 9 {
-            println!("Greater than 9, quit!");
+            writeln!(out, "Greater than 9, quit!")?;
             optional = None;
         } else {
-            println!("`i` is `{:?}`. Try again.", i);
+            writeln!(out, "`i` is `{:?}`. Try again.", i)?;
             optional = Some(i + 1);
         }
         // ^ Less rightward drift and doesn't require
@@ -41,4 +47,9 @@ fn main() {
     }
     // ^ `if let` had additional optional `else`/`else if`
     // clauses. `while let` does not have these.
+
+    Ok(())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
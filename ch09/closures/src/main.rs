@@ -1,6 +1,11 @@
+use std::io::{self, Write};
 use std::mem;
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Increment via closures and functions.
     fn function1(i: i32) -> i32 {
         i + 1
@@ -15,32 +20,33 @@ fn main() {
 
     let i = 1;
     // Call the function and closures.
-    println!("function: {}", function1(i));
-    println!("closure_annotated: {}", closure_annotated(i));
-    println!("closure_inferred: {}", closure_inferred(i));
+    writeln!(out, "function: {}", function1(i))?;
+    writeln!(out, "closure_annotated: {}", closure_annotated(i))?;
+    writeln!(out, "closure_inferred: {}", closure_inferred(i))?;
 
     // A closure taking no arguments which returns an `i32`.
     // The return type is inferred.
     let one = || 1;
-    println!("closure returning one: {}", one());
+    writeln!(out, "closure returning one: {}", one())?;
 
     let color = String::from("green");
 
     // A closure to print `color` which immediately borrows (`&`) `color` and
     // stores the borrow and closure in the `print` variable. It will remain
-    // borrowed until `print` is used the last time.
+    // borrowed until `print` is used the last time. The output to print to
+    // is passed in, so the closures below only capture what they print.
     //
-    // `println!` only requires arguments by immutable reference so it doesn't
+    // `writeln!` only requires arguments by immutable reference so it doesn't
     // impose anything more restrictive.
-    let print = || println!("`color`: {}", color);
+    let print = |out: &mut dyn Write| writeln!(out, "`color`: {}", color);
 
     // Call the closure using the borrow.
-    print();
+    print(out)?;
 
     // `color` can be borrowed immutably again, because the closure only holds
     // an immutable reference to `color`.
     let _reborrow = &color;
-    print();
+    print(out)?;
 
     // A move or reborrow is allowed after the final use of `print`
     let _color_moved = color;
@@ -52,19 +58,19 @@ fn main() {
     //
     // A `mut` is required on `inc` because a `&mut` is stored inside. Thus,
     // calling the closure mutates the closure which requires a `mut`.
-    let mut inc = || {
+    let mut inc = |out: &mut dyn Write| {
         count += 1;
-        println!("`count`: {}", count);
+        writeln!(out, "`count`: {}", count)
     };
 
     // Call the closure using a mutable borrow.
-    inc();
+    inc(out)?;
 
     // The closure still mutably borrows `count` because it is called later.
     // An attempt to reborrow will lead to an error.
     // let _reborrow = &count;
-    // ^ TODO: try uncommenting this line.
-    inc();
+    // ^ TODO: try uncommenting this line (error[E0502])
+    inc(out)?;
 
     // The closure no longer needs to borrow `&mut count`. Therefore, it is
     // possible to reborrow without an error
@@ -77,15 +83,16 @@ fn main() {
     // would copy into the closure leaving the original untouched.
     // A non-copy must move and so `movable` immediately moves into
     // the closure.
-    let consume = || {
-        println!("`movable`: {:?}", movable);
+    let consume = |out: &mut dyn Write| -> io::Result<()> {
+        writeln!(out, "`movable`: {:?}", movable)?;
         mem::drop(movable);
+        Ok(())
     };
 
     // `consume` consumes the variable so this can only be called once.
-    consume();
-    // consume();
-    // ^ TODO: Try uncommenting this line.
+    consume(out)?;
+    // consume(out)?;
+    // ^ TODO: Try uncommenting this line (error[E0382])
 
     // `Vec` has non-copy semantics.
     let haystack = vec![1, 2, 3];
@@ -93,10 +100,10 @@ fn main() {
     // let contains = move |needle| haystack.contains(needle);
     let contains = |needle| haystack.contains(needle);
 
-    println!("{}", contains(&1));
-    println!("{}", contains(&4));
+    writeln!(out, "{}", contains(&1))?;
+    writeln!(out, "{}", contains(&4))?;
 
-    println!("There're {} elements in vec", haystack.len());
+    writeln!(out, "There're {} elements in vec", haystack.len())?;
     // ^ Uncommenting above line will result in compile-time error
     // because borrow checker doesn't allow re-using variable after it
     // has been moved.
@@ -112,66 +119,67 @@ fn main() {
 
     // Capture 2 variables: `greeting` by reference and
     // `farewell` by value.
-    let diary = || {
+    let diary = |out: &mut dyn Write| -> io::Result<()> {
         // `greeting` is by reference: requires `Fn`.
-        println!("I said {}.", greeting);
+        writeln!(out, "I said {}.", greeting)?;
 
         // Mutation forces `farewell` to be captured by
         // mutable reference. Now requires `FnMut`.
         farewell.push_str("!!!");
-        println!("Then I screamed {}.", farewell);
-        println!("Now I can sleep. zzzzz");
+        writeln!(out, "Then I screamed {}.", farewell)?;
+        writeln!(out, "Now I can sleep. zzzzz")?;
 
         // Manually calling drop forces `farewell` to
         // be captured by value. Now requires `FnOnce`.
         mem::drop(farewell);
+        Ok(())
     };
 
     // Call the function which applies the closure.
-    apply(diary);
+    apply(out, diary)?;
 
     // `double` satisfies `apply_to_3`'s trait bound
     let double = |x| 2 * x;
 
-    println!("3 doubled: {}", apply_to_3(double));
+    writeln!(out, "3 doubled: {}", apply_to_3(double))?;
 
     let x = 7;
 
     // Capture `x` into an anonymous type and implement
     // `Fn` for it. Store it in `print`.
-    let print = || println!("{}", x);
+    let print = |out: &mut dyn Write| writeln!(out, "{}", x);
 
-    apply(print);
+    apply(out, print)?;
 
     // Define a closure satisfying the `Fn` bound
-    let closure = || println!("I'm a closure!");
+    let closure = |out: &mut dyn Write| writeln!(out, "I'm a closure!");
 
-    call_me(closure);
-    call_me(function2);
+    call_me(out, closure)?;
+    call_me(out, function2)?;
 
     let fn_plain = create_fn();
     let mut fn_mut = create_fnmut();
     let fn_once = create_fnonce();
 
-    fn_plain();
-    fn_mut();
-    fn_once();
+    fn_plain(out)?;
+    fn_mut(out)?;
+    fn_once(out)?;
 
     let vec1 = vec![1, 2, 3];
     let vec2 = vec![4, 5, 6];
 
     // `iter()` for vecs yields `&i32`. Destructure to `i32`.
-    println!("2 in vec1: {}", vec1.iter().any(|&x| x == 2));
+    writeln!(out, "2 in vec1: {}", vec1.iter().any(|&x| x == 2))?;
     // `into_iter()` for vecs yields `i32`. No destructuring required.
-    println!("2 in vec2: {}", vec2.into_iter().any(|x| x == 2));
+    writeln!(out, "2 in vec2: {}", vec2.into_iter().any(|x| x == 2))?;
 
     let array1 = [1, 2, 3];
     let array2 = [4, 5, 6];
 
     // `iter()` for arrays yields `&i32`.
-    println!("2 in array1: {}", array1.iter().any(|&x| x == 2));
+    writeln!(out, "2 in array1: {}", array1.iter().any(|&x| x == 2))?;
     // `into_iter()` for arrays unusually yields `&i32`. no longer the case?
-    println!("2 in array2: {}", array2.into_iter().any(|x| x == 2));
+    writeln!(out, "2 in array2: {}", array2.into_iter().any(|x| x == 2))?;
 
     let vec1 = vec![1, 2, 3];
     let vec2 = vec![4, 5, 6];
@@ -183,21 +191,26 @@ fn main() {
 
     // `iter()` for vecs yields `&i32`, and we want to reference one of its
     // items, so we have to destructure `&&i32` to `i32`
-    println!("Find 2 in vec1: {:?}", iter.find(|&&x| x == 2));
+    writeln!(out, "Find 2 in vec1: {:?}", iter.find(|&&x| x == 2))?;
     // `into_iter()` for vecs yields `i32`, and we want to reference one of
     // its items, so we have to destructure `&i32` to `i32`
-    println!("Find 2 in vec2: {:?}", into_iter.find(|&x| x == 2));
+    writeln!(out, "Find 2 in vec2: {:?}", into_iter.find(|&x| x == 2))?;
 
     let array1 = [1, 2, 3];
     let array2 = [4, 5, 6];
 
     // `iter()` for arrays yields `&i32`
-    println!("Find 2 in array1: {:?}", array1.iter().find(|&&x| x == 2));
+    writeln!(
+        out,
+        "Find 2 in array1: {:?}",
+        array1.iter().find(|&&x| x == 2)
+    )?;
     // `into_iter()` for arrays unusually yields `&i32`. no longer the case?
-    println!(
+    writeln!(
+        out,
         "Find 2 in array2: {:?}",
         array2.into_iter().find(|&x| x == 2)
-    );
+    )?;
 
     let vec = vec![1, 9, 3, 3, 13, 2];
 
@@ -206,21 +219,23 @@ fn main() {
 
     let index_of_first_negative_number = vec.iter().position(|x| x < &0);
     assert_eq!(index_of_first_negative_number, None);
+
+    Ok(())
 }
 
 // A function which takes a closure as an argument and calls it.
 // <F> denotes that F is a "Generic type parameter"
-// `F` must implement `Fn` for a closure which takes no
-// inputs and returns nothing - exactly what is required
-// for `print`.
-fn apply<F>(f: F)
+// `F` must implement `Fn` for a closure which takes only the
+// output and returns whether writing to it worked - exactly
+// what is required for `print`.
+fn apply<F>(out: &mut dyn Write, f: F) -> io::Result<()>
 where
-    // The closure takes no input and returns nothing.
-    F: FnOnce(),
+    // The closure takes the output and returns the result of writing.
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
 {
     // ^ TODO: Try changing this to `Fn` or `FnMut`.
 
-    f();
+    f(out)
 }
 
 // A function which takes a closure and returns an `i32`.
@@ -234,29 +249,32 @@ where
 
 // Define a function which takes a generic `F` argument
 // bounded by `Fn`, and calls it
-fn call_me<F: Fn()>(f: F) {
-    f();
+fn call_me<F: Fn(&mut dyn Write) -> io::Result<()>>(out: &mut dyn Write, f: F) -> io::Result<()> {
+    f(out)
 }
 
 // Define a wrapper function satisfying the `Fn` bound
-fn function2() {
-    println!("I'm a function!");
+fn function2(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "I'm a function!")
 }
 
-fn create_fn() -> impl Fn() {
+fn create_fn() -> impl Fn(&mut dyn Write) -> io::Result<()> {
     let text = "Fn".to_owned();
 
-    move || println!("This is a: {}", text)
+    move |out| writeln!(out, "This is a: {}", text)
 }
 
-fn create_fnmut() -> impl FnMut() {
+fn create_fnmut() -> impl FnMut(&mut dyn Write) -> io::Result<()> {
     let text = "FnMut".to_owned();
 
-    move || println!("This is a: {}", text)
+    move |out| writeln!(out, "This is a: {}", text)
 }
 
-fn create_fnonce() -> impl FnOnce() {
+fn create_fnonce() -> impl FnOnce(&mut dyn Write) -> io::Result<()> {
     let text = "FnOnce".to_owned();
 
-    move || println!("This is a: {}", text)
+    move |out| writeln!(out, "This is a: {}", text)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
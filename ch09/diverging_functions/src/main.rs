@@ -1,11 +1,17 @@
 // #![feature(never_type)]
 
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let a: () = some_fn();
-    println!("This function returns and you can see this line.");
+    writeln!(out, "This function returns and you can see this line.")?;
 
     // let x: ! = panic!("This call never returns.");
-    // println!("You will never see this line!");
+    // writeln!(out, "You will never see this line!")?;
 
     fn sum_odd_numbers(up_to: u32) -> u32 {
         let mut acc = 0;
@@ -24,10 +30,13 @@ fn main() {
         }
         acc
     }
-    println!(
+    writeln!(
+        out,
         "Sum of odd numbers up to 9 (excluding): {}",
         sum_odd_numbers(9)
-    );
+    )?;
+
+    Ok(())
 }
 
 fn foo() -> ! {
@@ -37,3 +46,6 @@ fn foo() -> ! {
 fn some_fn() {
     ()
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
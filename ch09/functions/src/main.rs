@@ -1,7 +1,15 @@
+use std::io::{self, Write};
+
 // Unlike C/C++, there's no restriction on the order of function definitions
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // We can use this function here, and define it somewhere later
-    fizzbuzz_to(100);
+    fizzbuzz_to(out, 100)?;
+
+    Ok(())
 }
 
 // Function that returns a boolean value
@@ -15,23 +23,29 @@ fn is_divisible_by(lhs: u32, rhs: u32) -> bool {
     lhs % rhs == 0
 }
 
-// Functions that "don't" return a value, actually return the unit type `()`
-fn fizzbuzz(n: u32) -> () {
+// Functions that "don't" return a value, actually return the unit type `()`.
+// Writing to `out` can fail, so here it comes wrapped in an `io::Result`.
+fn fizzbuzz(out: &mut impl Write, n: u32) -> io::Result<()> {
     if is_divisible_by(n, 15) {
-        println!("fizzbuzz");
+        writeln!(out, "fizzbuzz")?;
     } else if is_divisible_by(n, 3) {
-        println!("fizz");
+        writeln!(out, "fizz")?;
     } else if is_divisible_by(n, 5) {
-        println!("buzz");
+        writeln!(out, "buzz")?;
     } else {
-        println!("{}", n);
+        writeln!(out, "{}", n)?;
     }
+    Ok(())
 }
 
 // When a function returns `()`, the return type can be omitted from the
-// signature
-fn fizzbuzz_to(n: u32) {
+// signature. Not here: `?` hands a failed write back to the caller.
+fn fizzbuzz_to(out: &mut impl Write, n: u32) -> io::Result<()> {
     for n in 1..=n {
-        fizzbuzz(n);
+        fizzbuzz(out, n)?;
     }
+    Ok(())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,5 +1,14 @@
-fn main() {
-    println!("Find the sum of all the squared odd numbers under 1000");
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "Find the sum of all the squared odd numbers under 1000"
+    )?;
     let upper = 1000;
 
     // Imperative approach
@@ -18,7 +27,7 @@ fn main() {
             acc += n_squared;
         }
     }
-    println!("imperative style: {}", acc);
+    writeln!(out, "imperative style: {}", acc)?;
 
     // Functional approach
     let sum_of_squared_odd_numbers: u32 = (0..)
@@ -26,9 +35,12 @@ fn main() {
         .take_while(|&n_squared| n_squared < upper) // Below upper limit
         .filter(|&n_squared| is_odd(n_squared)) // That are odd
         .fold(0, |acc, n_squared| acc + n_squared); // Sum them
-    println!("functional style: {}", sum_of_squared_odd_numbers);
+    writeln!(out, "functional style: {}", sum_of_squared_odd_numbers)
 }
 
 fn is_odd(n: u32) -> bool {
     n % 2 == 1
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,3 +1,5 @@
+use std::io::{self, Write};
+
 struct Point {
     x: f64,
     y: f64,
@@ -63,17 +65,21 @@ struct Pair(Box<i32>, Box<i32>);
 impl Pair {
     // This method "consumes" the resources of the caller object
     // `self` desugars to `self: Self`
-    fn destroy(self) {
+    fn destroy(self, out: &mut impl Write) -> io::Result<()> {
         // Destructure `self`
         let Pair(first, second) = self;
 
-        println!("Destroying Pair({}, {})", first, second);
+        writeln!(out, "Destroying Pair({}, {})", first, second)
 
         // `first` and `second` go out of scope and get freed
     }
 }
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let rectangle = Rectangle {
         // Associated functions are called using double colons
         p1: Point::origin(),
@@ -83,8 +89,8 @@ fn main() {
     // Methods are called using the dot operator
     // Note that the first argument `&self` is implicitly passed, i.e.
     // `rectangle.perimeter()` === `Rectangle::perimeter(&rectangle)`
-    println!("Rectangle perimeter: {}", rectangle.perimeter());
-    println!("Rectangle area: {}", rectangle.area());
+    writeln!(out, "Rectangle perimeter: {}", rectangle.perimeter())?;
+    writeln!(out, "Rectangle area: {}", rectangle.area())?;
 
     let mut square = Rectangle {
         p1: Point::origin(),
@@ -94,16 +100,21 @@ fn main() {
     // Error! `rectangle` is immutable, but this method requires a mutable
     // object
     //rectangle.translate(1.0, 0.0);
-    // TODO ^ Try uncommenting this line
+    // TODO ^ Try uncommenting this line (error[E0596])
 
     // Okay! Mutable objects can call mutable methods
     square.translate(1.0, 1.0);
 
     let pair = Pair(Box::new(1), Box::new(2));
 
-    pair.destroy();
+    pair.destroy(out)?;
 
     // Error! Previous `destroy` call "consumed" `pair`
-    //pair.destroy();
-    // TODO ^ Try uncommenting this line
+    //pair.destroy(out)?;
+    // TODO ^ Try uncommenting this line (error[E0382])
+
+    Ok(())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
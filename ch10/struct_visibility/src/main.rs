@@ -1,16 +1,22 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Public structs with public fields can be constructed as usual
     let open_box = my::OpenBox {
         contents: "public information",
     };
 
     // and their fields can be normally accessed.
-    println!("The open box contains: {}", open_box.contents);
+    writeln!(out, "The open box contains: {}", open_box.contents)?;
 
     // Public structs with private fields cannot be constructed using field names.
     // Error! `ClosedBox` has private fields
     //let closed_box = my::ClosedBox { contents: "classified information" };
-    // TODO ^ Try uncommenting this line
+    // TODO ^ Try uncommenting this line (error[E0451])
 
     // However, structs with private fields can be created using
     // public constructors
@@ -18,8 +24,10 @@ fn main() {
 
     // and the private fields of a public struct cannot be accessed.
     // Error! The `contents` field is private
-    //println!("The closed box contains: {}", _closed_box.contents);
-    // TODO ^ Try uncommenting this line
+    //writeln!(out, "The closed box contains: {}", _closed_box.contents)?;
+    // TODO ^ Try uncommenting this line (error[E0616])
+
+    Ok(())
 }
 
 mod my {
@@ -41,3 +49,6 @@ mod my {
         }
     }
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,49 +1,68 @@
-fn main() {
-    my::indirect_call();
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
 }
 
-fn function() {
-    println!("called `function()`");
+fn run(out: &mut impl Write) -> io::Result<()> {
+    my::indirect_call(out)?;
+
+    Ok(())
+}
+
+fn function(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "called `function()`")
 }
 
 mod cool {
-    pub fn function() {
-        println!("called `cool::function()`");
+    use std::io::{self, Write};
+
+    pub fn function(out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "called `cool::function()`")
     }
 }
 
 mod my {
-    fn function() {
-        println!("called `my::function()`");
+    use std::io::{self, Write};
+
+    fn function(out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "called `my::function()`")
     }
 
     mod cool {
-        pub fn function() {
-            println!("called `my::cool::function()`");
+        use std::io::{self, Write};
+
+        pub fn function(out: &mut impl Write) -> io::Result<()> {
+            writeln!(out, "called `my::cool::function()`")
         }
     }
 
-    pub fn indirect_call() {
+    pub fn indirect_call(out: &mut impl Write) -> io::Result<()> {
         // Let's access all the functions named `function` from this scope!
-        print!("called `my::indirect_call()`, that\n> ");
+        write!(out, "called `my::indirect_call()`, that\n> ")?;
 
         // The `self` keyword refers to the current module scope - in this case `my`.
         // Calling `self::function()` and calling `function()` directly both give
         // the same result, because they refer to the same function.
-        self::function();
-        function();
+        self::function(out)?;
+        function(out)?;
 
         // We can also use `self` to access another module inside `my`:
-        self::cool::function();
+        self::cool::function(out)?;
 
         // The `super` keyword refers to the parent scope (outside the `my` module).
-        super::function();
+        super::function(out)?;
 
         // This will bind to the `cool::function` in the *crate* scope.
         // In this case the crate scope is the outermost scope.
         {
             use crate::cool::function as root_function;
-            root_function();
+            root_function(out)?;
         }
+
+        Ok(())
     }
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
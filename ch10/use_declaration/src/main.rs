@@ -4,16 +4,22 @@
 //     AndATraitType
 // };
 
+use std::io::{self, Write};
+
 // Bind the `deeply::nested::function` path to `other_function`.
 use deeply::nested::function as other_function;
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // my_first_function();
 
     // Easier access to `deeply::nested::function`
-    other_function();
+    other_function(out)?;
 
-    println!("Entering block");
+    writeln!(out, "Entering block")?;
     {
         // This is equivalent to `use deeply::nested::function as function`.
         // This `function()` will shadow the outer one.
@@ -21,22 +27,29 @@ fn main() {
 
         // `use` bindings have a local scope. In this case, the
         // shadowing of `function()` is only in this block.
-        function();
+        function(out)?;
 
-        println!("Leaving block");
+        writeln!(out, "Leaving block")?;
     }
 
-    function();
+    function(out)?;
+
+    Ok(())
 }
 
-fn function() {
-    println!("called `function()`");
+fn function(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "called `function()`")
 }
 
 mod deeply {
     pub mod nested {
-        pub fn function() {
-            println!("called `deeply::nested::function()`");
+        use std::io::{self, Write};
+
+        pub fn function(out: &mut impl Write) -> io::Result<()> {
+            writeln!(out, "called `deeply::nested::function()`")
         }
     }
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,120 +1,155 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Modules allow disambiguation between items that have the same name.
-    function();
-    my_mod::function();
+    function(out)?;
+    my_mod::function(out)?;
 
     // Public items, including those inside nested modules, can be
     // accessed from outside the parent module.
-    my_mod::indirect_access();
-    my_mod::nested::function();
-    my_mod::call_public_function_in_my_mod();
+    my_mod::indirect_access(out)?;
+    my_mod::nested::function(out)?;
+    my_mod::call_public_function_in_my_mod(out)?;
 
     // pub(crate) items can be called from anywhere in the same crate
-    my_mod::public_function_in_crate();
+    my_mod::public_function_in_crate(out)?;
 
     // pub(in path) items can only be called from within the module specified
     // Error! function `public_function_in_my_mod` is private
-    //my_mod::nested::public_function_in_my_mod();
-    // TODO ^ Try uncommenting this line
+    //my_mod::nested::public_function_in_my_mod(out)?;
+    // TODO ^ Try uncommenting this line (error[E0603])
 
     // Private items of a module cannot be directly accessed, even if
     // nested in a public module:
 
     // Error! `private_function` is private
-    //my_mod::private_function();
-    // TODO ^ Try uncommenting this line
+    //my_mod::private_function(out)?;
+    // TODO ^ Try uncommenting this line (error[E0603])
 
     // Error! `private_function` is private
-    //my_mod::nested::private_function();
-    // TODO ^ Try uncommenting this line
+    //my_mod::nested::private_function(out)?;
+    // TODO ^ Try uncommenting this line (error[E0603])
 
     // Error! `private_nested` is a private module
-    //my_mod::private_nested::function();
-    // TODO ^ Try uncommenting this line
+    //my_mod::private_nested::function(out)?;
+    // TODO ^ Try uncommenting this line (error[E0603])
 
     // Error! `private_nested` is a private module
-    //my_mod::private_nested::restricted_function();
-    // TODO ^ Try uncommenting this line
+    //my_mod::private_nested::restricted_function(out)?;
+    // TODO ^ Try uncommenting this line (error[E0603])
+
+    Ok(())
 }
 
 // A module named `my_mod`
 mod my_mod {
+    use std::io::{self, Write};
+
     // Items in modules default to private visibility.
-    fn private_function() {
-        println!("called `my_mod::private_function()`");
+    fn private_function(out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "called `my_mod::private_function()`")
     }
 
     // Use the `pub` modifier to override default visibility.
-    pub fn function() {
-        println!("called `my_mod::function()`");
+    pub fn function(out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "called `my_mod::function()`")
     }
 
     // Items can access other items in the same module,
     // even when private.
-    pub fn indirect_access() {
-        print!("called `my_mod::indirect_access()`, that\n> ");
-        private_function();
+    pub fn indirect_access(out: &mut impl Write) -> io::Result<()> {
+        write!(out, "called `my_mod::indirect_access()`, that\n> ")?;
+        private_function(out)?;
+
+        Ok(())
     }
 
     // Modules can also be nested
     pub mod nested {
-        pub fn function() {
-            println!("called `my_mod::nested::function()`");
+        use std::io::{self, Write};
+
+        pub fn function(out: &mut impl Write) -> io::Result<()> {
+            writeln!(out, "called `my_mod::nested::function()`")
         }
 
         #[allow(dead_code)]
-        fn private_function() {
-            println!("called `my_mod::nested::private_function()`");
+        fn private_function(out: &mut impl Write) -> io::Result<()> {
+            writeln!(out, "called `my_mod::nested::private_function()`")
         }
 
         // Functions declared using `pub(in path)` syntax are only visible
         // within the given path. `path` must be a parent or ancestor module
-        pub(in crate::my_mod) fn public_function_in_my_mod() {
-            print!("called `my_mod::nested::public_function_in_my_mod()`, that\n> ");
-            public_function_in_nested();
+        pub(in crate::my_mod) fn public_function_in_my_mod(out: &mut impl Write) -> io::Result<()> {
+            write!(
+                out,
+                "called `my_mod::nested::public_function_in_my_mod()`, that\n> "
+            )?;
+            public_function_in_nested(out)?;
+
+            Ok(())
         }
 
         // Functions declared using `pub(self)` syntax are only visible within
         // the current module, which is the same as leaving them private
-        pub(self) fn public_function_in_nested() {
-            println!("called `my_mod::nested::public_function_in_nested()`");
+        pub(self) fn public_function_in_nested(out: &mut impl Write) -> io::Result<()> {
+            writeln!(out, "called `my_mod::nested::public_function_in_nested()`")
         }
 
         // Functions declared using `pub(super)` syntax are only visible within
         // the parent module
-        pub(super) fn public_function_in_super_mod() {
-            println!("called `my_mod::nested::public_function_in_super_mod()`");
+        pub(super) fn public_function_in_super_mod(out: &mut impl Write) -> io::Result<()> {
+            writeln!(
+                out,
+                "called `my_mod::nested::public_function_in_super_mod()`"
+            )
         }
     }
 
-    pub fn call_public_function_in_my_mod() {
-        print!("called `my_mod::call_public_function_in_my_mod()`, that\n> ");
-        nested::public_function_in_my_mod();
-        print!("> ");
-        nested::public_function_in_super_mod();
+    pub fn call_public_function_in_my_mod(out: &mut impl Write) -> io::Result<()> {
+        write!(
+            out,
+            "called `my_mod::call_public_function_in_my_mod()`, that\n> "
+        )?;
+        nested::public_function_in_my_mod(out)?;
+        write!(out, "> ")?;
+        nested::public_function_in_super_mod(out)?;
+
+        Ok(())
     }
 
     // pub(crate) makes functions visible only within the current crate
-    pub(crate) fn public_function_in_crate() {
-        println!("called `my_mod::public_function_in_crate()`");
+    pub(crate) fn public_function_in_crate(out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "called `my_mod::public_function_in_crate()`")
     }
 
     // Nested modules follow the same rules for visibility
     mod private_nested {
+        use std::io::{self, Write};
+
         #[allow(dead_code)]
-        pub fn function() {
-            println!("called `my_mod::private_nested::function()`");
+        pub fn function(out: &mut impl Write) -> io::Result<()> {
+            writeln!(out, "called `my_mod::private_nested::function()`")
         }
 
         // Private parent items will still restrict the visibility of a child item,
         // even if it is declared as visible within a bigger scope.
         #[allow(dead_code)]
-        pub(crate) fn restricted_function() {
-            println!("called `my_mod::private_nested::restricted_function()`");
+        pub(crate) fn restricted_function(out: &mut impl Write) -> io::Result<()> {
+            writeln!(
+                out,
+                "called `my_mod::private_nested::restricted_function()`"
+            )
         }
     }
 }
 
-fn function() {
-    println!("called `function()`");
+fn function(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "called `function()`")
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,3 +1,12 @@
-fn main() {
-    println!("Hello, world! (main.rs)");
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
 }
+
+fn run(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "Hello, world! (main.rs)")
+}
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -4,14 +4,20 @@ mod tests {
     use std::fs::OpenOptions;
     use std::io::Write;
 
+    // Each test gets a directory of its own, which is removed afterwards, so
+    // the tests can run in parallel without writing over each other
+    use testing::temp_dir::TempDir;
+
     // This test writes to a file
     #[test]
     fn test_file() {
+        let dir = TempDir::new();
+
         // Opens the file ferris.txt or creates one if it doesn't exist.
         let mut file = OpenOptions::new()
             .append(true)
             .create(true)
-            .open("ferris.txt")
+            .open(dir.join("ferris.txt"))
             .expect("Failed to open ferris.txt");
 
         // Print "Ferris" 5 times.
@@ -19,16 +25,20 @@ mod tests {
             file.write_all("Ferris\n".as_bytes())
                 .expect("Could not write to ferris.txt");
         }
+
+        dir.assert_file("ferris.txt", &"Ferris\n".repeat(5));
     }
 
-    // This test tries to write to the same file
+    // This test writes to a file of the same name, but not the same file
     #[test]
     fn test_file_also() {
+        let dir = TempDir::new();
+
         // Opens the file ferris.txt or creates one if it doesn't exist.
         let mut file = OpenOptions::new()
             .append(true)
             .create(true)
-            .open("ferris.txt")
+            .open(dir.join("ferris.txt"))
             .expect("Failed to open ferris.txt");
 
         // Print "Corro" 5 times.
@@ -36,5 +46,7 @@ mod tests {
             file.write_all("Corro\n".as_bytes())
                 .expect("Could not write to ferris.txt");
         }
+
+        dir.assert_file("ferris.txt", &"Corro\n".repeat(5));
     }
 }
@@ -1,8 +1,13 @@
 use std::fmt::Display;
 // A trait which implements the print marker: `{:?}`.
 use std::fmt::Debug;
+use std::io::{self, Write};
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Error! `Vec<T>` does not implement `Display`. This
     // specialization will fail.
     // let s = S(vec![1]);
@@ -16,12 +21,12 @@ fn main() {
         height: 4.0,
     };
 
-    print_debug(&rectangle);
-    println!("Area: {}", area(&rectangle));
+    print_debug(out, &rectangle)?;
+    writeln!(out, "Area: {}", area(&rectangle))?;
 
-    //print_debug(&_triangle);
-    //println!("Area: {}", area(&_triangle));
-    // ^ TODO: Try uncommenting these.
+    //print_debug(out, &_triangle)?;
+    //writeln!(out, "Area: {}", area(&_triangle))?;
+    // ^ TODO: Try uncommenting these (error[E0277])
     // | Error: Does not implement either `Debug` or `HasArea`.
 
     let cardinal = Cardinal;
@@ -30,30 +35,32 @@ fn main() {
 
     // `red()` won't work on a blue jay nor vice versa
     // because of the bounds.
-    println!("A cardinal is {}", red(&cardinal));
-    println!("A blue jay is {}", blue(&blue_jay));
-    //println!("A turkey is {}", red(&_turkey));
-    // ^ TODO: Try uncommenting this line.
+    writeln!(out, "A cardinal is {}", red(&cardinal))?;
+    writeln!(out, "A blue jay is {}", blue(&blue_jay))?;
+    //writeln!(out, "A turkey is {}", red(&_turkey))?;
+    // ^ TODO: Try uncommenting this line (error[E0277])
 
     let string = "words";
     let array = [1, 2, 3];
     let vec = vec![1, 2, 3];
 
-    compare_prints(&string);
-    //compare_prints(&array);
-    // TODO ^ Try uncommenting this.
+    compare_prints(out, &string)?;
+    //compare_prints(out, &array)?;
+    // TODO ^ Try uncommenting this (error[E0277])
 
-    compare_types(&array, &vec);
+    compare_types(out, &array, &vec)?;
 
     let vec = vec![1, 2, 3];
 
-    vec.print_in_option();
+    vec.print_in_option(out)?;
+
+    Ok(())
 }
 
 // Define a function `printer` that takes a generic type `T` which
 // must implement trait `Display`.
-fn printer<T: Display>(t: T) {
-    println!("{}", t);
+fn printer<T: Display>(out: &mut impl Write, t: T) -> io::Result<()> {
+    writeln!(out, "{}", t)
 }
 
 struct S<T: Display>(T);
@@ -81,8 +88,8 @@ struct Triangle {
 
 // The generic `T` must implement `Debug`. Regardless
 // of the type, this will work properly.
-fn print_debug<T: Debug>(t: &T) {
-    println!("{:?}", t);
+fn print_debug<T: Debug>(out: &mut impl Write, t: &T) -> io::Result<()> {
+    writeln!(out, "{:?}", t)
 }
 
 // `T` must implement `HasArea`. Any type which meets
@@ -110,14 +117,14 @@ fn blue<T: Blue>(_: &T) -> &'static str {
     "blue"
 }
 
-fn compare_prints<T: Debug + Display>(t: &T) {
-    println!("Debug: `{:?}`", t);
-    println!("Display: `{}`", t);
+fn compare_prints<T: Debug + Display>(out: &mut impl Write, t: &T) -> io::Result<()> {
+    writeln!(out, "Debug: `{:?}`", t)?;
+    writeln!(out, "Display: `{}`", t)
 }
 
-fn compare_types<T: Debug, U: Debug>(t: &T, u: &U) {
-    println!("t: `{:?}`", t);
-    println!("u: `{:?}`", u);
+fn compare_types<T: Debug, U: Debug>(out: &mut impl Write, t: &T, u: &U) -> io::Result<()> {
+    writeln!(out, "t: `{:?}`", t)?;
+    writeln!(out, "u: `{:?}`", u)
 }
 
 // impl<A: TraitB + TraitC, D: TraitE + TraitF> MyTrait<A, D> for YourType {}
@@ -131,7 +138,7 @@ fn compare_types<T: Debug, U: Debug>(t: &T, u: &U) {
 // }
 
 trait PrintInOption {
-    fn print_in_option(self);
+    fn print_in_option(self, out: &mut impl Write) -> io::Result<()>;
 }
 
 // Because we would otherwise have to express this as `T: Debug` or
@@ -143,7 +150,10 @@ where
 {
     // We want `Option<T>: Debug` as our bound because that is what's
     // being printed. Doing otherwise would be using the wrong bound.
-    fn print_in_option(self) {
-        println!("{:?}", Some(self));
+    fn print_in_option(self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{:?}", Some(self))
     }
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,4 +1,10 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // `Single` is concrete and explicitly takes `A`.
     let _s = Single(A);
 
@@ -26,7 +32,7 @@ fn main() {
     let x = Val { val: 3.0 };
     let y = GenVal { gen_val: 3i32 };
 
-    println!("{}, {}", x.value(), y.value());
+    writeln!(out, "{}, {}", x.value(), y.value())?;
 
     let empty = Empty;
     let null = Null;
@@ -36,7 +42,9 @@ fn main() {
 
     //empty;
     //null;
-    // ^ TODO: Try uncommenting these lines.
+    // ^ TODO: Try uncommenting these lines (error[E0382])
+
+    Ok(())
 }
 
 // A concrete type `A`.
@@ -127,3 +135,6 @@ impl<T, U> DoubleDrop<T> for U {
     // deallocating both.
     fn double_drop(self, _: T) {}
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
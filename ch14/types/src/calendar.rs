@@ -0,0 +1,466 @@
+// Dates in the proleptic Gregorian calendar, that is the Gregorian leap year
+// rules applied to every year, even before 1582. Years may be zero or
+// negative, as in ISO 8601: year 0 is 1 BC.
+use std::error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+pub fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+// The number of days in `month` of `year`, `None` if `month` isn't 1 to 12
+pub fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => Some(31),
+        4 | 6 | 9 | 11 => Some(30),
+        2 if is_leap_year(year) => Some(29),
+        2 => Some(28),
+        _ => None,
+    }
+}
+
+// The fields are private so that every `Date` is a valid one. Deriving the
+// order compares the year first, then the month, then the day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    // `None` if there is no such day, e.g. 2023-02-29
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if days_in_month(year, month).is_some_and(|days| (1..=days).contains(&day)) {
+            Some(Date { year, month, day })
+        } else {
+            None
+        }
+    }
+
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    pub fn month(self) -> u32 {
+        self.month
+    }
+
+    pub fn day(self) -> u32 {
+        self.day
+    }
+
+    // The number of days since 1970-01-01, negative before it. This and
+    // `from_unix_days` follow Howard Hinnant's `days_from_civil` and
+    // `civil_from_days`, which count in 400-year eras starting on March 1st
+    // so that the leap day is the last day of a year.
+    pub fn to_unix_days(self) -> i64 {
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    // `None` if the year doesn't fit in an `i32`
+    pub fn from_unix_days(days: i64) -> Option<Date> {
+        let days = days.checked_add(719_468)?;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = era * 400 + year_of_era + i64::from(month <= 2);
+        Some(Date {
+            year: i32::try_from(year).ok()?,
+            month: month as u32,
+            day: day as u32,
+        })
+    }
+
+    pub fn checked_add_days(self, days: i64) -> Option<Date> {
+        Date::from_unix_days(self.to_unix_days().checked_add(days)?)
+    }
+
+    // Moving by months keeps the day of the month if it can, and otherwise
+    // uses the last day: one month after January 31st is February 28th or
+    // 29th.
+    pub fn checked_add_months(self, months: i64) -> Option<Date> {
+        let total = i64::from(self.year) * 12 + i64::from(self.month) - 1;
+        let total = total.checked_add(months)?;
+        let year = i32::try_from(total.div_euclid(12)).ok()?;
+        let month = total.rem_euclid(12) as u32 + 1;
+        let day = self.day.min(days_in_month(year, month)?);
+        Some(Date { year, month, day })
+    }
+
+    // Add the years and months first, then the days, as ISO 8601 does
+    pub fn checked_add(self, period: Period) -> Option<Date> {
+        let months = i64::from(period.years) * 12 + i64::from(period.months);
+        self.checked_add_months(months)?
+            .checked_add_days(period.days.into())
+    }
+
+    // Take the days away first, then the months and years, so that
+    // subtracting undoes adding whenever no day had to be clamped
+    pub fn checked_sub(self, period: Period) -> Option<Date> {
+        let months = i64::from(period.years) * 12 + i64::from(period.months);
+        self.checked_add_days(-i64::from(period.days))?
+            .checked_add_months(-months)
+    }
+
+    // The age of someone born on this date, on the date `on`. `None` if
+    // they aren't born yet.
+    pub fn age_on(self, on: Date) -> Option<Period> {
+        Period::between(self, on)
+    }
+}
+
+impl Add<Period> for Date {
+    type Output = Date;
+
+    fn add(self, period: Period) -> Date {
+        self.checked_add(period).expect("date out of range")
+    }
+}
+
+impl Sub<Period> for Date {
+    type Output = Date;
+
+    fn sub(self, period: Period) -> Date {
+        self.checked_sub(period).expect("date out of range")
+    }
+}
+
+// The number of days from `rhs` to `self`
+impl Sub for Date {
+    type Output = i64;
+
+    fn sub(self, rhs: Date) -> i64 {
+        self.to_unix_days() - rhs.to_unix_days()
+    }
+}
+
+// ISO 8601, as in `2024-02-29`. Years past 9999 or before 0 get a sign and
+// at least four digits, as in `-0044-03-15`.
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if (0..=9999).contains(&self.year) {
+            write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+        } else {
+            write!(f, "{:+05}-{:02}-{:02}", self.year, self.month, self.day)
+        }
+    }
+}
+
+// A calendar period, as opposed to an exact number of days: a month is 28
+// to 31 days long depending on where it starts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Period {
+    pub years: u32,
+    pub months: u32,
+    pub days: u32,
+}
+
+impl Period {
+    pub fn new(years: u32, months: u32, days: u32) -> Period {
+        Period {
+            years,
+            months,
+            days,
+        }
+    }
+
+    // The period from `from` to `to`, in whole years and months and the
+    // remaining days, such that `from + period == to`. `None` if `to` is
+    // before `from`.
+    //
+    // Someone born on February 29th is a year older on March 1st in common
+    // years, since February 28th is still a day short of it.
+    pub fn between(from: Date, to: Date) -> Option<Period> {
+        if to < from {
+            return None;
+        }
+        let mut months = (i64::from(to.year) - i64::from(from.year)) * 12 + i64::from(to.month)
+            - i64::from(from.month);
+        if to.day < from.day {
+            months -= 1;
+        }
+        let days = to - from.checked_add_months(months)?;
+        Some(Period {
+            years: (months / 12) as u32,
+            months: (months % 12) as u32,
+            days: days as u32,
+        })
+    }
+}
+
+// ISO 8601, as in `P1Y2M10D`. Zero parts are left out, and the empty period
+// is `P0D`.
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "P")?;
+        if self.years != 0 {
+            write!(f, "{}Y", self.years)?;
+        }
+        if self.months != 0 {
+            write!(f, "{}M", self.months)?;
+        }
+        if self.days != 0 || *self == Period::default() {
+            write!(f, "{}D", self.days)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseDateError {
+    // Not laid out as `YYYY-MM-DD` or `PnYnMnD`
+    Format,
+    Number(ParseIntError),
+    // A valid layout for a day that doesn't exist, e.g. `2023-02-29`
+    OutOfRange,
+    // A period with hours, minutes or seconds, e.g. `PT12H`
+    TimeComponent,
+}
+
+impl fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseDateError::Format => write!(f, "not an ISO 8601 date or period"),
+            ParseDateError::Number(e) => write!(f, "invalid number: {}", e),
+            ParseDateError::OutOfRange => write!(f, "no such day"),
+            ParseDateError::TimeComponent => write!(f, "periods can't have a time part"),
+        }
+    }
+}
+
+impl error::Error for ParseDateError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ParseDateError::Number(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseIntError> for ParseDateError {
+    fn from(err: ParseIntError) -> Self {
+        ParseDateError::Number(err)
+    }
+}
+
+fn all_digits(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+impl FromStr for Date {
+    type Err = ParseDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The year may be signed, so split the month and day off the end
+        let mut parts = s.rsplitn(3, '-');
+        let (day, month, year) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(day), Some(month), Some(year)) => (day, month, year),
+            _ => return Err(ParseDateError::Format),
+        };
+        let digits = year.strip_prefix(['+', '-']).unwrap_or(year);
+        if digits.len() < 4
+            || !all_digits(digits, digits.len())
+            || !all_digits(month, 2)
+            || !all_digits(day, 2)
+        {
+            return Err(ParseDateError::Format);
+        }
+        Date::new(year.parse()?, month.parse()?, day.parse()?).ok_or(ParseDateError::OutOfRange)
+    }
+}
+
+// Accepts years, months, weeks and days in that order, as in `P1Y2M10D` or
+// `P3W`. Weeks are counted as 7 days.
+impl FromStr for Period {
+    type Err = ParseDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rest = s.strip_prefix('P').ok_or(ParseDateError::Format)?;
+        if rest.contains('T') {
+            return Err(ParseDateError::TimeComponent);
+        }
+        if rest.is_empty() {
+            return Err(ParseDateError::Format);
+        }
+
+        let mut period = Period::default();
+        // The designators that may still come
+        let mut designators = "YMWD";
+        while !rest.is_empty() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or(ParseDateError::Format)?;
+            let (number, tail) = rest.split_at(end);
+            let designator = tail.chars().next().unwrap();
+            let position = designators.find(designator).ok_or(ParseDateError::Format)?;
+            if number.is_empty() {
+                return Err(ParseDateError::Format);
+            }
+            let number: u32 = number.parse()?;
+            match designator {
+                'Y' => period.years = number,
+                'M' => period.months = number,
+                'W' => period.days = number.checked_mul(7).ok_or(ParseDateError::OutOfRange)?,
+                _ => {
+                    period.days = period
+                        .days
+                        .checked_add(number)
+                        .ok_or(ParseDateError::OutOfRange)?
+                }
+            }
+            designators = &designators[position + 1..];
+            rest = &tail[1..];
+        }
+        Ok(period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_leap_years() {
+        assert!(is_leap_year(2024));
+        assert!(!is_leap_year(2023));
+        assert!(!is_leap_year(1900));
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(0));
+        assert!(is_leap_year(-4));
+        assert_eq!(days_in_month(2024, 2), Some(29));
+        assert_eq!(days_in_month(2100, 2), Some(28));
+        assert_eq!(days_in_month(2023, 9), Some(30));
+        assert_eq!(days_in_month(2023, 0), None);
+        assert_eq!(days_in_month(2023, 13), None);
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(Date::new(2023, 13, 1), None);
+        assert_eq!(Date::new(2023, 4, 0), None);
+    }
+
+    #[test]
+    fn test_unix_days() {
+        assert_eq!(date("1970-01-01").to_unix_days(), 0);
+        assert_eq!(date("2000-03-01").to_unix_days(), 11_017);
+        assert_eq!(date("1969-12-31").to_unix_days(), -1);
+        assert_eq!(date("0000-03-01").to_unix_days(), -719_468);
+
+        // Every day is the one after the previous day
+        let mut previous = Date::from_unix_days(-800_000).unwrap();
+        for days in -799_999..800_000 {
+            let date = Date::from_unix_days(days).unwrap();
+            assert_eq!(date.to_unix_days(), days);
+            assert!(date > previous);
+            previous = date;
+        }
+        assert_eq!(Date::from_unix_days(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(
+            date("2024-01-31") + Period::new(0, 1, 0),
+            date("2024-02-29")
+        );
+        assert_eq!(
+            date("2023-01-31") + Period::new(0, 1, 0),
+            date("2023-02-28")
+        );
+        assert_eq!(
+            date("2024-02-29") + Period::new(1, 0, 0),
+            date("2025-02-28")
+        );
+        assert_eq!(
+            date("2023-12-25") + Period::new(0, 0, 10),
+            date("2024-01-04")
+        );
+        assert_eq!(
+            date("2024-01-04") - Period::new(0, 0, 10),
+            date("2023-12-25")
+        );
+        assert_eq!(date("2025-03-01") - date("2024-03-01"), 365);
+        assert_eq!(date("2024-03-01") - date("2023-03-01"), 366);
+    }
+
+    #[test]
+    fn test_age() {
+        let birth = date("2000-02-29");
+        assert_eq!(
+            birth.age_on(date("2018-02-28")),
+            Some(Period::new(17, 11, 30))
+        );
+        assert_eq!(
+            birth.age_on(date("2018-03-01")),
+            Some(Period::new(18, 0, 1))
+        );
+        assert_eq!(
+            birth.age_on(date("2020-02-29")),
+            Some(Period::new(20, 0, 0))
+        );
+        assert_eq!(birth.age_on(date("1999-12-31")), None);
+
+        // 365 days per year would already make this 18
+        let birth = date("2000-01-01");
+        assert_eq!(
+            birth.age_on(date("2017-12-28")),
+            Some(Period::new(17, 11, 27))
+        );
+
+        // Adding the age to the birth date gives the date back
+        let birth = date("1987-08-31");
+        for days in 0..5000 {
+            let on = birth.checked_add_days(days).unwrap();
+            assert_eq!(birth + birth.age_on(on).unwrap(), on);
+        }
+    }
+
+    #[test]
+    fn test_iso_8601() {
+        assert_eq!(date("2024-02-29").to_string(), "2024-02-29");
+        assert_eq!(date("-0044-03-15").year(), -44);
+        assert_eq!(date("-0044-03-15").to_string(), "-0044-03-15");
+        assert_eq!(date("+12345-01-01").to_string(), "+12345-01-01");
+        assert_eq!(
+            "2023-02-29".parse::<Date>(),
+            Err(ParseDateError::OutOfRange)
+        );
+        assert_eq!("2023-2-28".parse::<Date>(), Err(ParseDateError::Format));
+        assert_eq!("20230228".parse::<Date>(), Err(ParseDateError::Format));
+
+        assert_eq!("P1Y2M10D".parse(), Ok(Period::new(1, 2, 10)));
+        assert_eq!("P3W".parse(), Ok(Period::new(0, 0, 21)));
+        assert_eq!("P1M".parse(), Ok(Period::new(0, 1, 0)));
+        assert_eq!(Period::new(1, 2, 10).to_string(), "P1Y2M10D");
+        assert_eq!(Period::new(0, 5, 0).to_string(), "P5M");
+        assert_eq!(Period::default().to_string(), "P0D");
+        assert_eq!(
+            "PT12H".parse::<Period>(),
+            Err(ParseDateError::TimeComponent)
+        );
+        assert_eq!("P".parse::<Period>(), Err(ParseDateError::Format));
+        assert_eq!("P2D1Y".parse::<Period>(), Err(ParseDateError::Format));
+        assert_eq!("1Y".parse::<Period>(), Err(ParseDateError::Format));
+    }
+}
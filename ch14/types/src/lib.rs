@@ -0,0 +1,4 @@
+// Units of measure built on phantom types, and calendar dates. They live in
+// a library so that other examples can use them too.
+pub mod calendar;
+pub mod units;
@@ -1,12 +1,29 @@
+use std::io::{self, Write};
 use std::marker::PhantomData;
 use std::ops::Add;
 
-fn main() {
-    let age = Years(5);
-    let age_days = age.to_days();
-    println!("Old enough {}", old_enough(&age));
-    println!("Old enough {}", old_enough(&age_days.to_years()));
-    // println!("Old enough {}", old_enough(&age_days));
+use types::calendar::{Date, Period};
+use types::units::{self, Area, Celsius, Cm, Fahrenheit, Ft, Km, Min, Temperature, Time, H, M};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
+    let birth: Date = "2006-02-28".parse().unwrap();
+    let today = Date::new(2024, 2, 27).unwrap();
+    writeln!(out, "Age on {}: {}", today, birth.age_on(today).unwrap())?;
+    writeln!(out, "Old enough {}", old_enough(&birth, &today))?;
+    let tomorrow = today + "P1D".parse::<Period>().unwrap();
+    writeln!(out, "Old enough {}", old_enough(&birth, &tomorrow))?;
+    // A date can't be mistaken for an age:
+    // writeln!(out, "Old enough {}", old_enough(&birth.age_on(today), &today))?;
+    writeln!(
+        out,
+        "{} is {} days after 1970-01-01",
+        today,
+        today.to_unix_days()
+    )?;
 
     let years = Years(42);
     let years_as_primitive_1: i64 = years.0; // Tuple
@@ -17,32 +34,34 @@ fn main() {
 
     let container = Container(number_1, number_2);
 
-    println!(
+    writeln!(
+        out,
         "Does container contain {} and {}: {}",
         &number_1,
         &number_2,
         container.contains1(&number_1, &number_2)
-    );
-    println!("First number: {}", container.first1());
-    println!("Last number: {}", container.last1());
+    )?;
+    writeln!(out, "First number: {}", container.first1())?;
+    writeln!(out, "Last number: {}", container.last1())?;
 
-    println!("The difference is: {}", difference1(&container));
+    writeln!(out, "The difference is: {}", difference1(&container))?;
 
     let number_1 = 3;
     let number_2 = 10;
 
     let container = Container(number_1, number_2);
 
-    println!(
+    writeln!(
+        out,
         "Does container contain {} and {}: {}",
         &number_1,
         &number_2,
         container.contains2(&number_1, &number_2)
-    );
-    println!("First number: {}", container.first2());
-    println!("Last number: {}", container.last2());
+    )?;
+    writeln!(out, "First number: {}", container.first2())?;
+    writeln!(out, "Last number: {}", container.last2())?;
 
-    println!("The difference is: {}", difference2(&container));
+    writeln!(out, "The difference is: {}", difference2(&container))?;
 
     // Here, `f32` and `f64` are the hidden parameters.
     // PhantomTuple type specified as `<char, f32>`.
@@ -62,12 +81,12 @@ fn main() {
     };
 
     // Compile-time Error! Type mismatch so these cannot be compared:
-    //println!("_tuple1 == _tuple2 yields: {}",
-    //          _tuple1 == _tuple2);
+    //writeln!(out, "_tuple1 == _tuple2 yields: {}",
+    //          _tuple1 == _tuple2)?;
 
     // Compile-time Error! Type mismatch so these cannot be compared:
-    //println!("_struct1 == _struct2 yields: {}",
-    //          _struct1 == _struct2);
+    //writeln!(out, "_struct1 == _struct2 yields: {}",
+    //          _struct1 == _struct2)?;
 
     // Specifies `one_foot` to have phantom type parameter `Inch`.
     let one_foot: Length<Inch> = Length(12.0, PhantomData);
@@ -82,33 +101,60 @@ fn main() {
     let two_meters = one_meter + one_meter;
 
     // Addition works.
-    println!("one foot + one_foot = {:?} in", two_feet.0);
-    println!("one meter + one_meter = {:?} mm", two_meters.0);
+    writeln!(out, "one foot + one_foot = {:?} in", two_feet.0)?;
+    writeln!(out, "one meter + one_meter = {:?} mm", two_meters.0)?;
 
     // Nonsensical operations fail as they should:
     // Compile-time Error: type mismatch.
     //let one_feter = one_foot + one_meter;
-}
-
-struct Years(i64);
 
-struct Days(i64);
-
-impl Years {
-    pub fn to_days(&self) -> Days {
-        Days(self.0 * 365)
+    // The `units` library grows `Length` into units of measure. It doesn't
+    // mix units either, but they can be converted into one another.
+    let one_foot = units::Length::<units::Inch>::new(12.0);
+    let one_meter = units::Length::<units::Mm>::new(1000.0);
+    let one_feter = one_foot.convert::<units::Mm>() + one_meter;
+    writeln!(out, "one foot + one meter = {}", one_feter)?;
+    writeln!(
+        out,
+        "                     = {:.3}",
+        one_feter.convert::<M>()
+    )?;
+
+    // Multiplying lengths gives an area, dividing by a time gives a speed
+    let room: Area<Ft> = units::Length::<Ft>::new(12.0) * units::Length::new(10.0);
+    writeln!(out, "The room is {} or {:.2}", room, room.convert::<M>())?;
+    let speed = units::Length::<Km>::new(42.195) / Time::<H>::new(2.0);
+    writeln!(out, "A marathon in two hours is {:.2}", speed)?;
+    writeln!(
+        out,
+        "                        or {:.2}",
+        speed.convert::<M, Min>()
+    )?;
+
+    // Quantities parse from their printed form, in any unit of the same
+    // dimension
+    let height: units::Length<Cm> = "6 ft".parse().unwrap();
+    writeln!(out, "6 ft is {:.1}", height)?;
+    match "20 kg".parse::<units::Length<units::Inch>>() {
+        Ok(length) => writeln!(out, "20 kg is {}?", length)?,
+        Err(e) => writeln!(out, "20 kg is not a length: {}", e)?,
     }
+    let body: Temperature<Celsius> = "98.6 °F".parse().unwrap();
+    writeln!(out, "98.6 °F is {:.1}", body)?;
+    writeln!(
+        out,
+        "0 °C is {}",
+        Temperature::<Celsius>::new(0.0).convert::<Fahrenheit>()
+    )?;
+
+    Ok(())
 }
 
-impl Days {
-    /// truncates partial years
-    pub fn to_years(&self) -> Years {
-        Years(self.0 / 365)
-    }
-}
+struct Years(i64);
 
-fn old_enough(age: &Years) -> bool {
-    age.0 >= 18
+// Whether someone born on `birth` is an adult on the date `on`
+fn old_enough(birth: &Date, on: &Date) -> bool {
+    birth.age_on(*on).is_some_and(|age| age.years >= 18)
 }
 
 struct Container(i32, i32);
@@ -224,3 +270,6 @@ impl<Unit> Add for Length<Unit> {
         Length(self.0 + rhs.0, PhantomData)
     }
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -0,0 +1,552 @@
+// Units of measure, built on the phantom type trick of `Length<Unit>`: the
+// unit is only known to the type checker, so a `Length<Inch>` is stored as a
+// bare `f64`, but adding it to a `Length<Mm>` doesn't compile.
+//
+// Going from one unit to another is always explicit, with `convert::<To>()`.
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::num::ParseFloatError;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use formatting::flags::{number, pad};
+
+// Every unit has the symbol it is printed and parsed with
+pub trait Unit: Copy {
+    const SYMBOL: &'static str;
+}
+
+// A unit that is a fixed multiple of the SI unit of its dimension, e.g. an
+// inch is 0.0254 meters
+pub trait LinearUnit: Unit {
+    const FACTOR: f64;
+}
+
+pub trait LengthUnit: LinearUnit {}
+pub trait MassUnit: LinearUnit {}
+pub trait TimeUnit: LinearUnit {}
+
+// Defines void enumerations as units of one dimension, and a table of them
+// used to parse any of their symbols.
+macro_rules! linear_units {
+    ($dimension:ident, $table:ident: $($unit:ident = $symbol:literal, $factor:expr;)+) => {
+        $(
+            #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+            pub enum $unit {}
+
+            impl Unit for $unit {
+                const SYMBOL: &'static str = $symbol;
+            }
+
+            impl LinearUnit for $unit {
+                const FACTOR: f64 = $factor;
+            }
+
+            impl $dimension for $unit {}
+        )+
+
+        const $table: &[(&str, f64)] = &[$(($symbol, $factor)),+];
+    };
+}
+
+linear_units! { LengthUnit, LENGTH_UNITS:
+    Mm = "mm", 0.001;
+    Cm = "cm", 0.01;
+    M = "m", 1.0;
+    Km = "km", 1000.0;
+    Inch = "in", 0.0254;
+    Ft = "ft", 0.3048;
+    Mi = "mi", 1609.344;
+}
+
+linear_units! { MassUnit, MASS_UNITS:
+    Mg = "mg", 1e-6;
+    G = "g", 0.001;
+    Kg = "kg", 1.0;
+    Oz = "oz", 0.028349523125;
+    Lb = "lb", 0.45359237;
+}
+
+linear_units! { TimeUnit, TIME_UNITS:
+    Ms = "ms", 0.001;
+    S = "s", 1.0;
+    Min = "min", 60.0;
+    H = "h", 3600.0;
+}
+
+// Temperature scales don't share their zero, so converting between them
+// goes through kelvins instead of a single factor.
+pub trait TemperatureUnit: Unit {
+    fn to_kelvin(value: f64) -> f64;
+    fn from_kelvin(kelvin: f64) -> f64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Kelvin {}
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Celsius {}
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Fahrenheit {}
+
+impl Unit for Kelvin {
+    const SYMBOL: &'static str = "K";
+}
+
+impl TemperatureUnit for Kelvin {
+    fn to_kelvin(value: f64) -> f64 {
+        value
+    }
+    fn from_kelvin(kelvin: f64) -> f64 {
+        kelvin
+    }
+}
+
+impl Unit for Celsius {
+    const SYMBOL: &'static str = "°C";
+}
+
+impl TemperatureUnit for Celsius {
+    fn to_kelvin(value: f64) -> f64 {
+        value + 273.15
+    }
+    fn from_kelvin(kelvin: f64) -> f64 {
+        kelvin - 273.15
+    }
+}
+
+impl Unit for Fahrenheit {
+    const SYMBOL: &'static str = "°F";
+}
+
+impl TemperatureUnit for Fahrenheit {
+    fn to_kelvin(value: f64) -> f64 {
+        (value - 32.0) * 5.0 / 9.0 + 273.15
+    }
+    fn from_kelvin(kelvin: f64) -> f64 {
+        (kelvin - 273.15) * 9.0 / 5.0 + 32.0
+    }
+}
+
+type ToKelvin = fn(f64) -> f64;
+
+const TEMPERATURE_UNITS: &[(&str, ToKelvin)] = &[
+    ("K", Kelvin::to_kelvin),
+    ("°C", Celsius::to_kelvin),
+    ("°F", Fahrenheit::to_kelvin),
+];
+
+// Defines a quantity as a tuple struct of its value and a phantom unit,
+// with the arithmetic that keeps the unit: adding or subtracting two values
+// of the same unit, and scaling by a plain `f64`.
+macro_rules! quantity {
+    ($name:ident<$unit:ident: $bound:ident>) => {
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+        pub struct $name<$unit>(pub f64, pub PhantomData<$unit>);
+
+        impl<$unit: $bound> $name<$unit> {
+            pub fn new(value: f64) -> Self {
+                $name(value, PhantomData)
+            }
+
+            pub fn value(self) -> f64 {
+                self.0
+            }
+        }
+
+        impl<$unit: $bound> Add for $name<$unit> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                $name::new(self.0 + rhs.0)
+            }
+        }
+
+        impl<$unit: $bound> Sub for $name<$unit> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                $name::new(self.0 - rhs.0)
+            }
+        }
+
+        impl<$unit: $bound> Neg for $name<$unit> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $name::new(-self.0)
+            }
+        }
+
+        impl<$unit: $bound> Mul<f64> for $name<$unit> {
+            type Output = Self;
+
+            fn mul(self, rhs: f64) -> Self {
+                $name::new(self.0 * rhs)
+            }
+        }
+
+        impl<$unit: $bound> Div<f64> for $name<$unit> {
+            type Output = Self;
+
+            fn div(self, rhs: f64) -> Self {
+                $name::new(self.0 / rhs)
+            }
+        }
+
+        // Dividing two values of the same unit gives a plain ratio
+        impl<$unit: $bound> Div for $name<$unit> {
+            type Output = f64;
+
+            fn div(self, rhs: Self) -> f64 {
+                self.0 / rhs.0
+            }
+        }
+
+        impl<$unit: $bound> fmt::Display for $name<$unit> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                with_unit(f, self.0, $unit::SYMBOL)
+            }
+        }
+    };
+}
+
+quantity!(Length<U: LengthUnit>);
+quantity!(Mass<U: MassUnit>);
+quantity!(Time<U: TimeUnit>);
+
+impl<U: LengthUnit> Length<U> {
+    pub fn convert<To: LengthUnit>(self) -> Length<To> {
+        Length::new(self.0 * (U::FACTOR / To::FACTOR))
+    }
+}
+
+impl<U: MassUnit> Mass<U> {
+    pub fn convert<To: MassUnit>(self) -> Mass<To> {
+        Mass::new(self.0 * (U::FACTOR / To::FACTOR))
+    }
+}
+
+impl<U: TimeUnit> Time<U> {
+    pub fn convert<To: TimeUnit>(self) -> Time<To> {
+        Time::new(self.0 * (U::FACTOR / To::FACTOR))
+    }
+}
+
+// A temperature is a point on a scale, not an amount: adding 20 °C to 20 °C
+// means nothing, so `Temperature` has no arithmetic, only conversions.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Temperature<U>(pub f64, pub PhantomData<U>);
+
+impl<U: TemperatureUnit> Temperature<U> {
+    pub fn new(value: f64) -> Self {
+        Temperature(value, PhantomData)
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    pub fn convert<To: TemperatureUnit>(self) -> Temperature<To> {
+        Temperature::new(To::from_kelvin(U::to_kelvin(self.0)))
+    }
+}
+
+impl<U: TemperatureUnit> fmt::Display for Temperature<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        with_unit(f, self.0, U::SYMBOL)
+    }
+}
+
+// An area, in square `U`s
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Area<U>(pub f64, pub PhantomData<U>);
+
+impl<U: LengthUnit> Area<U> {
+    pub fn new(value: f64) -> Self {
+        Area(value, PhantomData)
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    pub fn convert<To: LengthUnit>(self) -> Area<To> {
+        let factor = U::FACTOR / To::FACTOR;
+        Area::new(self.0 * factor * factor)
+    }
+}
+
+impl<U: LengthUnit> Add for Area<U> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Area::new(self.0 + rhs.0)
+    }
+}
+
+impl<U: LengthUnit> fmt::Display for Area<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        with_unit(f, self.0, &format!("{}²", U::SYMBOL))
+    }
+}
+
+// A speed, in `L`s per `T`
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Speed<L, T>(pub f64, pub PhantomData<(L, T)>);
+
+impl<L: LengthUnit, T: TimeUnit> Speed<L, T> {
+    pub fn new(value: f64) -> Self {
+        Speed(value, PhantomData)
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    pub fn convert<ToL: LengthUnit, ToT: TimeUnit>(self) -> Speed<ToL, ToT> {
+        Speed::new(self.0 * L::FACTOR / ToL::FACTOR * ToT::FACTOR / T::FACTOR)
+    }
+}
+
+impl<L: LengthUnit, T: TimeUnit> Add for Speed<L, T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Speed::new(self.0 + rhs.0)
+    }
+}
+
+impl<L: LengthUnit, T: TimeUnit> fmt::Display for Speed<L, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        with_unit(f, self.0, &format!("{}/{}", L::SYMBOL, T::SYMBOL))
+    }
+}
+
+// Both sides must be in the same unit, just like for `+`
+impl<U: LengthUnit> Mul for Length<U> {
+    type Output = Area<U>;
+
+    fn mul(self, rhs: Self) -> Area<U> {
+        Area::new(self.0 * rhs.0)
+    }
+}
+
+impl<U: LengthUnit> Div<Length<U>> for Area<U> {
+    type Output = Length<U>;
+
+    fn div(self, rhs: Length<U>) -> Length<U> {
+        Length::new(self.0 / rhs.0)
+    }
+}
+
+impl<L: LengthUnit, T: TimeUnit> Div<Time<T>> for Length<L> {
+    type Output = Speed<L, T>;
+
+    fn div(self, rhs: Time<T>) -> Speed<L, T> {
+        Speed::new(self.0 / rhs.0)
+    }
+}
+
+impl<L: LengthUnit, T: TimeUnit> Mul<Time<T>> for Speed<L, T> {
+    type Output = Length<L>;
+
+    fn mul(self, rhs: Time<T>) -> Length<L> {
+        Length::new(self.0 * rhs.0)
+    }
+}
+
+// Write `value` followed by the unit `symbol`, as in `12 in`. The precision
+// and sign options apply to the number, the width pads the whole.
+fn with_unit(f: &mut fmt::Formatter, value: f64, symbol: &str) -> fmt::Result {
+    pad(f, &format!("{} {}", number(f, value), symbol))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseQuantityError {
+    // The string doesn't start with a number
+    Number(ParseFloatError),
+    // There is no unit after the number
+    MissingUnit,
+    // The unit isn't one of this dimension, e.g. `kg` for a length
+    UnknownUnit(String),
+}
+
+impl fmt::Display for ParseQuantityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseQuantityError::Number(e) => write!(f, "invalid number: {}", e),
+            ParseQuantityError::MissingUnit => write!(f, "missing unit"),
+            ParseQuantityError::UnknownUnit(unit) => write!(f, "unknown unit `{}`", unit),
+        }
+    }
+}
+
+impl error::Error for ParseQuantityError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ParseQuantityError::Number(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseFloatError> for ParseQuantityError {
+    fn from(err: ParseFloatError) -> Self {
+        ParseQuantityError::Number(err)
+    }
+}
+
+// Split `"12 in"` or `"12in"` into the number and the unit symbol
+fn split_quantity(s: &str) -> Result<(f64, &str), ParseQuantityError> {
+    let s = s.trim();
+    // The number ends where the unit starts. An `e` may belong to either,
+    // as in `1e3 m`, so look for the last split that parses as a number.
+    let number_end = s
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(Some(s.len()))
+        .rev()
+        .find(|&i| s[..i].trim_end().parse::<f64>().is_ok());
+    let (number, unit) = match number_end {
+        Some(i) => s.split_at(i),
+        // Report why the leading word isn't a number
+        None => {
+            let word = s.split_whitespace().next().unwrap_or("");
+            return Err(word.parse::<f64>().unwrap_err().into());
+        }
+    };
+    let unit = unit.trim();
+    if unit.is_empty() {
+        return Err(ParseQuantityError::MissingUnit);
+    }
+    Ok((number.trim_end().parse()?, unit))
+}
+
+// Look `symbol` up in one of the unit tables
+fn lookup<T: Copy>(table: &[(&str, T)], symbol: &str) -> Result<T, ParseQuantityError> {
+    table
+        .iter()
+        .find(|(s, _)| *s == symbol)
+        .map(|&(_, unit)| unit)
+        .ok_or_else(|| ParseQuantityError::UnknownUnit(symbol.to_owned()))
+}
+
+// Any unit of the dimension is accepted and converted, so that `"1 ft"`
+// parses as a `Length<Inch>` of 12.
+macro_rules! parse_linear {
+    ($name:ident<$unit:ident: $bound:ident>, $table:ident) => {
+        impl<$unit: $bound> FromStr for $name<$unit> {
+            type Err = ParseQuantityError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let (value, symbol) = split_quantity(s)?;
+                let factor = lookup($table, symbol)?;
+                Ok($name::new(value * (factor / $unit::FACTOR)))
+            }
+        }
+    };
+}
+
+parse_linear!(Length<U: LengthUnit>, LENGTH_UNITS);
+parse_linear!(Mass<U: MassUnit>, MASS_UNITS);
+parse_linear!(Time<U: TimeUnit>, TIME_UNITS);
+
+impl<U: TemperatureUnit> FromStr for Temperature<U> {
+    type Err = ParseQuantityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, symbol) = split_quantity(s)?;
+        let to_kelvin = lookup(TEMPERATURE_UNITS, symbol)?;
+        Ok(Temperature::new(U::from_kelvin(to_kelvin(value))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_close(Length::<Ft>::new(1.0).convert::<Inch>().value(), 12.0);
+        assert_close(Length::<Mi>::new(1.0).convert::<Km>().value(), 1.609344);
+        assert_close(Length::<Inch>::new(1.0).convert::<Cm>().value(), 2.54);
+        assert_close(Mass::<Lb>::new(1.0).convert::<Oz>().value(), 16.0);
+        assert_close(Time::<H>::new(1.5).convert::<Min>().value(), 90.0);
+        assert_close(
+            Temperature::<Celsius>::new(100.0)
+                .convert::<Fahrenheit>()
+                .value(),
+            212.0,
+        );
+        assert_close(
+            Temperature::<Fahrenheit>::new(-40.0)
+                .convert::<Celsius>()
+                .value(),
+            -40.0,
+        );
+        assert_close(
+            Temperature::<Kelvin>::new(0.0).convert::<Celsius>().value(),
+            -273.15,
+        );
+    }
+
+    #[test]
+    fn test_derived_quantities() {
+        let side = Length::<M>::new(3.0);
+        let area = side * side;
+        assert_eq!(area, Area::new(9.0));
+        assert_close(area.convert::<Cm>().value(), 90_000.0);
+        assert_eq!(area / side, side);
+
+        let speed = Length::<Km>::new(150.0) / Time::<H>::new(2.0);
+        assert_eq!(speed, Speed::new(75.0));
+        assert_close(speed.convert::<M, S>().value(), 75.0 / 3.6);
+        assert_eq!(speed * Time::new(4.0), Length::new(300.0));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("12 in".parse(), Ok(Length::<Inch>::new(12.0)));
+        assert_eq!("12in".parse(), Ok(Length::<Inch>::new(12.0)));
+        assert_eq!(" 1.5e3 m ".parse(), Ok(Length::<M>::new(1500.0)));
+        assert_close("1 ft".parse::<Length<Inch>>().unwrap().value(), 12.0);
+        assert_close("2 lb".parse::<Mass<Kg>>().unwrap().value(), 0.90718474);
+        assert_eq!("90 min".parse(), Ok(Time::<Min>::new(90.0)));
+        assert_close(
+            "212 °F".parse::<Temperature<Celsius>>().unwrap().value(),
+            100.0,
+        );
+
+        assert_eq!(
+            "12".parse::<Length<Inch>>(),
+            Err(ParseQuantityError::MissingUnit)
+        );
+        assert_eq!(
+            "12 kg".parse::<Length<Inch>>(),
+            Err(ParseQuantityError::UnknownUnit("kg".to_owned()))
+        );
+        assert!(matches!(
+            "twelve in".parse::<Length<Inch>>(),
+            Err(ParseQuantityError::Number(_))
+        ));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Length::<Inch>::new(12.0).to_string(), "12 in");
+        assert_eq!(format!("{:.1}", Mass::<Kg>::new(2.25)), "2.2 kg");
+        assert_eq!(format!("{:>8}", Time::<S>::new(1.5)), "   1.5 s");
+        assert_eq!(format!("{:+}", Temperature::<Celsius>::new(4.0)), "+4 °C");
+        assert_eq!(Area::<M>::new(9.0).to_string(), "9 m²");
+        assert_eq!(Speed::<Km, H>::new(75.0).to_string(), "75 km/h");
+
+        // What is printed parses back
+        let length = Length::<Mi>::new(26.2);
+        assert_eq!(length.to_string().parse(), Ok(length));
+    }
+}
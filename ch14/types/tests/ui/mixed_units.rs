@@ -0,0 +1,7 @@
+use types::units::{Inch, Length, Mm};
+
+fn main() {
+    let one_foot = Length::<Inch>::new(12.0);
+    let one_meter = Length::<Mm>::new(1000.0);
+    let _one_feter = one_foot + one_meter;
+}
@@ -0,0 +1,5 @@
+// Quantities in different units can't be added together
+#[test]
+fn test_mixed_units() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
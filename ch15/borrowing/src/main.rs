@@ -1,12 +1,18 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Create a boxed i32, and a stacked i32
     let boxed_i32 = Box::new(5_i32);
     let stacked_i32 = 6_i32;
 
     // Borrow the contents of the box. Ownership is not taken,
     // so the contents can be borrowed again.
-    borrow_i32(&boxed_i32);
-    borrow_i32(&stacked_i32);
+    borrow_i32(out, &boxed_i32)?;
+    borrow_i32(out, &stacked_i32)?;
 
     {
         // Take a reference to the data contained inside the box
@@ -15,16 +21,16 @@ fn main() {
 
         // Error!
         // Can't destroy `boxed_i32` while the inner value is borrowed later in scope.
-        // eat_box_i32(boxed_i32);
-        // FIXME ^ Comment out this line
+        // eat_box_i32(out, boxed_i32)?;
+        // FIXME ^ Comment out this line (error[E0505])
 
         // Attempt to borrow `_ref_to_i32` after inner value is destroyed
-        borrow_i32(_ref_to_i32);
+        borrow_i32(out, _ref_to_i32)?;
         // `_ref_to_i32` goes out of scope and is no longer borrowed.
     }
 
     // `boxed_i32` can now give up ownership to `eat_box` and be destroyed
-    eat_box_i32(boxed_i32);
+    eat_box_i32(out, boxed_i32)?;
 
     // Create an immutable Book named `immutabook`
     let immutabook = Book {
@@ -38,17 +44,17 @@ fn main() {
     let mut mutabook = immutabook;
 
     // Immutably borrow an immutable object
-    borrow_book(&immutabook);
+    borrow_book(out, &immutabook)?;
 
     // Immutably borrow a mutable object
-    borrow_book(&mutabook);
+    borrow_book(out, &mutabook)?;
 
     // Borrow a mutable object as mutable
-    new_edition(&mut mutabook);
+    new_edition(out, &mut mutabook)?;
 
     // Error! Cannot borrow an immutable object as mutable
-    // new_edition(&mut immutabook);
-    // FIXME ^ Comment out this line
+    // new_edition(out, &mut immutabook)?;
+    // FIXME ^ Comment out this line (error[E0596])
 
     let mut point = Point1 { x: 0, y: 0, z: 0 };
 
@@ -56,21 +62,23 @@ fn main() {
     let another_borrow = &point;
 
     // Data can be accessed via the references and the original owner
-    println!(
+    writeln!(
+        out,
         "Point has coordinates: ({}, {}, {})",
         borrowed_point.x, another_borrow.y, point.z
-    );
+    )?;
 
     // Error! Can't borrow `point` as mutable because it's currently
     // borrowed as immutable.
     // let mutable_borrow = &mut point;
-    // TODO ^ Try uncommenting this line
+    // TODO ^ Try uncommenting this line (error[E0502])
 
     // The borrowed values are used again here
-    println!(
+    writeln!(
+        out,
         "Point has coordinates: ({}, {}, {})",
         borrowed_point.x, another_borrow.y, point.z
-    );
+    )?;
 
     // The immutable references are no longer used for the rest of the code so
     // it is possible to reborrow with a mutable reference.
@@ -84,25 +92,27 @@ fn main() {
     // Error! Can't borrow `point` as immutable because it's currently
     // borrowed as mutable.
     // let y = &point.y;
-    // TODO ^ Try uncommenting this line
+    // TODO ^ Try uncommenting this line (error[E0502])
 
-    // Error! Can't print because `println!` takes an immutable reference.
-    // println!("Point Z coordinate is {}", point.z);
-    // TODO ^ Try uncommenting this line
+    // Error! Can't print because `writeln!` takes an immutable reference.
+    // writeln!(out, "Point Z coordinate is {}", point.z)?;
+    // TODO ^ Try uncommenting this line (error[E0502])
 
-    // Ok! Mutable references can be passed as immutable to `println!`
-    println!(
+    // Ok! Mutable references can be passed as immutable to `writeln!`
+    writeln!(
+        out,
         "Point has coordinates: ({}, {}, {})",
         mutable_borrow.x, mutable_borrow.y, mutable_borrow.z
-    );
+    )?;
 
     // The mutable reference is no longer used for the rest of the code so it
     // is possible to reborrow
     let new_borrowed_point = &point;
-    println!(
+    writeln!(
+        out,
         "Point now has coordinates: ({}, {}, {})",
         new_borrowed_point.x, new_borrowed_point.y, new_borrowed_point.z
-    );
+    )?;
 
     let c = 'Q';
 
@@ -111,7 +121,7 @@ fn main() {
     let ref ref_c1 = c;
     let ref_c2 = &c;
 
-    println!("ref_c1 equals ref_c2: {}", *ref_c1 == *ref_c2);
+    writeln!(out, "ref_c1 equals ref_c2: {}", *ref_c1 == *ref_c2)?;
 
     let point = Point2 { x: 0, y: 0 };
 
@@ -141,11 +151,12 @@ fn main() {
         *mut_ref_to_y = 1;
     }
 
-    println!("point is ({}, {})", point.x, point.y);
-    println!(
+    writeln!(out, "point is ({}, {})", point.x, point.y)?;
+    writeln!(
+        out,
         "mutable_point is ({}, {})",
         mutable_point.x, mutable_point.y
-    );
+    )?;
 
     // A mutable tuple that includes a pointer
     let mut mutable_tuple = (Box::new(5u32), 3u32);
@@ -156,17 +167,17 @@ fn main() {
         *last = 2u32;
     }
 
-    println!("tuple is {:?}", mutable_tuple);
+    writeln!(out, "tuple is {:?}", mutable_tuple)
 }
 
 // This function takes ownership of a box and destroys it
-fn eat_box_i32(boxed_i32: Box<i32>) {
-    println!("Destroying box that contains {}", boxed_i32);
+fn eat_box_i32(out: &mut impl Write, boxed_i32: Box<i32>) -> io::Result<()> {
+    writeln!(out, "Destroying box that contains {}", boxed_i32)
 }
 
 // This function borrows an i32
-fn borrow_i32(borrowed_i32: &i32) {
-    println!("This int is: {}", borrowed_i32);
+fn borrow_i32(out: &mut impl Write, borrowed_i32: &i32) -> io::Result<()> {
+    writeln!(out, "This int is: {}", borrowed_i32)
 }
 
 #[allow(dead_code)]
@@ -179,17 +190,24 @@ struct Book {
 }
 
 // This function takes a reference to a book
-fn borrow_book(book: &Book) {
-    println!(
+fn borrow_book(out: &mut impl Write, book: &Book) -> io::Result<()> {
+    writeln!(
+        out,
         "I immutably borrowed {} - {} edition",
         book.title, book.year
-    );
+    )?;
+    Ok(())
 }
 
 // This function takes a reference to a mutable book and changes `year` to 2014
-fn new_edition(book: &mut Book) {
+fn new_edition(out: &mut impl Write, book: &mut Book) -> io::Result<()> {
     book.year = 2014;
-    println!("I mutably borrowed {} - {} edition", book.title, book.year);
+    writeln!(
+        out,
+        "I mutably borrowed {} - {} edition",
+        book.title, book.year
+    )?;
+    Ok(())
 }
 
 struct Point1 {
@@ -203,3 +221,6 @@ struct Point2 {
     x: i32,
     y: i32,
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,11 +1,16 @@
 use std::fmt::Debug; // Trait to bound with.
+use std::io::{self, Write};
 
 // Lifetimes are annotated below with lines denoting the creation
 // and destruction of each variable.
 // `i` has the longest lifetime because its scope entirely encloses
 // both `borrow1` and `borrow2`. The duration of `borrow1` compared
 // to `borrow2` is irrelevant since they are disjoint.
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     {
         let i = 3; // Lifetime for `i` starts. ────────────────┐
                    //                                                     │
@@ -13,7 +18,7 @@ fn main() {
             //                                                   │
             let borrow1 = &i; // `borrow1` lifetime starts. ──┐│
                               //                                                ││
-            println!("borrow1: {}", borrow1); //              ││
+            writeln!(out, "borrow1: {}", borrow1)?; //              ││
         } // `borrow1 ends. ──────────────────────────────────┘│
           //                                                     │
           //                                                     │
@@ -21,7 +26,7 @@ fn main() {
             //                                                   │
             let borrow2 = &i; // `borrow2` lifetime starts. ──┐│
                               //                                                ││
-            println!("borrow2: {}", borrow2); //              ││
+            writeln!(out, "borrow2: {}", borrow2)?; //              ││
         } // `borrow2` ends. ─────────────────────────────────┘│
           //                                                     │
     } // Lifetime ends. ─────────────────────────────────────┘
@@ -30,7 +35,7 @@ fn main() {
     let (four, nine) = (4, 9);
 
     // Borrows (`&`) of both variables are passed into the function.
-    print_refs(&four, &nine);
+    print_refs(out, &four, &nine)?;
     // Any input which is borrowed must outlive the borrower.
     // In other words, the lifetime of `four` and `nine` must
     // be longer than that of `print_refs`.
@@ -43,20 +48,20 @@ fn main() {
     let x = 7;
     let y = 9;
 
-    print_one(&x);
-    print_multi(&x, &y);
+    print_one(out, &x)?;
+    print_multi(out, &x, &y)?;
 
     let z = pass_x(&x, &y);
-    print_one(z);
+    print_one(out, z)?;
 
     let mut t = 3;
     add_one(&mut t);
-    print_one(&t);
+    print_one(out, &t)?;
 
     let mut owner = Owner(18);
 
     owner.add_one();
-    owner.print();
+    owner.print(out)?;
 
     let x = 18;
     let y = 15;
@@ -66,29 +71,29 @@ fn main() {
     let reference = Either::Ref(&x);
     let number = Either::Num(y);
 
-    println!("x is borrowed in {:?}", single);
-    println!("x and y are borrowed in {:?}", double);
-    println!("x is borrowed in {:?}", reference);
-    println!("y is *not* borrowed in {:?}", number);
+    writeln!(out, "x is borrowed in {:?}", single)?;
+    writeln!(out, "x and y are borrowed in {:?}", double)?;
+    writeln!(out, "x is borrowed in {:?}", reference)?;
+    writeln!(out, "y is *not* borrowed in {:?}", number)?;
 
     let b1: Borrowed2 = Default::default();
     let b2 = Borrowed2::default();
-    println!("b1 is {:?}", b1);
-    println!("b2 is {:?}", b2);
+    writeln!(out, "b1 is {:?}", b1)?;
+    writeln!(out, "b2 is {:?}", b2)?;
 
     let x = 7;
     let ref_x = Ref(&x);
 
-    print_ref(&ref_x);
-    print(ref_x);
+    print_ref(out, &ref_x)?;
+    print(out, ref_x)?;
 
     let first = 2; // Longer lifetime
 
     {
         let second = 3; // Shorter lifetime
 
-        println!("The product is {}", multiply(&first, &second));
-        println!("{} is the first", choose_first(&first, &second));
+        writeln!(out, "The product is {}", multiply(&first, &second))?;
+        writeln!(out, "{} is the first", choose_first(&first, &second))?;
     };
 
     // A reference with 'static lifetime:
@@ -104,7 +109,7 @@ fn main() {
     {
         // Make a `string` literal and print it:
         let static_string = "I'm in read-only memory";
-        println!("static_string: {}", static_string);
+        writeln!(out, "static_string: {}", static_string)?;
 
         // When `static_string` goes out of scope, the reference
         // can no longer be used, but the data remains in the binary.
@@ -117,33 +122,33 @@ fn main() {
         // Coerce `NUM` to lifetime of `lifetime_num`:
         let coerced_static = coerce_static(&lifetime_num);
 
-        println!("coerced_static: {}", coerced_static);
+        writeln!(out, "coerced_static: {}", coerced_static)?;
     }
 
-    println!("NUM: {} stays accessible!", NUM);
+    writeln!(out, "NUM: {} stays accessible!", NUM)?;
 
     // i is owned and contains no references, thus it's 'static:
     let i = 5;
-    print_it(i);
+    print_it(out, i)?;
 
     // oops, &i only has the lifetime defined by the scope of
     // main(), so it's not 'static:
-    // print_it(&i);
+    // print_it(out, &i)?;
 
     let x = 3;
 
-    elided_input(&x);
-    annotated_input(&x);
+    elided_input(out, &x)?;
+    annotated_input(out, &x)?;
 
-    println!("`elided_pass`: {}", elided_pass(&x));
-    println!("`annotated_pass`: {}", annotated_pass(&x));
+    writeln!(out, "`elided_pass`: {}", elided_pass(&x))?;
+    writeln!(out, "`annotated_pass`: {}", annotated_pass(&x))
 }
 
 // `print_refs` takes two references to `i32` which have different
 // lifetimes `'a` and `'b`. These two lifetimes must both be at
 // least as long as the function `print_refs`.
-fn print_refs<'a, 'b>(x: &'a i32, y: &'b i32) {
-    println!("x is {} and y is {}", x, y);
+fn print_refs<'a, 'b>(out: &mut impl Write, x: &'a i32, y: &'b i32) -> io::Result<()> {
+    writeln!(out, "x is {} and y is {}", x, y)
 }
 
 // A function which takes no arguments, but has a lifetime parameter `'a`.
@@ -160,8 +165,8 @@ fn failed_borrow<'a>() {
 
 // One input reference with lifetime `'a` which must live
 // at least as long as the function.
-fn print_one<'a>(x: &'a i32) {
-    println!("`print_one`: x is {}", x);
+fn print_one<'a>(out: &mut impl Write, x: &'a i32) -> io::Result<()> {
+    writeln!(out, "`print_one`: x is {}", x)
 }
 
 // Mutable references are possible with lifetimes as well.
@@ -172,8 +177,8 @@ fn add_one<'a>(x: &'a mut i32) {
 // Multiple elements with different lifetimes. In this case, it
 // would be fine for both to have the same lifetime `'a`, but
 // in more complex cases, different lifetimes may be required.
-fn print_multi<'a, 'b>(x: &'a i32, y: &'b i32) {
-    println!("`print_multi`: x is {}, y is {}", x, y);
+fn print_multi<'a, 'b>(out: &mut impl Write, x: &'a i32, y: &'b i32) -> io::Result<()> {
+    writeln!(out, "`print_multi`: x is {}, y is {}", x, y)
 }
 
 // Returning references that have been passed in is acceptable.
@@ -195,8 +200,8 @@ impl Owner {
     fn add_one<'a>(&'a mut self) {
         self.0 += 1;
     }
-    fn print<'a>(&'a self) {
-        println!("`print`: {}", self.0);
+    fn print<'a>(&'a self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "`print`: {}", self.0)
     }
 }
 
@@ -240,21 +245,21 @@ struct Ref<'a, T: 'a>(&'a T);
 // of `Ref` may not exceed `'a`.
 
 // A generic function which prints using the `Debug` trait.
-fn print<T>(t: T)
+fn print<T>(out: &mut impl Write, t: T) -> io::Result<()>
 where
     T: Debug,
 {
-    println!("`print`: t is {:?}", t);
+    writeln!(out, "`print`: t is {:?}", t)
 }
 
 // Here a reference to `T` is taken where `T` implements
 // `Debug` and all *references* in `T` outlive `'a`. In
 // addition, `'a` must outlive the function.
-fn print_ref<'a, T>(t: &'a T)
+fn print_ref<'a, T>(out: &mut impl Write, t: &'a T) -> io::Result<()>
 where
     T: Debug + 'a,
 {
-    println!("`print_ref`: t is {:?}", t);
+    writeln!(out, "`print_ref`: t is {:?}", t)
 }
 
 // Here, Rust infers a lifetime that is as short as possible.
@@ -278,18 +283,18 @@ fn coerce_static<'a>(_: &'a i32) -> &'a i32 {
     &NUM
 }
 
-fn print_it(input: impl Debug + 'static) {
-    println!("'static value passed in is: {:?}", input);
+fn print_it(out: &mut impl Write, input: impl Debug + 'static) -> io::Result<()> {
+    writeln!(out, "'static value passed in is: {:?}", input)
 }
 
 // `elided_input` and `annotated_input` essentially have identical signatures
 // because the lifetime of `elided_input` is inferred by the compiler:
-fn elided_input(x: &i32) {
-    println!("`elided_input`: {}", x);
+fn elided_input(out: &mut impl Write, x: &i32) -> io::Result<()> {
+    writeln!(out, "`elided_input`: {}", x)
 }
 
-fn annotated_input<'a>(x: &'a i32) {
-    println!("`annotated_input`: {}", x);
+fn annotated_input<'a>(out: &mut impl Write, x: &'a i32) -> io::Result<()> {
+    writeln!(out, "`annotated_input`: {}", x)
 }
 
 // Similarly, `elided_pass` and `annotated_pass` have identical signatures
@@ -301,3 +306,6 @@ fn elided_pass(x: &i32) -> &i32 {
 fn annotated_pass<'a>(x: &'a i32) -> &'a i32 {
     x
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
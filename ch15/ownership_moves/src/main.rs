@@ -1,4 +1,10 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     {
         // Allocate an integer on the heap
         let _box2 = Box::new(5i32);
@@ -27,12 +33,12 @@ fn main() {
     let y = x;
 
     // Both values can be independently used
-    println!("x is {}, and y is {}", x, y);
+    writeln!(out, "x is {}, and y is {}", x, y)?;
 
     // `a` is a pointer to a _heap_ allocated integer
     let a = Box::new(5i32);
 
-    println!("a contains: {}", a);
+    writeln!(out, "a contains: {}", a)?;
 
     // *Move* `a` into `b`
     let b = a;
@@ -42,21 +48,21 @@ fn main() {
 
     // Error! `a` can no longer access the data, because it no longer owns the
     // heap memory
-    //println!("a contains: {}", a);
-    // TODO ^ Try uncommenting this line
+    //writeln!(out, "a contains: {}", a)?;
+    // TODO ^ Try uncommenting this line (error[E0382])
 
     // This function takes ownership of the heap allocated memory from `b`
-    destroy_box(b);
+    destroy_box(out, b)?;
 
     // Since the heap memory has been freed at this point, this action would
     // result in dereferencing freed memory, but it's forbidden by the compiler
     // Error! Same reason as the previous Error
-    //println!("b contains: {}", b);
-    // TODO ^ Try uncommenting this line
+    //writeln!(out, "b contains: {}", b)?;
+    // TODO ^ Try uncommenting this line (error[E0382])
 
     let immutable_box = Box::new(5u32);
 
-    println!("immutable_box contains {}", immutable_box);
+    writeln!(out, "immutable_box contains {}", immutable_box)?;
 
     // Mutability error
     //*immutable_box = 4;
@@ -64,12 +70,12 @@ fn main() {
     // *Move* the box, changing the ownership (and mutability)
     let mut mutable_box = immutable_box;
 
-    println!("mutable_box contains {}", mutable_box);
+    writeln!(out, "mutable_box contains {}", mutable_box)?;
 
     // Modify the contents of the box
     *mutable_box = 4;
 
-    println!("mutable_box now contains {}", mutable_box);
+    writeln!(out, "mutable_box now contains {}", mutable_box)?;
 
     #[derive(Debug)]
     struct Person {
@@ -85,15 +91,15 @@ fn main() {
     // `name` is moved out of person, but `age` is referenced
     let Person { name, ref age } = person;
 
-    println!("The person's age is {}", age);
+    writeln!(out, "The person's age is {}", age)?;
 
-    println!("The person's name is {}", name);
+    writeln!(out, "The person's name is {}", name)?;
 
     // Error! borrow of partially moved value: `person` partial move occurs
-    //println!("The person struct is {:?}", person);
+    //writeln!(out, "The person struct is {:?}", person)?;
 
     // `person` cannot be used but `person.age` can be used as it is not moved
-    println!("The person's age from person struct is {}", person.age);
+    writeln!(out, "The person's age from person struct is {}", person.age)
 }
 
 // raii.rs
@@ -105,8 +111,12 @@ fn create_box() {
 }
 
 // This function takes ownership of the heap allocated memory
-fn destroy_box(c: Box<i32>) {
-    println!("Destroying a box that contains {}", c);
+fn destroy_box(out: &mut impl Write, c: Box<i32>) -> io::Result<()> {
+    writeln!(out, "Destroying a box that contains {}", c)?;
 
     // `c` is destroyed and the memory freed
+    Ok(())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
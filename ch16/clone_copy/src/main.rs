@@ -1,24 +1,30 @@
-fn main() {
+use std::io::{self, Write};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Instantiate `Unit`
     let unit = Unit;
     // Copy `Unit`, there are no resources to move
     let copied_unit = unit;
 
     // Both `Unit`s can be used independently
-    println!("original: {:?}", unit);
-    println!("copy: {:?}", copied_unit);
+    writeln!(out, "original: {:?}", unit)?;
+    writeln!(out, "copy: {:?}", copied_unit)?;
 
     // Instantiate `Pair`
     let pair = Pair(Box::new(1), Box::new(2));
-    println!("original: {:?}", pair);
+    writeln!(out, "original: {:?}", pair)?;
 
     // Move `pair` into `moved_pair`, moves resources
     let moved_pair = pair;
-    println!("moved: {:?}", moved_pair);
+    writeln!(out, "moved: {:?}", moved_pair)?;
 
     // Error! `pair` has lost its resources
-    //println!("original: {:?}", pair);
-    // TODO ^ Try uncommenting this line
+    //writeln!(out, "original: {:?}", pair)?;
+    // TODO ^ Try uncommenting this line (error[E0382])
 
     // Clone `moved_pair` into `cloned_pair` (resources are included)
     let cloned_pair = moved_pair.clone();
@@ -26,11 +32,11 @@ fn main() {
     drop(moved_pair);
 
     // Error! `moved_pair` has been dropped
-    //println!("copy: {:?}", moved_pair);
-    // TODO ^ Try uncommenting this line
+    //writeln!(out, "copy: {:?}", moved_pair)?;
+    // TODO ^ Try uncommenting this line (error[E0382])
 
     // The result from .clone() can still be used!
-    println!("clone: {:?}", cloned_pair);
+    writeln!(out, "clone: {:?}", cloned_pair)
 }
 
 // A unit struct without resources
@@ -40,3 +46,6 @@ struct Unit;
 // A tuple struct with resources that implements the `Clone` trait
 #[derive(Clone, Debug)]
 struct Pair(Box<i32>, Box<i32>);
+
+#[cfg(test)]
+testing::snapshot_test!(run);
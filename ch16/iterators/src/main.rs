@@ -1,41 +1,46 @@
+use std::io::{self, Write};
 use std::iter;
 use std::vec::IntoIter;
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // `0..3` is an `Iterator` that generates: 0, 1, and 2.
     let mut sequence = 0..3;
 
-    println!("Four consecutive `next` calls on 0..3");
-    println!("> {:?}", sequence.next());
-    println!("> {:?}", sequence.next());
-    println!("> {:?}", sequence.next());
-    println!("> {:?}", sequence.next());
+    writeln!(out, "Four consecutive `next` calls on 0..3")?;
+    writeln!(out, "> {:?}", sequence.next())?;
+    writeln!(out, "> {:?}", sequence.next())?;
+    writeln!(out, "> {:?}", sequence.next())?;
+    writeln!(out, "> {:?}", sequence.next())?;
 
     // `for` works through an `Iterator` until it returns `None`.
     // Each `Some` value is unwrapped and bound to a variable (here, `i`).
-    println!("Iterate through 0..3 using `for`");
+    writeln!(out, "Iterate through 0..3 using `for`")?;
     for i in 0..3 {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
 
     // The `take(n)` method reduces an `Iterator` to its first `n` terms.
-    println!("The first four terms of the Fibonacci sequence are: ");
+    writeln!(out, "The first four terms of the Fibonacci sequence are: ")?;
     for i in fibonacci().take(4) {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
 
     // The `skip(n)` method shortens an `Iterator` by dropping its first `n` terms.
-    println!("The next four terms of the Fibonacci sequence are: ");
+    writeln!(out, "The next four terms of the Fibonacci sequence are: ")?;
     for i in fibonacci().skip(4).take(4) {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
 
     let array = [1u32, 3, 3, 7];
 
     // The `iter` method produces an `Iterator` over an array/slice.
-    println!("Iterate the following array {:?}", &array);
+    writeln!(out, "Iterate the following array {:?}", &array)?;
     for i in array.iter() {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
 
     let v1 = vec![1, 2, 3];
@@ -46,18 +51,18 @@ fn main() {
     assert_eq!(Some(3), v3.next());
     assert_eq!(Some(4), v3.next());
     assert_eq!(Some(5), v3.next());
-    println!("all done");
+    writeln!(out, "all done")?;
     let v1 = vec![1, 2, 3];
     let v2 = vec![4, 5];
     let mut v3 = combine_vecs_explicit_return_type1(v1, v2);
     for i in v3.take(6) {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
     let v1 = vec![1, 2, 3];
     let v2 = vec![4, 5];
     let mut v3 = combine_vecs_explicit_return_type2(v1, v2);
     for i in v3.take(6) {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
 
     let plus_one = make_adder_function(1);
@@ -66,6 +71,8 @@ fn main() {
     let singles = vec![-3, -2, 2, 3];
     let doubles = double_positives(&singles);
     assert_eq!(doubles.collect::<Vec<i32>>(), vec![4, 6]);
+
+    Ok(())
 }
 
 struct Fibonacci {
@@ -132,3 +139,6 @@ fn make_adder_function(y: i32) -> impl Fn(i32) -> i32 {
 fn double_positives<'a>(numbers: &'a Vec<i32>) -> impl Iterator<Item = i32> + 'a {
     numbers.iter().filter(|x| x > &&0).map(|x| x * 2)
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -1,77 +1,108 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
 use std::ops;
 
-fn main() {
+use types::units::{Cm, Inch, Length};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Type annotation is necessary in this case.
     let mut dolly: Sheep1 = Animal1::new("Dolly");
     // TODO ^ Try removing the type annotations.
 
-    dolly.talk();
-    dolly.shear();
-    dolly.talk();
+    dolly.talk(out)?;
+    dolly.shear(out)?;
+    dolly.talk(out)?;
 
     let _one_second = Seconds(1);
 
     // Error: `Seconds` can't be printed; it doesn't implement the `Debug` trait
-    //println!("One second looks like: {:?}", _one_second);
-    // TODO ^ Try uncommenting this line
+    //writeln!(out, "One second looks like: {:?}", _one_second)?;
+    // TODO ^ Try uncommenting this line (error[E0277])
 
     // Error: `Seconds` can't be compared; it doesn't implement the `PartialEq` trait
     //let _this_is_true = (_one_second == _one_second);
-    // TODO ^ Try uncommenting this line
+    // TODO ^ Try uncommenting this line (error[E0369])
 
-    let foot = Inches(12);
+    // Lengths from the units library of the `types` example, which derive
+    // `PartialOrd` and print with their unit
+    let foot = Length::<Inch>::new(12.0);
 
-    println!("One foot equals {:?}", foot);
+    writeln!(out, "One foot equals {}", foot)?;
 
-    let meter = Centimeters(100.0);
+    let meter = Length::<Cm>::new(100.0);
 
-    let cmp = if foot.to_centimeters() < meter {
+    let cmp = if foot.convert::<Cm>() < meter {
         "smaller"
     } else {
         "bigger"
     };
 
-    println!("One foot is {} than one meter.", cmp);
+    writeln!(out, "One foot is {} than one meter.", cmp)?;
 
     let random_number = 0.234;
     let animal = random_animal(random_number);
-    println!(
+    writeln!(
+        out,
         "You've randomly chosen an animal, and it says {}",
         animal.noise()
-    );
+    )?;
+
+    // `add` and `drop` have no `out` parameter, so from here on `out` is
+    // shared with the values that print in them
+    let out = RefCell::new(out);
 
-    println!("Foo + Bar = {:?}", Foo + Bar);
-    println!("Bar + Foo = {:?}", Bar + Foo);
+    let foo_bar = Foo(&out) + Bar(&out);
+    writeln!(out.borrow_mut(), "Foo + Bar = {:?}", foo_bar)?;
+    let bar_foo = Bar(&out) + Foo(&out);
+    writeln!(out.borrow_mut(), "Bar + Foo = {:?}", bar_foo)?;
 
     {
-        let _a = Droppable { name: "a" };
+        let _a = Droppable {
+            name: "a",
+            out: &out,
+        };
 
         // block A
         {
-            let _b = Droppable { name: "b" };
+            let _b = Droppable {
+                name: "b",
+                out: &out,
+            };
 
             // block B
             {
-                let _c = Droppable { name: "c" };
-                let _d = Droppable { name: "d" };
-
-                println!("Exiting block B");
+                let _c = Droppable {
+                    name: "c",
+                    out: &out,
+                };
+                let _d = Droppable {
+                    name: "d",
+                    out: &out,
+                };
+
+                writeln!(out.borrow_mut(), "Exiting block B")?;
             }
-            println!("Just exited block B");
+            writeln!(out.borrow_mut(), "Just exited block B")?;
 
-            println!("Exiting block A");
+            writeln!(out.borrow_mut(), "Exiting block A")?;
         }
-        println!("Just exited block A");
+        writeln!(out.borrow_mut(), "Just exited block A")?;
 
         // Variable can be manually dropped using the `drop` function
         drop(_a);
         // TODO ^ Try commenting this line
 
-        println!("end of the main function");
+        writeln!(out.borrow_mut(), "end of the main function")?;
 
         // `_a` *won't* be `drop`ed again here, because it already has been
         // (manually) `drop`ed
     }
+
+    Ok(())
 }
 
 struct Sheep1 {
@@ -88,8 +119,8 @@ trait Animal1 {
     fn noise(&self) -> &'static str;
 
     // Traits can provide default method definitions.
-    fn talk(&self) {
-        println!("{} says {}", self.name(), self.noise());
+    fn talk(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{} says {}", self.name(), self.noise())
     }
 }
 
@@ -98,15 +129,16 @@ impl Sheep1 {
         self.naked
     }
 
-    fn shear(&mut self) {
+    fn shear(&mut self, out: &mut impl Write) -> io::Result<()> {
         if self.is_naked() {
             // Implementor methods can use the implementor's trait methods.
-            println!("{} is already naked...", self.name());
+            writeln!(out, "{} is already naked...", self.name())?;
         } else {
-            println!("{} gets a haircut!", self.name);
+            writeln!(out, "{} gets a haircut!", self.name)?;
 
             self.naked = true;
         }
+        Ok(())
     }
 }
 
@@ -133,25 +165,9 @@ impl Animal1 for Sheep1 {
     }
 
     // Default trait methods can be overridden.
-    fn talk(&self) {
+    fn talk(&self, out: &mut impl Write) -> io::Result<()> {
         // For example, we can add some quiet contemplation.
-        println!("{} pauses briefly... {}", self.name, self.noise());
-    }
-}
-
-// `Centimeters`, a tuple struct that can be compared
-#[derive(PartialEq, PartialOrd)]
-struct Centimeters(f64);
-
-// `Inches`, a tuple struct that can be printed
-#[derive(Debug)]
-struct Inches(i32);
-
-impl Inches {
-    fn to_centimeters(&self) -> Centimeters {
-        let &Inches(inches) = self;
-
-        Centimeters(inches as f64 * 2.54)
+        writeln!(out, "{} pauses briefly... {}", self.name, self.noise())
     }
 }
 
@@ -190,8 +206,9 @@ fn random_animal(random_number: f64) -> Box<dyn Animal2> {
     }
 }
 
-struct Foo;
-struct Bar;
+// Both print to the `out` they hold when added
+struct Foo<'a, W: Write>(&'a RefCell<W>);
+struct Bar<'a, W: Write>(&'a RefCell<W>);
 
 #[derive(Debug)]
 struct FooBar;
@@ -202,11 +219,12 @@ struct BarFoo;
 // The `std::ops::Add` trait is used to specify the functionality of `+`.
 // Here, we make `Add<Bar>` - the trait for addition with a RHS of type `Bar`.
 // The following block implements the operation: Foo + Bar = FooBar
-impl ops::Add<Bar> for Foo {
+impl<'a, W: Write> ops::Add<Bar<'a, W>> for Foo<'a, W> {
     type Output = FooBar;
 
-    fn add(self, _rhs: Bar) -> FooBar {
-        println!("> Foo.add(Bar) was called");
+    fn add(self, _rhs: Bar<'a, W>) -> FooBar {
+        // `add` can't return the error, so it's ignored
+        let _ = writeln!(self.0.borrow_mut(), "> Foo.add(Bar) was called");
 
         FooBar
     }
@@ -215,23 +233,28 @@ impl ops::Add<Bar> for Foo {
 // By reversing the types, we end up implementing non-commutative addition.
 // Here, we make `Add<Foo>` - the trait for addition with a RHS of type `Foo`.
 // This block implements the operation: Bar + Foo = BarFoo
-impl ops::Add<Foo> for Bar {
+impl<'a, W: Write> ops::Add<Foo<'a, W>> for Bar<'a, W> {
     type Output = BarFoo;
 
-    fn add(self, _rhs: Foo) -> BarFoo {
-        println!("> Bar.add(Foo) was called");
+    fn add(self, _rhs: Foo<'a, W>) -> BarFoo {
+        let _ = writeln!(self.0.borrow_mut(), "> Bar.add(Foo) was called");
 
         BarFoo
     }
 }
 
-struct Droppable {
+struct Droppable<'a, W: Write> {
     name: &'static str,
+    out: &'a RefCell<W>,
 }
 
 // This trivial implementation of `drop` adds a print to console.
-impl Drop for Droppable {
+impl<W: Write> Drop for Droppable<'_, W> {
     fn drop(&mut self) {
-        println!("> Dropping {}", self.name);
+        // Like `add`, `drop` can't return the error
+        let _ = writeln!(self.out.borrow_mut(), "> Dropping {}", self.name);
     }
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
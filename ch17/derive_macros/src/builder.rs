@@ -0,0 +1,347 @@
+// `#[derive(Builder)]`, which writes a fluent builder for a struct with
+// named fields:
+//
+//     #[derive(Builder)]
+//     struct PhoneNumber {
+//         area_code: Option<u8>,
+//         number: u32,
+//     }
+//
+//     let phone = PhoneNumber::builder().area_code(61).number(439222222).build()?;
+//
+// Every field gets a setter of the same name, which takes the value by
+// itself, or anything that converts into it with `#[builder(into)]`. A field
+// is required, unless
+//
+// * it is an `Option`, whose setter takes what is inside, and which is
+//   `None` when not set, or
+// * it has a default, `#[builder(default)]` for `Default::default()` or
+//   `#[builder(default = expr)]`.
+//
+// `build()` checks the required fields, and returns a `<Name>BuilderError`
+// listing all the ones that are missing.
+//
+// A field marked `#[builder(nested)]`, whose type also derives `Builder`, gets
+// one more setter, `<field>_with`, which is handed a builder of its own to
+// fill in. The fields missing from it are reported by the outer `build()`,
+// as `job.phone_number.number`.
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    Data, DeriveInput, Error, Expr, Fields, GenericArgument, Ident, PathArguments, Result, Type,
+};
+
+// The `T` of an `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// The builder of a type, `Job` -> `JobBuilder` and `a::Job<T>` ->
+// `a::JobBuilder<T>`, and its error, `JobBuilderError` and
+// `a::JobBuilderError`: the error has no generics
+fn builder_of(ty: &Type) -> Result<(Type, Type)> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let mut builder = path.clone();
+            let last = builder.path.segments.last_mut().unwrap();
+            let name = last.ident.clone();
+            last.ident = format_ident!("{}Builder", name);
+            let mut error = builder.clone();
+            let last = error.path.segments.last_mut().unwrap();
+            last.ident = format_ident!("{}BuilderError", name);
+            last.arguments = PathArguments::None;
+            Ok((Type::Path(builder), Type::Path(error)))
+        }
+        _ => Err(Error::new(
+            ty.span(),
+            "`#[builder(nested)]` needs a struct that derives `Builder`, like `Job`",
+        )),
+    }
+}
+
+enum DefaultValue {
+    Trait,
+    Expr(Box<Expr>),
+}
+
+// A field, and what its `#[builder(...)]` attributes say
+struct Field<'a> {
+    ident: &'a Ident,
+    // The type the setter takes, without any `Option`
+    ty: &'a Type,
+    optional: bool,
+    default: Option<DefaultValue>,
+    into: bool,
+    // The builder and its error, for `#[builder(nested)]`
+    nested: Option<(Type, Type)>,
+}
+
+fn parse_field(field: &syn::Field) -> Result<Field<'_>> {
+    let ident = field.ident.as_ref().unwrap();
+    if ident == "build" {
+        return Err(Error::new(
+            ident.span(),
+            "a field named `build` would clash with `build()`",
+        ));
+    }
+    let inner = option_inner(&field.ty);
+    let mut result = Field {
+        ident,
+        ty: inner.unwrap_or(&field.ty),
+        optional: inner.is_some(),
+        default: None,
+        into: false,
+        nested: None,
+    };
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("builder"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let repeated = || meta.error("repeated `#[builder(...)]` option");
+            if meta.path.is_ident("default") {
+                if result.optional {
+                    return Err(meta.error("an `Option` field is already `None` by default"));
+                }
+                if result.default.is_some() {
+                    return Err(repeated());
+                }
+                result.default = Some(if meta.input.peek(syn::Token![=]) {
+                    DefaultValue::Expr(meta.value()?.parse()?)
+                } else {
+                    DefaultValue::Trait
+                });
+            } else if meta.path.is_ident("into") {
+                if result.into {
+                    return Err(repeated());
+                }
+                result.into = true;
+            } else if meta.path.is_ident("nested") {
+                if result.nested.is_some() {
+                    return Err(repeated());
+                }
+                result.nested = Some(builder_of(result.ty)?);
+            } else {
+                return Err(meta.error("expected `default`, `default = ...`, `into` or `nested`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(result)
+}
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            Fields::Unnamed(fields) => return Err(Error::new(
+                fields.span(),
+                "`#[derive(Builder)]` needs named fields, a tuple struct is built with `Name(...)`",
+            )),
+            Fields::Unit => {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "`#[derive(Builder)]` has nothing to build for a unit struct",
+                ))
+            }
+        },
+        Data::Enum(data) => {
+            return Err(Error::new(
+                data.enum_token.span,
+                "`#[derive(Builder)]` only supports structs",
+            ))
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                "`#[derive(Builder)]` only supports structs",
+            ))
+        }
+    };
+    if let Some(attr) = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("builder"))
+    {
+        return Err(Error::new(
+            attr.span(),
+            "`#[builder(...)]` goes on the fields",
+        ));
+    }
+    let fields = fields.iter().map(parse_field).collect::<Result<Vec<_>>>()?;
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let builder = format_ident!("{}Builder", name);
+    let error = format_ident!("{}BuilderError", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut storage = Vec::new();
+    let mut setters = Vec::new();
+    let mut checks = Vec::new();
+    let mut values = Vec::new();
+    for field in &fields {
+        let ident = field.ident;
+        let ty = field.ty;
+        let label = ident.to_string();
+
+        // A nested builder is built by its setter, and keeps what it was
+        // missing for `build()`
+        storage.push(match &field.nested {
+            Some(_) => quote! {
+                #ident: ::std::option::Option<
+                    ::std::result::Result<#ty, ::std::vec::Vec<::std::string::String>>
+                >
+            },
+            None => quote!(#ident: ::std::option::Option<#ty>),
+        });
+
+        let (param, value) = if field.into {
+            (quote!(impl ::std::convert::Into<#ty>), quote!(value.into()))
+        } else {
+            (quote!(#ty), quote!(value))
+        };
+        let stored = match &field.nested {
+            Some(_) => quote!(::std::result::Result::Ok(#value)),
+            None => value,
+        };
+        setters.push(quote! {
+            pub fn #ident(mut self, value: #param) -> Self {
+                self.#ident = ::std::option::Option::Some(#stored);
+                self
+            }
+        });
+        if let Some((nested, nested_error)) = &field.nested {
+            let with = format_ident!("{}_with", ident);
+            setters.push(quote! {
+                pub fn #with(mut self, f: impl ::std::ops::FnOnce(#nested) -> #nested) -> Self {
+                    let built: ::std::result::Result<#ty, #nested_error> =
+                        f(::std::default::Default::default()).build();
+                    self.#ident = ::std::option::Option::Some(built.map_err(|e| e.missing));
+                    self
+                }
+            });
+        }
+
+        let required = !field.optional && field.default.is_none();
+        let missing = if required {
+            quote!(__missing.push(::std::string::String::from(#label));)
+        } else {
+            quote!()
+        };
+        checks.push(match &field.nested {
+            Some(_) => quote! {
+                let #ident = match self.#ident {
+                    ::std::option::Option::Some(::std::result::Result::Ok(value)) => {
+                        ::std::option::Option::Some(value)
+                    }
+                    ::std::option::Option::Some(::std::result::Result::Err(inner)) => {
+                        __missing.extend(
+                            inner.into_iter().map(|inner| ::std::format!("{}.{}", #label, inner)),
+                        );
+                        ::std::option::Option::None
+                    }
+                    ::std::option::Option::None => {
+                        #missing
+                        ::std::option::Option::None
+                    }
+                };
+            },
+            None if required => quote! {
+                if self.#ident.is_none() {
+                    #missing
+                }
+                let #ident = self.#ident;
+            },
+            None => quote!(let #ident = self.#ident;),
+        });
+
+        values.push(match &field.default {
+            _ if field.optional => quote!(#ident),
+            Some(DefaultValue::Trait) => {
+                quote!(#ident.unwrap_or_else(::std::default::Default::default))
+            }
+            Some(DefaultValue::Expr(expr)) => quote!(#ident.unwrap_or_else(|| #expr)),
+            None => quote!(#ident.unwrap()),
+        });
+    }
+    let idents: Vec<_> = fields.iter().map(|field| field.ident).collect();
+
+    let builder_doc = format!("A builder for `{}`, from `{}::builder()`", name, name);
+    let error_doc = format!("The fields missing from a `{}`", builder);
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #vis struct #builder #impl_generics #where_clause {
+            #(#storage,)*
+        }
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct #error {
+            pub missing: ::std::vec::Vec<::std::string::String>,
+        }
+
+        impl ::std::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                ::std::write!(f, "missing fields for `{}`: ", ::std::stringify!(#name))?;
+                for (i, field) in self.missing.iter().enumerate() {
+                    if i != 0 {
+                        ::std::write!(f, ", ")?;
+                    }
+                    ::std::write!(f, "`{}`", field)?;
+                }
+                ::std::result::Result::Ok(())
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        impl #impl_generics ::std::default::Default for #builder #ty_generics #where_clause {
+            fn default() -> Self {
+                #builder {
+                    #(#idents: ::std::option::Option::None,)*
+                }
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub fn builder() -> #builder #ty_generics {
+                ::std::default::Default::default()
+            }
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #(#setters)*
+
+            pub fn build(self) -> ::std::result::Result<#name #ty_generics, #error> {
+                #[allow(unused_mut)]
+                let mut __missing: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
+                #(#checks)*
+                if !__missing.is_empty() {
+                    return ::std::result::Result::Err(#error { missing: __missing });
+                }
+                ::std::result::Result::Ok(#name {
+                    #(#idents: #values,)*
+                })
+            }
+        }
+    })
+}
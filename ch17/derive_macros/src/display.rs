@@ -0,0 +1,595 @@
+// `#[derive(Display)]`, which can write the `fmt::Display` impls of the
+// examples from a format string:
+//
+//     #[derive(Display)]
+//     #[display("x: {x}, y: {y}", alternate = "x: {x}\ny: {y}")]
+//     struct Point2D {
+//         x: f64,
+//         y: f64,
+//     }
+//
+// The format string names fields, `{x}`, or tuple fields by index, `{0}`,
+// and may give them a format spec, `{x:>8.2}`. Other values can be added as
+// named arguments, `name = expr`, which hide a field of the same name. The
+// expression runs inside `fn fmt(&self, f: &mut fmt::Formatter)`, and the
+// names `self` and `f` are part of the derive: it can read the fields through
+// `self` and the formatting options through `f`, for output that a format
+// spec can't describe, such as a hemisphere that `{:+}` turns into a sign, or
+// a precision that only one field takes.
+//
+// The generated impl follows the formatting options the way the hand-written
+// ones of the examples do:
+//
+// * precision and `+` go to the numbers, that is fields of a primitive number
+//   type or marked `#[display(number)]`, unless their spec says otherwise,
+// * width, fill and alignment pad the whole value, line by line, and
+// * `{:#}` uses the `alternate` format string, if there is one.
+//
+// Without a format string, a struct or variant with one field, or a field
+// marked `#[display(delegate)]`, hands the formatting over to that field, and
+// a unit variant writes its name. On an enum, the attributes go on the
+// variants. `T: Display` is required of every type parameter, unless
+// `#[display(bound = "...")]` says what to require instead.
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Expr, Fields, Ident, LitStr, Result, Token, Type,
+    WherePredicate,
+};
+
+// What the `#[display(...)]` attributes of a struct, enum or variant say
+#[derive(Default)]
+struct Attrs {
+    format: Option<LitStr>,
+    alternate: Option<LitStr>,
+    args: Vec<(Ident, Expr)>,
+    bound: Option<Punctuated<WherePredicate, Token![,]>>,
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> Result<Attrs> {
+    let mut result = Attrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("display")) {
+        attr.parse_args_with(|input: ParseStream| {
+            while !input.is_empty() {
+                if input.peek(LitStr) {
+                    let format: LitStr = input.parse()?;
+                    if result.format.is_some() {
+                        return Err(Error::new(format.span(), "more than one format string"));
+                    }
+                    result.format = Some(format);
+                } else {
+                    let name: Ident = input.parse()?;
+                    input.parse::<Token![=]>()?;
+                    if name == "alternate" {
+                        result.alternate = Some(input.parse()?);
+                    } else if name == "bound" {
+                        let bound: LitStr = input.parse()?;
+                        result.bound = Some(bound.parse_with(Punctuated::parse_terminated)?);
+                    } else if result.args.iter().any(|(arg, _)| *arg == name) {
+                        return Err(Error::new(name.span(), "duplicate argument"));
+                    } else {
+                        result.args.push((name, input.parse()?));
+                    }
+                }
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+    if result.format.is_none() {
+        if let Some(alternate) = &result.alternate {
+            return Err(Error::new(
+                alternate.span(),
+                "`alternate` needs a format string first",
+            ));
+        }
+        if let Some((name, _)) = result.args.first() {
+            return Err(Error::new(
+                name.span(),
+                "arguments need a format string first",
+            ));
+        }
+    }
+    Ok(result)
+}
+
+// A field, and what is known about it
+struct Field {
+    // `x` or `0`
+    member: syn::Member,
+    // What the field is bound to in the generated code
+    binding: Ident,
+    number: bool,
+    delegate: bool,
+}
+
+fn is_number(ty: &Type) -> bool {
+    const NUMBERS: &[&str] = &[
+        "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+        "f32", "f64",
+    ];
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .get_ident()
+            .is_some_and(|ident| NUMBERS.iter().any(|number| ident == number)),
+        _ => false,
+    }
+}
+
+fn parse_fields(fields: &Fields) -> Result<Vec<Field>> {
+    let mut result = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let (member, binding) = match &field.ident {
+            Some(ident) => (
+                syn::Member::Named(ident.clone()),
+                format_ident!("__self_{}", ident),
+            ),
+            None => (
+                syn::Member::Unnamed(i.into()),
+                format_ident!("__self_{}", i),
+            ),
+        };
+        let mut info = Field {
+            member,
+            binding,
+            number: is_number(&field.ty),
+            delegate: false,
+        };
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("display"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("number") {
+                    info.number = true;
+                    Ok(())
+                } else if meta.path.is_ident("delegate") {
+                    info.delegate = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `number` or `delegate`"))
+                }
+            })?;
+        }
+        result.push(info);
+    }
+    Ok(result)
+}
+
+// A `{...}` of a format string
+struct Placeholder {
+    binding: Ident,
+    number: bool,
+    // The bindings of any `name$` in the spec
+    counts: Vec<Ident>,
+    // The parts of the spec, `[[fill]align][sign]['#']['0'][width]['.' precision][type]`,
+    // with any `name$` already turned into a binding
+    align: String,
+    sign: Option<char>,
+    flags: String,
+    width: String,
+    precision: Option<String>,
+    ty: String,
+}
+
+enum Piece {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+// What a format string can refer to
+struct Scope<'a> {
+    fields: &'a [Field],
+    args: &'a [(Ident, Expr)],
+    // The fields and arguments that are used, by binding
+    used: Vec<Ident>,
+}
+
+impl Scope<'_> {
+    // The binding for a name in a format string, and whether it is a number
+    fn resolve(&mut self, name: &str, lit: &LitStr) -> Result<(Ident, bool)> {
+        let (binding, number) =
+            if let Some((arg, _)) = self.args.iter().find(|(arg, _)| arg == name) {
+                (format_ident!("__arg_{}", arg), false)
+            } else if let Some(field) = self.fields.iter().find(|field| match &field.member {
+                syn::Member::Named(ident) => ident == name,
+                syn::Member::Unnamed(index) => index.index.to_string() == name,
+            }) {
+                (field.binding.clone(), field.number)
+            } else if name.is_empty() {
+                return Err(Error::new(
+                    lit.span(),
+                    "`{}` needs a field name or index, like `{x}` or `{0}`",
+                ));
+            } else {
+                return Err(Error::new(
+                    lit.span(),
+                    format!("there is no field or argument `{}`", name),
+                ));
+            };
+        if !self.used.contains(&binding) {
+            self.used.push(binding.clone());
+        }
+        Ok((binding, number))
+    }
+}
+
+fn is_align(c: char) -> bool {
+    matches!(c, '<' | '^' | '>')
+}
+
+// Take a width or precision, a number or a `name$`, from the front of `s`
+fn count(s: &mut &str, scope: &mut Scope, counts: &mut Vec<Ident>, lit: &LitStr) -> Result<String> {
+    let end = s
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(s.len());
+    let (run, rest) = s.split_at(end);
+    if let Some(rest) = rest.strip_prefix('$') {
+        let (binding, _) = scope.resolve(run, lit)?;
+        counts.push(binding.clone());
+        *s = rest;
+        Ok(format!("{}$", binding))
+    } else if run.chars().all(|c| c.is_ascii_digit()) {
+        *s = rest;
+        Ok(run.to_owned())
+    } else {
+        // Only digits at the front are a count, the rest is the type
+        let digits = run.find(|c: char| !c.is_ascii_digit()).unwrap_or(run.len());
+        let (count, _) = run.split_at(digits);
+        *s = &s[digits..];
+        Ok(count.to_owned())
+    }
+}
+
+fn parse_placeholder(inner: &str, scope: &mut Scope, lit: &LitStr) -> Result<Placeholder> {
+    let (name, mut spec) = inner.split_once(':').unwrap_or((inner, ""));
+    let (binding, number) = scope.resolve(name, lit)?;
+    let mut counts = Vec::new();
+
+    let mut align = String::new();
+    let mut chars = spec.chars();
+    match (chars.next(), chars.next()) {
+        (Some(fill), Some(c)) if is_align(c) => {
+            align = format!("{}{}", fill, c);
+            spec = &spec[fill.len_utf8() + 1..];
+        }
+        (Some(c), _) if is_align(c) => {
+            align.push(c);
+            spec = &spec[1..];
+        }
+        _ => {}
+    }
+    let mut sign = None;
+    if let Some(c) = spec.chars().next().filter(|&c| c == '+' || c == '-') {
+        sign = Some(c);
+        spec = &spec[1..];
+    }
+    let mut flags = String::new();
+    if let Some(rest) = spec.strip_prefix('#') {
+        flags.push('#');
+        spec = rest;
+    }
+    if spec.starts_with('0') && !spec.starts_with("0$") {
+        flags.push('0');
+        spec = &spec[1..];
+    }
+    let width = count(&mut spec, scope, &mut counts, lit)?;
+    let mut precision = None;
+    if let Some(mut rest) = spec.strip_prefix('.') {
+        if rest.starts_with('*') {
+            return Err(Error::new(
+                lit.span(),
+                "`.*` is not supported, use a `name$` for the precision",
+            ));
+        }
+        precision = Some(count(&mut rest, scope, &mut counts, lit)?);
+        spec = rest;
+    }
+
+    Ok(Placeholder {
+        binding,
+        number,
+        counts,
+        align,
+        sign,
+        flags,
+        width,
+        precision,
+        ty: spec.to_owned(),
+    })
+}
+
+fn parse_format(lit: &LitStr, scope: &mut Scope) -> Result<Vec<Piece>> {
+    let value = lit.value();
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut rest = value.as_str();
+    while let Some(i) = rest.find(['{', '}']) {
+        text.push_str(&rest[..i]);
+        let c = rest.as_bytes()[i];
+        rest = &rest[i + 1..];
+        if rest.as_bytes().first() == Some(&c) {
+            // `{{` or `}}`
+            text.push(c as char);
+            rest = &rest[1..];
+        } else if c == b'}' {
+            return Err(Error::new(lit.span(), "unmatched `}` in format string"));
+        } else {
+            let end = rest
+                .find('}')
+                .ok_or_else(|| Error::new(lit.span(), "unmatched `{` in format string"))?;
+            pieces.push(Piece::Text(std::mem::take(&mut text)));
+            pieces.push(Piece::Placeholder(parse_placeholder(
+                &rest[..end],
+                scope,
+                lit,
+            )?));
+            rest = &rest[end + 1..];
+        }
+    }
+    text.push_str(rest);
+    pieces.push(Piece::Text(text));
+    Ok(pieces)
+}
+
+// Write the format string back out, with the sign and the precision of the
+// formatter added to the numbers when `sign` and `precision` are set.
+// Returns whether the `__precision` argument is used.
+fn render(pieces: &[Piece], sign: bool, precision: bool) -> (String, bool) {
+    let mut s = String::new();
+    let mut uses_precision = false;
+    for piece in pieces {
+        match piece {
+            Piece::Text(text) => s.push_str(&text.replace('{', "{{").replace('}', "}}")),
+            Piece::Placeholder(p) => {
+                let mut spec = p.align.clone();
+                match p.sign {
+                    Some(c) => spec.push(c),
+                    None if p.number && sign => spec.push('+'),
+                    None => {}
+                }
+                spec.push_str(&p.flags);
+                spec.push_str(&p.width);
+                match &p.precision {
+                    Some(precision) => spec.push_str(&format!(".{}", precision)),
+                    None if p.number && precision => {
+                        spec.push_str(".__precision$");
+                        uses_precision = true;
+                    }
+                    None => {}
+                }
+                spec.push_str(&p.ty);
+                if spec.is_empty() {
+                    s.push_str(&format!("{{{}}}", p.binding));
+                } else {
+                    s.push_str(&format!("{{{}:{}}}", p.binding, spec));
+                }
+            }
+        }
+    }
+    (s, uses_precision)
+}
+
+// An expression that formats `lit` into a `String`, for every combination of
+// `{:+}` and precision that changes it
+fn format_string(lit: &LitStr, scope: &mut Scope) -> Result<TokenStream> {
+    let pieces = parse_format(lit, scope)?;
+    let mut used = Vec::new();
+    for piece in &pieces {
+        if let Piece::Placeholder(p) = piece {
+            used.push(p.binding.clone());
+            used.extend(p.counts.iter().cloned());
+        }
+    }
+    used.sort();
+    used.dedup();
+    let args = quote!(#(#used = #used),*);
+
+    let format = |sign: bool, precision: bool| {
+        let (s, uses_precision) = render(&pieces, sign, precision);
+        let s = LitStr::new(&s, lit.span());
+        if uses_precision {
+            quote!(::std::format!(#s, #args, __precision = __precision))
+        } else {
+            quote!(::std::format!(#s, #args))
+        }
+    };
+    if pieces
+        .iter()
+        .any(|piece| matches!(piece, Piece::Placeholder(p) if p.number))
+    {
+        let (plain, signed) = (format(false, false), format(true, false));
+        let (precise, both) = (format(false, true), format(true, true));
+        Ok(quote! {
+            match (f.sign_plus(), f.precision()) {
+                (false, ::std::option::Option::None) => #plain,
+                (true, ::std::option::Option::None) => #signed,
+                (false, ::std::option::Option::Some(__precision)) => #precise,
+                (true, ::std::option::Option::Some(__precision)) => #both,
+            }
+        })
+    } else {
+        Ok(format(false, false))
+    }
+}
+
+// The body for one struct or variant: a pattern that binds the fields, and
+// the code that formats them
+fn expand_fields(
+    path: TokenStream,
+    name: &Ident,
+    attrs: &Attrs,
+    fields: &Fields,
+) -> Result<(TokenStream, TokenStream)> {
+    let fields = parse_fields(fields)?;
+    let mut delegates = fields.iter().filter(|field| field.delegate);
+    let delegate = match (delegates.next(), delegates.next()) {
+        (_, Some(second)) => {
+            return Err(Error::new(
+                second.binding.span(),
+                "only one field can be `#[display(delegate)]`",
+            ))
+        }
+        (Some(field), None) => Some(field),
+        (None, None) if attrs.format.is_none() && fields.len() == 1 => Some(&fields[0]),
+        (None, None) => None,
+    };
+
+    let (used, body) = match (&attrs.format, delegate) {
+        (Some(format), Some(field)) if field.delegate => {
+            return Err(Error::new(
+                format.span(),
+                "a format string and a `#[display(delegate)]` field don't go together",
+            ))
+        }
+        (Some(format), _) => {
+            let mut scope = Scope {
+                fields: &fields,
+                args: &attrs.args,
+                used: Vec::new(),
+            };
+            let normal = format_string(format, &mut scope)?;
+            let body = match &attrs.alternate {
+                Some(alternate) => {
+                    let alternate = format_string(alternate, &mut scope)?;
+                    quote!(if f.alternate() { #alternate } else { #normal })
+                }
+                None => normal,
+            };
+            let lets = attrs.args.iter().map(|(arg, expr)| {
+                let binding = format_ident!("__arg_{}", arg);
+                quote!(let #binding = #expr;)
+            });
+            let body = quote! {{
+                #(#lets)*
+                let __s: ::std::string::String = #body;
+                __pad(f, &__s)
+            }};
+            (scope.used, body)
+        }
+        (None, Some(field)) => {
+            let binding = &field.binding;
+            let body = quote!(::std::fmt::Display::fmt(#binding, f));
+            (vec![binding.clone()], body)
+        }
+        (None, None) if fields.is_empty() => {
+            let name = name.to_string();
+            (Vec::new(), quote!(__pad(f, #name)))
+        }
+        (None, None) => {
+            return Err(Error::new(
+                name.span(),
+                "`#[derive(Display)]` needs a `#[display(\"...\")]` format string here",
+            ))
+        }
+    };
+
+    let bindings = fields
+        .iter()
+        .filter(|field| used.contains(&field.binding))
+        .map(|field| {
+            let (member, binding) = (&field.member, &field.binding);
+            quote!(#member: ref #binding)
+        });
+    Ok((quote!(#path { #(#bindings,)* .. }), body))
+}
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let attrs = parse_attrs(&input.attrs)?;
+    let name = &input.ident;
+
+    let arms = match &input.data {
+        Data::Struct(data) => vec![expand_fields(quote!(Self), name, &attrs, &data.fields)?],
+        Data::Enum(data) => {
+            if let Some(format) = &attrs.format {
+                return Err(Error::new(
+                    format.span(),
+                    "on an enum, `#[display(\"...\")]` goes on each variant",
+                ));
+            }
+            data.variants
+                .iter()
+                .map(|variant| {
+                    let variant_attrs = parse_attrs(&variant.attrs)?;
+                    if let Some(bound) = &variant_attrs.bound {
+                        return Err(Error::new(bound.span(), "`bound` goes on the enum"));
+                    }
+                    let ident = &variant.ident;
+                    expand_fields(quote!(Self::#ident), ident, &variant_attrs, &variant.fields)
+                })
+                .collect::<Result<_>>()?
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                "`#[derive(Display)]` doesn't support unions",
+            ))
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    match attrs.bound {
+        Some(bound) => where_clause.predicates.extend(bound),
+        None => {
+            for param in input.generics.type_params() {
+                let ident = &param.ident;
+                where_clause
+                    .predicates
+                    .push(syn::parse_quote!(#ident: ::std::fmt::Display));
+            }
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let arms = arms
+        .into_iter()
+        .map(|(pattern, body)| quote!(#pattern => #body));
+    Ok(quote! {
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                // Write `s` padded to the width in `f`, using its fill and
+                // alignment, a line at a time
+                #[allow(dead_code)]
+                fn __pad(f: &mut ::std::fmt::Formatter, s: &str) -> ::std::fmt::Result {
+                    let width = f.width().unwrap_or(0);
+                    for (i, line) in s.split('\n').enumerate() {
+                        if i != 0 {
+                            ::std::writeln!(f)?;
+                        }
+                        let padding = width.saturating_sub(line.chars().count());
+                        let (before, after) = match f.align() {
+                            ::std::option::Option::Some(::std::fmt::Alignment::Right) => {
+                                (padding, 0)
+                            }
+                            ::std::option::Option::Some(::std::fmt::Alignment::Center) => {
+                                (padding / 2, padding - padding / 2)
+                            }
+                            _ => (0, padding),
+                        };
+                        let fill = f.fill();
+                        for _ in 0..before {
+                            ::std::write!(f, "{}", fill)?;
+                        }
+                        ::std::write!(f, "{}", line)?;
+                        for _ in 0..after {
+                            ::std::write!(f, "{}", fill)?;
+                        }
+                    }
+                    ::std::result::Result::Ok(())
+                }
+
+                match *self {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,143 @@
+// The structs of chapters 3 and 18, built with `#[derive(Builder)]`
+use derive_macros::Builder;
+
+#[derive(Debug, PartialEq, Builder)]
+struct PhoneNumber {
+    area_code: Option<u8>,
+    number: u32,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+struct Job {
+    #[builder(nested)]
+    phone_number: Option<PhoneNumber>,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+struct Person {
+    #[builder(into)]
+    name: String,
+    #[builder(default = 18)]
+    age: u8,
+    #[builder(nested)]
+    job: Option<Job>,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+struct Point {
+    #[builder(default)]
+    x: f32,
+    #[builder(default)]
+    y: f32,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+struct Rectangle {
+    #[builder(nested)]
+    top_left: Point,
+    #[builder(nested)]
+    bottom_right: Point,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+// A generic struct nested in a generic struct
+#[derive(Debug, PartialEq, Builder)]
+struct Labeled<T> {
+    label: &'static str,
+    #[builder(nested)]
+    pair: Pair<T>,
+}
+
+#[test]
+fn test_build() {
+    let person = Person::builder()
+        .name("Ferris")
+        .job_with(|job| job.phone_number_with(|phone| phone.area_code(61).number(439222222)))
+        .build()
+        .unwrap();
+    assert_eq!(
+        person,
+        Person {
+            name: "Ferris".to_owned(),
+            age: 18,
+            job: Some(Job {
+                phone_number: Some(PhoneNumber {
+                    area_code: Some(61),
+                    number: 439222222,
+                }),
+            }),
+        }
+    );
+
+    // A value can be given directly as well, and the last setter wins
+    let phone = PhoneNumber::builder().number(1).number(2).build().unwrap();
+    let job = Job::builder().phone_number(phone).build().unwrap();
+    assert_eq!(job.phone_number.unwrap().area_code, None);
+
+    let rectangle = Rectangle::builder()
+        .top_left_with(|point| point.y(5.6))
+        .bottom_right(Point { x: 5.6, y: 0.0 })
+        .build()
+        .unwrap();
+    assert_eq!(rectangle.top_left, Point { x: 0.0, y: 5.6 });
+
+    let pair = Pair::builder().first("a").second("b").build().unwrap();
+    assert_eq!((pair.first, pair.second), ("a", "b"));
+
+    let labeled = Labeled::builder()
+        .label("xy")
+        .pair_with(|pair| pair.first(1).second(2))
+        .build()
+        .unwrap();
+    assert_eq!(
+        labeled.pair,
+        Pair {
+            first: 1,
+            second: 2
+        }
+    );
+}
+
+#[test]
+fn test_missing() {
+    let e = Pair::<i32>::builder().build().unwrap_err();
+    assert_eq!(e.missing, ["first", "second"]);
+    assert_eq!(
+        e.to_string(),
+        "missing fields for `Pair`: `first`, `second`"
+    );
+
+    // The fields missing from a nested builder, by their path
+    let e = Person::builder()
+        .job_with(|job| job.phone_number_with(|phone| phone.area_code(61)))
+        .build()
+        .unwrap_err();
+    assert_eq!(e.missing, ["name", "job.phone_number.number"]);
+
+    // Setting the value afterwards replaces the failed builder
+    let person = Person::builder()
+        .name("Ferris")
+        .job_with(|job| job.phone_number_with(|phone| phone))
+        .job(Job { phone_number: None })
+        .build();
+    assert!(person.is_ok());
+
+    let e = Rectangle::builder().build().unwrap_err();
+    assert_eq!(e.missing, ["top_left", "bottom_right"]);
+
+    let e = Labeled::builder()
+        .pair_with(|pair| pair.first(1.5))
+        .build()
+        .unwrap_err();
+    assert_eq!(e.missing, ["label", "pair.second"]);
+}
+
+#[test]
+fn test_misuse() {
+    trybuild::TestCases::new().compile_fail("tests/ui/builder/*.rs");
+}
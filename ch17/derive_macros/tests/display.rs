@@ -0,0 +1,115 @@
+// What `#[derive(Display)]` writes for enums, delegating fields, format specs
+// of their own and named arguments that use `self` and `f`. The examples of
+// chapters 1, 6 and 20 check that it reproduces their hand-written impls.
+use derive_macros::Display;
+
+#[derive(Display)]
+enum Shape {
+    #[display("circle of radius {0}")]
+    Circle(u32),
+    #[display("{w}x{h} rectangle")]
+    Rectangle {
+        w: u32,
+        h: u32,
+    },
+    // Hands over to the field, with the formatting options
+    Named(String),
+    Empty,
+}
+
+#[test]
+fn test_enums_and_delegation() {
+    assert_eq!(Shape::Circle(2).to_string(), "circle of radius 2");
+    assert_eq!(Shape::Rectangle { w: 3, h: 4 }.to_string(), "3x4 rectangle");
+    assert_eq!(format!("{:>6}", Shape::Named("a".to_owned())), "     a");
+    assert_eq!(format!("{:.2}", Shape::Named("abc".to_owned())), "ab");
+    assert_eq!(format!("[{:^7}]", Shape::Empty), "[ Empty ]");
+}
+
+#[derive(Display)]
+#[display(
+    "RGB ({red}, {green}, {blue}) 0x{red:-02X}{green:-02X}{blue:-02X} {{{label:>width$}}}",
+    label = "rgb"
+)]
+struct Color {
+    red: u8,
+    green: u8,
+    blue: u8,
+    width: usize,
+}
+
+#[derive(Display)]
+struct Meters {
+    #[display(delegate)]
+    value: f64,
+    #[allow(dead_code)]
+    exact: bool,
+}
+
+#[test]
+fn test_specs() {
+    let color = Color {
+        red: 0,
+        green: 3,
+        blue: 254,
+        width: 5,
+    };
+    assert_eq!(color.to_string(), "RGB (0, 3, 254) 0x0003FE {  rgb}");
+    // A spec of its own wins over the formatter's
+    assert_eq!(
+        format!("{:+}", color),
+        "RGB (+0, +3, +254) 0x0003FE {  rgb}"
+    );
+
+    let length = Meters {
+        value: 1.25,
+        exact: true,
+    };
+    assert_eq!(format!("{:>+8.1}", length), "    +1.2");
+}
+
+// Named arguments read the fields through `self` and the formatting options
+// through `f`
+#[derive(Display)]
+#[display(
+    "{name} {lat}",
+    lat = if f.sign_plus() {
+        format!("{:+}°", self.lat)
+    } else {
+        let c = if self.lat >= 0.0 { 'N' } else { 'S' };
+        format!("{}°{}", self.lat.abs(), c)
+    },
+)]
+struct Place {
+    name: &'static str,
+    lat: f64,
+}
+
+// Only the real part takes the sign
+#[derive(Display)]
+#[display(
+    "{re} + {im}i",
+    im = match f.precision() {
+        Some(precision) => format!("{:.*}", precision, self.im),
+        None => format!("{}", self.im),
+    },
+)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+#[test]
+fn test_self_and_formatter() {
+    let sydney = Place {
+        name: "Sydney",
+        lat: -33.9,
+    };
+    assert_eq!(sydney.to_string(), "Sydney 33.9°S");
+    assert_eq!(format!("{:+}", sydney), "Sydney -33.9°");
+    assert_eq!(format!("{:>15}", sydney), "  Sydney 33.9°S");
+
+    let z = Complex { re: 3.33, im: 7.26 };
+    assert_eq!(z.to_string(), "3.33 + 7.26i");
+    assert_eq!(format!("{:+.1}", z), "+3.3 + 7.3i");
+}
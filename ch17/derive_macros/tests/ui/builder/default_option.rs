@@ -0,0 +1,10 @@
+use derive_macros::Builder;
+
+#[derive(Builder)]
+struct PhoneNumber {
+    #[builder(default = 61)]
+    area_code: Option<u8>,
+    number: u32,
+}
+
+fn main() {}
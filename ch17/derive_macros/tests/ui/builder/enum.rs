@@ -0,0 +1,8 @@
+use derive_macros::Builder;
+
+#[derive(Builder)]
+enum Shape {
+    Circle { radius: f64 },
+}
+
+fn main() {}
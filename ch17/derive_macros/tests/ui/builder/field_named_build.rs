@@ -0,0 +1,8 @@
+use derive_macros::Builder;
+
+#[derive(Builder)]
+struct House {
+    build: u32,
+}
+
+fn main() {}
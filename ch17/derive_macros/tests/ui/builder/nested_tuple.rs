@@ -0,0 +1,9 @@
+use derive_macros::Builder;
+
+#[derive(Builder)]
+struct Segment {
+    #[builder(nested)]
+    ends: (f32, f32),
+}
+
+fn main() {}
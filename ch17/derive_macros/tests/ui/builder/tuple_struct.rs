@@ -0,0 +1,6 @@
+use derive_macros::Builder;
+
+#[derive(Builder)]
+struct Pair(i32, f32);
+
+fn main() {}
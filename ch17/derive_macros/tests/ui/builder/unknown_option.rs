@@ -0,0 +1,9 @@
+use derive_macros::Builder;
+
+#[derive(Builder)]
+struct Point {
+    #[builder(default, optional)]
+    x: f32,
+}
+
+fn main() {}
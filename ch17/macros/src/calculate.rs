@@ -0,0 +1,528 @@
+// The support code for `calculate!` and `try_calculate!`, a small language for
+// tables of constants that shows how each one is worked out:
+//
+//     calculate! { out;
+//         type u64,
+//         let kib = 1024,
+//         let mib = kib * 1024,
+//         eval pow(2, 20) == mib
+//     }
+//
+// Every `let` and `eval` is a line. A `let` binds a name that the lines after
+// it can use, and an `eval` just computes a value. With `== expected` at the
+// end, a line also checks that it comes out as `expected`. The type is
+// `usize` unless a `type` is given first. `calculate!` writes the lines to the
+// `out` before the `;`.
+//
+// The arithmetic is checked: `+`, `-`, `*`, `/`, `%` and the built-ins `min`,
+// `max`, `pow` and `abs` report an overflow, and the line it happened on,
+// instead of wrapping around or panicking.
+use std::any;
+use std::error;
+use std::fmt;
+
+// The numbers `calculate!` can work with. For floats, a result that is not
+// finite is an overflow.
+pub trait Number: Copy + PartialEq + PartialOrd + fmt::Display {
+    const ZERO: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    // Never called with a zero `rhs`
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn checked_abs(self) -> Option<Self>;
+    // Never called with a negative `exp`
+    fn checked_pow(self, exp: Self) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty)*; $abs:expr) => {$(
+        impl Number for $t {
+            const ZERO: Self = 0;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_div(self, rhs)
+            }
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_rem(self, rhs)
+            }
+            fn checked_neg(self) -> Option<Self> {
+                <$t>::checked_neg(self)
+            }
+            fn checked_abs(self) -> Option<Self> {
+                $abs(self)
+            }
+            fn checked_pow(self, exp: Self) -> Option<Self> {
+                u32::try_from(exp).ok().and_then(|exp| <$t>::checked_pow(self, exp))
+            }
+        }
+    )*};
+}
+
+impl_integer!(i8 i16 i32 i64 i128 isize; |x: Self| x.checked_abs());
+impl_integer!(u8 u16 u32 u64 u128 usize; Some);
+
+macro_rules! impl_float {
+    ($($t:ty)*) => {$(
+        impl Number for $t {
+            const ZERO: Self = 0.0;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                Some(self + rhs).filter(|x| x.is_finite())
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                Some(self - rhs).filter(|x| x.is_finite())
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                Some(self * rhs).filter(|x| x.is_finite())
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                Some(self / rhs).filter(|x| x.is_finite())
+            }
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                Some(self % rhs).filter(|x| x.is_finite())
+            }
+            fn checked_neg(self) -> Option<Self> {
+                Some(-self)
+            }
+            fn checked_abs(self) -> Option<Self> {
+                Some(self.abs())
+            }
+            fn checked_pow(self, exp: Self) -> Option<Self> {
+                Some(self.powf(exp)).filter(|x| x.is_finite())
+            }
+        }
+    )*};
+}
+
+impl_float!(f32 f64);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    Overflow,
+    DivisionByZero,
+    NegativeExponent,
+    // An `eval ... == expected` that didn't come out as expected
+    NotEqual { value: String, expected: String },
+}
+
+// Which line went wrong, and how. Lines count the `let`s and `eval`s from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalcError {
+    pub line: usize,
+    pub source: &'static str,
+    pub type_name: &'static str,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: `{}` ", self.line, self.source)?;
+        match &self.kind {
+            ErrorKind::Overflow => write!(f, "overflows {}", self.type_name),
+            ErrorKind::DivisionByZero => write!(f, "divides by zero"),
+            ErrorKind::NegativeExponent => write!(f, "has a negative exponent"),
+            ErrorKind::NotEqual { value, expected } => {
+                write!(f, "failed: {} != {}", value, expected)
+            }
+        }
+    }
+}
+
+impl error::Error for CalcError {}
+
+impl CalcError {
+    // For use by the macros
+    #[doc(hidden)]
+    pub fn new<T>(line: usize, source: &'static str, kind: ErrorKind) -> CalcError {
+        CalcError {
+            line,
+            source,
+            type_name: any::type_name::<T>(),
+            kind,
+        }
+    }
+}
+
+// A value in the middle of a calculation, or the first error on the way to
+// it. `calculate!` turns every number and name in an expression into one, so
+// that the operators below do the checking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checked<T>(Result<T, ErrorKind>);
+
+impl<T: Number> Checked<T> {
+    pub fn new(value: T) -> Self {
+        Checked(Ok(value))
+    }
+
+    pub fn into_result(self) -> Result<T, ErrorKind> {
+        self.0
+    }
+
+    fn and_then<F: FnOnce(T, T) -> Result<T, ErrorKind>>(self, rhs: Self, f: F) -> Self {
+        Checked(self.0.and_then(|a| rhs.0.and_then(|b| f(a, b))))
+    }
+
+    // The built-ins, which take their arguments as an array so that a wrong
+    // number of them doesn't type check
+    pub fn min<const N: usize>(args: [Self; N]) -> Self {
+        args.into_iter()
+            .reduce(|a, b| a.and_then(b, |a, b| Ok(if b < a { b } else { a })))
+            .expect("`min` needs an argument")
+    }
+
+    pub fn max<const N: usize>(args: [Self; N]) -> Self {
+        args.into_iter()
+            .reduce(|a, b| a.and_then(b, |a, b| Ok(if b > a { b } else { a })))
+            .expect("`max` needs an argument")
+    }
+
+    pub fn pow([base, exp]: [Self; 2]) -> Self {
+        base.and_then(exp, |base, exp| {
+            if exp < T::ZERO {
+                Err(ErrorKind::NegativeExponent)
+            } else {
+                base.checked_pow(exp).ok_or(ErrorKind::Overflow)
+            }
+        })
+    }
+
+    pub fn abs([x]: [Self; 1]) -> Self {
+        Checked(x.0.and_then(|x| x.checked_abs().ok_or(ErrorKind::Overflow)))
+    }
+}
+
+macro_rules! impl_op {
+    ($($bound:ident $method:ident $checked:ident $divides:literal),*) => {$(
+        impl<T: Number> std::ops::$bound for Checked<T> {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                self.and_then(rhs, |a, b| {
+                    if $divides && b == T::ZERO {
+                        Err(ErrorKind::DivisionByZero)
+                    } else {
+                        a.$checked(b).ok_or(ErrorKind::Overflow)
+                    }
+                })
+            }
+        }
+    )*};
+}
+
+impl_op!(
+    Add add checked_add false,
+    Sub sub checked_sub false,
+    Mul mul checked_mul false,
+    Div div checked_div true,
+    Rem rem checked_rem true
+);
+
+impl<T: Number> std::ops::Neg for Checked<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Checked(
+            self.0
+                .and_then(|x| x.checked_neg().ok_or(ErrorKind::Overflow)),
+        )
+    }
+}
+
+// A line of a calculation. `name` is `None` for an `eval`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row<T> {
+    pub name: Option<&'static str>,
+    pub expr: &'static str,
+    pub value: T,
+}
+
+// Like `mib = kib * 1024 = 1048576`, or just `kib = 1024` when there's
+// nothing to work out
+impl<T: fmt::Display> fmt::Display for Row<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.name {
+            write!(f, "{} = ", name)?;
+        }
+        let value = self.value.to_string();
+        if self.expr == value {
+            write!(f, "{}", value)
+        } else {
+            write!(f, "{} = {}", self.expr, value)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table<T> {
+    pub rows: Vec<Row<T>>,
+}
+
+impl<T: Copy> Table<T> {
+    pub fn new() -> Self {
+        Table { rows: Vec::new() }
+    }
+
+    // The value of a `let`
+    pub fn get(&self, name: &str) -> Option<T> {
+        self.rows
+            .iter()
+            .find(|row| row.name == Some(name))
+            .map(|row| row.value)
+    }
+}
+
+impl<T: Copy> Default for Table<T> {
+    fn default() -> Self {
+        Table::new()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Table<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.rows.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+// Work out a calculation, and return the `Table` of its lines, or the first
+// line that failed
+#[macro_export]
+macro_rules! try_calculate {
+    // Split the lines on the commas. `$table` is passed along, as names from
+    // different expansions of a macro can't see each other.
+    (@lines [$table:ident $t:ty] [$($line:tt)*]) => {
+        $crate::try_calculate!(@line [$table $t] $($line)*);
+    };
+    (@lines [$table:ident $t:ty] [$($line:tt)*] , $($rest:tt)*) => {
+        $crate::try_calculate!(@line [$table $t] $($line)*);
+        $crate::try_calculate!(@lines [$table $t] [] $($rest)*);
+    };
+    (@lines [$table:ident $t:ty] [$($line:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::try_calculate!(@lines [$table $t] [$($line)* $next] $($rest)*);
+    };
+
+    // A trailing comma leaves an empty line
+    (@line [$table:ident $t:ty]) => {};
+    (@line [$table:ident $t:ty] let $name:ident = $($e:tt)+) => {
+        // The last few lines of a table are often not used by the others
+        #[allow(unused_variables)]
+        let $name: $t = $crate::try_calculate!(
+            @eval [$table $t]
+            (stringify!(let $name = $($e)+)) (Some(stringify!($name))) (stringify!($($e)+))
+            [$($e)+]
+        );
+    };
+    (@line [$table:ident $t:ty] eval $($e:tt)+) => {
+        $crate::try_calculate!(@eq [$table $t] (stringify!(eval $($e)+)) [] $($e)+);
+    };
+
+    // Split `eval a == b` into `a` and `b`
+    (@eq [$table:ident $t:ty] ($source:expr) [$($e:tt)+] == $($expected:tt)+) => {
+        $crate::try_calculate!(
+            @eval [$table $t] ($source) (None) (stringify!($($e)+)) [$($e)+] [$($expected)+]
+        );
+    };
+    (@eq [$table:ident $t:ty] ($source:expr) [$($e:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::try_calculate!(@eq [$table $t] ($source) [$($e)* $next] $($rest)*);
+    };
+    (@eq [$table:ident $t:ty] ($source:expr) [$($e:tt)+]) => {
+        $crate::try_calculate!(@eval [$table $t] ($source) (None) (stringify!($($e)+)) [$($e)+]);
+    };
+
+    // Compute a line, check it, and add it to the table
+    (
+        @eval [$table:ident $t:ty] ($source:expr) ($name:expr) ($expr:expr)
+        [$($e:tt)+] $([$($expected:tt)+])?
+    ) => {{
+        let line = $table.rows.len() + 1;
+        let value: $t = match $crate::try_calculate!(@expr [$t] [] $($e)+).into_result() {
+            Ok(value) => value,
+            Err(kind) => return Err($crate::calculate::CalcError::new::<$t>(line, $source, kind)),
+        };
+        $(
+            let expected: $t =
+                match $crate::try_calculate!(@expr [$t] [] $($expected)+).into_result() {
+                    Ok(expected) => expected,
+                    Err(kind) => {
+                        return Err($crate::calculate::CalcError::new::<$t>(line, $source, kind))
+                    }
+                };
+            if value != expected {
+                let kind = $crate::calculate::ErrorKind::NotEqual {
+                    value: value.to_string(),
+                    expected: expected.to_string(),
+                };
+                return Err($crate::calculate::CalcError::new::<$t>(line, $source, kind));
+            }
+        )?
+        $table.rows.push($crate::calculate::Row { name: $name, expr: $expr, value });
+        value
+    }};
+
+    // Rewrite an expression so that every number and name is `Checked`, and
+    // the operators check for overflow. Rust's own precedence still applies.
+    (@expr [$t:ty] [$($out:tt)*]) => { $($out)* };
+    (@expr [$t:ty] [$($out:tt)*] + $($rest:tt)*) => {
+        $crate::try_calculate!(@expr [$t] [$($out)* +] $($rest)*)
+    };
+    (@expr [$t:ty] [$($out:tt)*] - $($rest:tt)*) => {
+        $crate::try_calculate!(@expr [$t] [$($out)* -] $($rest)*)
+    };
+    (@expr [$t:ty] [$($out:tt)*] * $($rest:tt)*) => {
+        $crate::try_calculate!(@expr [$t] [$($out)* *] $($rest)*)
+    };
+    (@expr [$t:ty] [$($out:tt)*] / $($rest:tt)*) => {
+        $crate::try_calculate!(@expr [$t] [$($out)* /] $($rest)*)
+    };
+    (@expr [$t:ty] [$($out:tt)*] % $($rest:tt)*) => {
+        $crate::try_calculate!(@expr [$t] [$($out)* %] $($rest)*)
+    };
+    // A built-in, like `pow(2, 10)`
+    (@expr [$t:ty] [$($out:tt)*] $f:ident ($($args:tt)*) $($rest:tt)*) => {
+        $crate::try_calculate!(
+            @expr [$t]
+            [$($out)* $crate::calculate::Checked::<$t>::$f($crate::try_calculate!(@args [$t] [] [] $($args)*))]
+            $($rest)*
+        )
+    };
+    (@expr [$t:ty] [$($out:tt)*] ($($inner:tt)*) $($rest:tt)*) => {
+        $crate::try_calculate!(
+            @expr [$t] [$($out)* ($crate::try_calculate!(@expr [$t] [] $($inner)*))] $($rest)*
+        )
+    };
+    // A number or a name
+    (@expr [$t:ty] [$($out:tt)*] $x:tt $($rest:tt)*) => {
+        $crate::try_calculate!(
+            @expr [$t] [$($out)* $crate::calculate::Checked::<$t>::new($x)] $($rest)*
+        )
+    };
+
+    // Split the arguments of a built-in on the commas, into an array
+    (@args [$t:ty] [$($done:tt)*] [$($arg:tt)*]) => {
+        [$($done)* $crate::try_calculate!(@expr [$t] [] $($arg)*)]
+    };
+    (@args [$t:ty] [$($done:tt)*] [$($arg:tt)*] , $($rest:tt)*) => {
+        $crate::try_calculate!(
+            @args [$t] [$($done)* $crate::try_calculate!(@expr [$t] [] $($arg)*),] [] $($rest)*
+        )
+    };
+    (@args [$t:ty] [$($done:tt)*] [$($arg:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::try_calculate!(@args [$t] [$($done)*] [$($arg)* $next] $($rest)*)
+    };
+
+    (type $t:ty, $($lines:tt)*) => {{
+        // The closure is there so that a failed line can `return`
+        #[allow(clippy::redundant_closure_call)]
+        let result = (|| {
+            let mut table = $crate::calculate::Table::<$t>::new();
+            $crate::try_calculate!(@lines [table $t] [] $($lines)*);
+            Ok::<_, $crate::calculate::CalcError>(table)
+        })();
+        result
+    }};
+    ($($lines:tt)*) => {
+        $crate::try_calculate!(type usize, $($lines)*)
+    };
+}
+
+// Work out a calculation and write every line to `$out`, or panic with the
+// line that failed
+#[macro_export]
+macro_rules! calculate {
+    ($out:expr; $($lines:tt)*) => {
+        match $crate::try_calculate!($($lines)*) {
+            Ok(table) => {
+                for row in &table.rows {
+                    writeln!($out, "{}", row)?;
+                }
+            }
+            Err(e) => panic!("{}", e),
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lets_and_builtins() {
+        let table = crate::try_calculate! {
+            type i64,
+            let a = 3,
+            let b = pow(a, 4) - 1,
+            let c = max(a, b, 7) * -(2 + 1),
+            eval min(abs(c), 100) % 7 == 2,
+            eval (b + 1) / a,
+        }
+        .unwrap();
+        assert_eq!(table.get("b"), Some(80));
+        assert_eq!(table.get("c"), Some(-240));
+        assert_eq!(table.rows[4].value, 27);
+        assert_eq!(
+            table.to_string(),
+            "a = 3\n\
+             b = pow(a, 4) - 1 = 80\n\
+             c = max(a, b, 7) * -(2 + 1) = -240\n\
+             min(abs(c), 100) % 7 = 2\n\
+             (b + 1) / a = 27"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let e = crate::try_calculate! {
+            type u8,
+            let a = 16,
+            let b = a * a,
+        }
+        .unwrap_err();
+        assert_eq!(e.line, 2);
+        assert_eq!(e.kind, ErrorKind::Overflow);
+        assert_eq!(e.to_string(), "line 2: `let b = a * a` overflows u8");
+
+        let e = crate::try_calculate! { eval 1, eval 2 / (1 - 1) }.unwrap_err();
+        assert_eq!((e.line, e.kind), (2, ErrorKind::DivisionByZero));
+
+        let e = crate::try_calculate! { type i32, eval pow(2, -1) }.unwrap_err();
+        assert_eq!(e.kind, ErrorKind::NegativeExponent);
+
+        let e = crate::try_calculate! { type i8, eval abs(-127 - 1) }.unwrap_err();
+        assert_eq!(e.kind, ErrorKind::Overflow);
+
+        let e = crate::try_calculate! { eval 1 + 2 == 4 }.unwrap_err();
+        assert_eq!(e.to_string(), "line 1: `eval 1 + 2 == 4` failed: 3 != 4");
+    }
+
+    #[test]
+    fn test_floats() {
+        let table = crate::try_calculate! {
+            type f64,
+            let r = 1.5,
+            eval pow(r, 2.0) * 4.0 == 9.0,
+        }
+        .unwrap();
+        assert_eq!(table.rows.len(), 2);
+
+        let e = crate::try_calculate! { type f32, eval 1e30 * 1e30 }.unwrap_err();
+        assert_eq!(e.to_string(), "line 1: `eval 1e30 * 1e30` overflows f32");
+    }
+}
@@ -1,11 +1,13 @@
-use std::ops::{Add, Mul, Sub};
+use std::io::{self, Write};
 
 // This is a simple macro named `say_hello`.
 macro_rules! say_hello {
-    // `()` indicates that the macro takes no argument.
-    () => {
+    // `($out:expr)` indicates that the macro takes one argument: where to
+    // write. The `out` of the code calling the macro can't be seen from its
+    // body, so it has to be passed in.
+    ($out:expr) => {
         // The macro will expand into the contents of this block.
-        println!("Hello!");
+        writeln!($out, "Hello!")?;
     };
 }
 
@@ -14,9 +16,9 @@ macro_rules! create_function {
     // creates a function named `$func_name`.
     // The `ident` designator is used for variable/function names.
     ($func_name:ident) => {
-        fn $func_name() {
+        fn $func_name(out: &mut impl Write) -> io::Result<()> {
             // The `stringify!` macro converts an `ident` into a string.
-            println!("You called {:?}()", stringify!($func_name));
+            writeln!(out, "You called {:?}()", stringify!($func_name))
         }
     };
 }
@@ -29,9 +31,9 @@ macro_rules! print_result {
     // This macro takes an expression of type `expr` and prints
     // it as a string along with its result.
     // The `expr` designator is used for expressions.
-    ($expression:expr) => {
+    ($out:expr, $expression:expr) => {
         // `stringify!` will convert the expression *as it is* into a string.
-        println!("{:?} = {:?}", stringify!($expression), $expression);
+        writeln!($out, "{:?} = {:?}", stringify!($expression), $expression)?;
     };
 }
 
@@ -40,22 +42,24 @@ macro_rules! print_result {
 macro_rules! test {
     // Arguments don't need to be separated by a comma.
     // Any template can be used!
-    ($left:expr; and $right:expr) => {
-        println!(
+    ($out:expr, $left:expr; and $right:expr) => {
+        writeln!(
+            $out,
             "{:?} and {:?} is {:?}",
             stringify!($left),
             stringify!($right),
             $left && $right
-        )
+        )?
     };
     // ^ each arm must end with a semicolon.
-    ($left:expr; or $right:expr) => {
-        println!(
+    ($out:expr, $left:expr; or $right:expr) => {
+        writeln!(
+            $out,
             "{:?} or {:?} is {:?}",
             stringify!($left),
             stringify!($right),
             $left || $right
-        )
+        )?
     };
 }
 
@@ -70,125 +74,98 @@ macro_rules! find_min {
     )
 }
 
-macro_rules! assert_equal_len {
-    // The `tt` (token tree) designator is used for
-    // operators and tokens.
-    ($a:expr, $b:expr, $func:ident, $op:tt) => {
-        assert!(
-            $a.len() == $b.len(),
-            "{:?}: dimension mismatch: {:?} {:?} {:?}",
-            stringify!($func),
-            ($a.len(),),
-            stringify!($op),
-            ($b.len(),)
-        );
-    };
-}
-
-macro_rules! op {
-    ($func:ident, $bound:ident, $op:tt, $method:ident) => {
-        fn $func<T: $bound<T, Output = T> + Copy>(xs: &mut Vec<T>, ys: &Vec<T>) {
-            assert_equal_len!(xs, ys, $func, $op);
-
-            for (x, y) in xs.iter_mut().zip(ys.iter()) {
-                *x = $bound::$method(*x, *y);
-                // *x = x.$method(*y);
-            }
-        }
-    };
-}
-
-// Implement `add_assign`, `mul_assign`, and `sub_assign` functions.
-op!(add_assign, Add, +=, add);
-op!(mul_assign, Mul, *=, mul);
-op!(sub_assign, Sub, -=, sub);
-
-mod test {
-    use std::iter;
-    macro_rules! test {
-        ($func:ident, $x:expr, $y:expr, $z:expr) => {
-            #[test]
-            fn $func() {
-                for size in 0usize..10 {
-                    let mut x: Vec<_> = iter::repeat($x).take(size).collect();
-                    let y: Vec<_> = iter::repeat($y).take(size).collect();
-                    let z: Vec<_> = iter::repeat($z).take(size).collect();
-
-                    super::$func(&mut x, &y);
-
-                    assert_eq!(x, z);
-                }
-            }
-        };
-    }
-
-    // Test `add_assign`, `mul_assign`, and `sub_assign`.
-    test!(add_assign, 1u32, 2u32, 3u32);
-    test!(mul_assign, 2u32, 3u32, 6u32);
-    test!(sub_assign, 3u32, 2u32, 1u32);
-}
+// The vector operations, made with the `op!` macro, are in `vector.rs`
+use macros::vector;
 
-macro_rules! calculate {
-    // The pattern for a single `eval`
-    (eval $e:expr) => {{
-        {
-            let val: usize = $e; // Force types to be integers
-            println!("{} = {}", stringify!{$e}, val);
-        }
-    }};
+// `calculate!`, with its checked arithmetic, is in `calculate.rs`
+use macros::{calculate, try_calculate};
 
-    // Decompose multiple `eval`s recursively
-    (eval $e:expr, $(eval $es:expr),+) => {{
-        calculate! { eval $e }
-        calculate! { $(eval $es),+ }
-    }};
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
 }
 
-fn main() {
-    // This call will expand into `println!("Hello");`
-    say_hello!();
-    say_hello! {}
+fn run(out: &mut impl Write) -> io::Result<()> {
+    // This call will expand into `writeln!(out, "Hello!")?;`
+    say_hello!(out);
+    say_hello! { out }
 
-    foo();
-    bar();
+    foo(out)?;
+    bar(out)?;
 
-    print_result!(1u32 + 1);
+    print_result!(out, 1u32 + 1);
 
     // Recall that blocks are expressions too!
-    print_result!({
+    print_result!(out, {
         let x = 1u32;
 
         x * x + 2 * x - 1
     });
 
-    print_result! { 1u32 + 1 };
-    print_result! {{
+    print_result! { out, 1u32 + 1 };
+    print_result! { out, {
         let x = 1u32;
 
         x * x + 2 * x - 1
     }};
 
-    test!(1i32 + 1 == 2i32; and 2i32 * 2 == 4i32);
-    test!(true; or false);
+    test!(out, 1i32 + 1 == 2i32; and 2i32 * 2 == 4i32);
+    test!(out, true; or false);
 
-    println!("{}", find_min!(1u32));
-    println!("{}", find_min!(1u32 + 2, 2u32));
-    println!("{}", find_min!(5u32, 2u32 * 3, 4u32));
+    writeln!(out, "{}", find_min!(1u32))?;
+    writeln!(out, "{}", find_min!(1u32 + 2, 2u32))?;
+    writeln!(out, "{}", find_min!(5u32, 2u32 * 3, 4u32))?;
 
-    calculate! {
+    calculate! { out;
         eval 1 + 2 // hehehe `eval` is _not_ a Rust keyword!
     }
 
-    calculate! {
+    calculate! { out;
         eval (1 + 2) * (3 / 4)
     }
 
-    calculate!(eval 1 + 2);
-    calculate!(eval(1 + 2) * (3 / 4));
+    calculate!(out; eval 1 + 2);
+    calculate!(out; eval(1 + 2) * (3 / 4));
 
-    calculate! { // Look ma! Variadic `calculate!`!
+    calculate! { out; // Look ma! Variadic `calculate!`!
         eval 1 + 2,
         eval 3 + 4,
         eval (2 * 3) + 1
     }
+
+    let mut xs = vec![1, 2, 3];
+    vector::add_assign(&mut xs, &[10, 20, 30]);
+    vector::shl_assign_scalar(&mut xs, 1);
+    writeln!(out, "{:?}", xs)?;
+    writeln!(out, "{:?}", vector::try_mul(&xs, &[1, 2, 3]))?;
+    match vector::try_sub_assign(&mut xs, &[1, 2]) {
+        Ok(()) => writeln!(out, "{:?}", xs)?,
+        Err(e) => writeln!(out, "Error: {}", e)?,
+    }
+
+    // A table of constants, with the type given, names for the lines, and
+    // checks that they come out as expected
+    calculate! { out;
+        type u64,
+        let kib = 1024,
+        let mib = kib * kib,
+        let gib = pow(2, 30),
+        eval gib / mib == kib,
+        eval max(mib, gib) - min(mib, gib) == 1_072_693_248
+    }
+
+    // The line that overflows is reported, instead of the whole program
+    // panicking or wrapping around
+    match try_calculate! {
+        type u32,
+        let gib = pow(2, 30),
+        let tib = gib * 1024,
+    } {
+        Ok(table) => writeln!(out, "{}", table)?,
+        Err(e) => writeln!(out, "Error: {}", e)?,
+    }
+
+    Ok(())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -0,0 +1,271 @@
+use std::error;
+use std::fmt;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Rem, Shl, Shr, Sub};
+
+// The error of every `try_*` function, when the two vectors don't have the
+// same length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    // The function that was called, like `"try_add_assign"`
+    pub func: &'static str,
+    pub op: &'static str,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: dimension mismatch: {} {} {}",
+            self.func, self.left, self.op, self.right
+        )
+    }
+}
+
+impl error::Error for DimensionMismatch {}
+
+macro_rules! check_equal_len {
+    // The `tt` (token tree) designator is used for
+    // operators and tokens.
+    ($a:expr, $b:expr, $func:ident, $op:tt) => {
+        if $a.len() != $b.len() {
+            return Err(DimensionMismatch {
+                func: stringify!($func),
+                op: stringify!($op),
+                left: $a.len(),
+                right: $b.len(),
+            });
+        }
+    };
+}
+
+// For an operator, like `+`, this generates
+//
+// * `add(xs, ys)`, `try_add(xs, ys)` and `add_scalar(xs, y)`, which return a
+//   new vector, and
+// * `add_assign(xs, ys)`, `try_add_assign(xs, ys)` and
+//   `add_assign_scalar(xs, y)`, which update `xs` in place.
+//
+// The `try_*` functions return a `DimensionMismatch` when `xs` and `ys` have
+// different lengths, the others panic with it. The `*_scalar` functions
+// apply `y` to every element of `xs`, so can't fail.
+macro_rules! op {
+    (
+        $bound:ident, $method:ident, $op:tt, $assign_op:tt:
+        $func:ident, $try_func:ident, $scalar_func:ident;
+        $assign:ident, $try_assign:ident, $scalar_assign:ident
+    ) => {
+        pub fn $try_assign<T: $bound<T, Output = T> + Copy>(
+            xs: &mut [T],
+            ys: &[T],
+        ) -> Result<(), DimensionMismatch> {
+            check_equal_len!(xs, ys, $try_assign, $assign_op);
+
+            for (x, y) in xs.iter_mut().zip(ys.iter()) {
+                *x = $bound::$method(*x, *y);
+            }
+            Ok(())
+        }
+
+        pub fn $assign<T: $bound<T, Output = T> + Copy>(xs: &mut [T], ys: &[T]) {
+            if let Err(e) = $try_assign(xs, ys) {
+                panic!("{}", e);
+            }
+        }
+
+        pub fn $scalar_assign<T: $bound<T, Output = T> + Copy>(xs: &mut [T], y: T) {
+            for x in xs.iter_mut() {
+                *x = $bound::$method(*x, y);
+            }
+        }
+
+        pub fn $try_func<T: $bound<T, Output = T> + Copy>(
+            xs: &[T],
+            ys: &[T],
+        ) -> Result<Vec<T>, DimensionMismatch> {
+            check_equal_len!(xs, ys, $try_func, $op);
+
+            Ok(xs
+                .iter()
+                .zip(ys.iter())
+                .map(|(x, y)| $bound::$method(*x, *y))
+                .collect())
+        }
+
+        pub fn $func<T: $bound<T, Output = T> + Copy>(xs: &[T], ys: &[T]) -> Vec<T> {
+            $try_func(xs, ys).unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        pub fn $scalar_func<T: $bound<T, Output = T> + Copy>(xs: &[T], y: T) -> Vec<T> {
+            xs.iter().map(|x| $bound::$method(*x, y)).collect()
+        }
+    };
+}
+
+// Arithmetic
+op!(Add, add, +, +=: add, try_add, add_scalar; add_assign, try_add_assign, add_assign_scalar);
+op!(Sub, sub, -, -=: sub, try_sub, sub_scalar; sub_assign, try_sub_assign, sub_assign_scalar);
+op!(Mul, mul, *, *=: mul, try_mul, mul_scalar; mul_assign, try_mul_assign, mul_assign_scalar);
+op!(Div, div, /, /=: div, try_div, div_scalar; div_assign, try_div_assign, div_assign_scalar);
+op!(Rem, rem, %, %=: rem, try_rem, rem_scalar; rem_assign, try_rem_assign, rem_assign_scalar);
+
+// Bitwise
+op!(BitAnd, bitand, &, &=: bitand, try_bitand, bitand_scalar;
+    bitand_assign, try_bitand_assign, bitand_assign_scalar);
+op!(BitOr, bitor, |, |=: bitor, try_bitor, bitor_scalar;
+    bitor_assign, try_bitor_assign, bitor_assign_scalar);
+op!(BitXor, bitxor, ^, ^=: bitxor, try_bitxor, bitxor_scalar;
+    bitxor_assign, try_bitxor_assign, bitxor_assign_scalar);
+op!(Shl, shl, <<, <<=: shl, try_shl, shl_scalar; shl_assign, try_shl_assign, shl_assign_scalar);
+op!(Shr, shr, >>, >>=: shr, try_shr, shr_scalar; shr_assign, try_shr_assign, shr_assign_scalar);
+
+#[cfg(test)]
+mod test {
+    use super::DimensionMismatch;
+    use std::iter;
+
+    // `$x op $y` must be `$z`, for every function generated for `op`
+    macro_rules! test {
+        (
+            $name:ident, $x:expr, $y:expr, $z:expr;
+            $func:ident, $try_func:ident, $scalar_func:ident;
+            $assign:ident, $try_assign:ident, $scalar_assign:ident
+        ) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn same_length() {
+                    // Including empty vectors
+                    for size in 0usize..10 {
+                        let x: Vec<_> = iter::repeat($x).take(size).collect();
+                        let y: Vec<_> = iter::repeat($y).take(size).collect();
+                        let z: Vec<_> = iter::repeat($z).take(size).collect();
+
+                        assert_eq!(super::super::$func(&x, &y), z);
+                        assert_eq!(super::super::$try_func(&x, &y), Ok(z.clone()));
+                        assert_eq!(super::super::$scalar_func(&x, $y), z);
+
+                        let mut xs = x.clone();
+                        super::super::$assign(&mut xs, &y);
+                        assert_eq!(xs, z);
+
+                        let mut xs = x.clone();
+                        assert_eq!(super::super::$try_assign(&mut xs, &y), Ok(()));
+                        assert_eq!(xs, z);
+
+                        let mut xs = x.clone();
+                        super::super::$scalar_assign(&mut xs, $y);
+                        assert_eq!(xs, z);
+                    }
+                }
+
+                #[test]
+                fn mismatch() {
+                    for (left, right) in [(0usize, 1usize), (3, 2), (2, 3)] {
+                        let x: Vec<_> = iter::repeat($x).take(left).collect();
+                        let y: Vec<_> = iter::repeat($y).take(right).collect();
+
+                        let e = super::super::$try_func(&x, &y).unwrap_err();
+                        assert_eq!(
+                            (e.func, e.left, e.right),
+                            (stringify!($try_func), left, right)
+                        );
+
+                        // Nothing is changed on an error
+                        let mut xs = x.clone();
+                        let e = super::super::$try_assign(&mut xs, &y).unwrap_err();
+                        assert_eq!(
+                            (e.func, e.left, e.right),
+                            (stringify!($try_assign), left, right)
+                        );
+                        assert_eq!(xs, x);
+                    }
+                }
+
+                #[test]
+                #[should_panic(expected = "dimension mismatch: 1")]
+                fn mismatch_panics() {
+                    super::super::$func(&[$x], &[$y, $y]);
+                }
+
+                #[test]
+                #[should_panic(expected = "dimension mismatch: 2")]
+                fn mismatch_assign_panics() {
+                    super::super::$assign(&mut [$x, $x], &[$y]);
+                }
+            }
+        };
+    }
+
+    test!(add, 1u32, 2u32, 3u32;
+        add, try_add, add_scalar; add_assign, try_add_assign, add_assign_scalar);
+    test!(sub, 3u32, 2u32, 1u32;
+        sub, try_sub, sub_scalar; sub_assign, try_sub_assign, sub_assign_scalar);
+    test!(mul, 2u32, 3u32, 6u32;
+        mul, try_mul, mul_scalar; mul_assign, try_mul_assign, mul_assign_scalar);
+    test!(div, 7u32, 2u32, 3u32;
+        div, try_div, div_scalar; div_assign, try_div_assign, div_assign_scalar);
+    test!(rem, 7u32, 2u32, 1u32;
+        rem, try_rem, rem_scalar; rem_assign, try_rem_assign, rem_assign_scalar);
+    test!(bitand, 0b1100u8, 0b1010u8, 0b1000u8;
+        bitand, try_bitand, bitand_scalar; bitand_assign, try_bitand_assign, bitand_assign_scalar);
+    test!(bitor, 0b1100u8, 0b1010u8, 0b1110u8;
+        bitor, try_bitor, bitor_scalar; bitor_assign, try_bitor_assign, bitor_assign_scalar);
+    test!(bitxor, 0b1100u8, 0b1010u8, 0b0110u8;
+        bitxor, try_bitxor, bitxor_scalar; bitxor_assign, try_bitxor_assign, bitxor_assign_scalar);
+    test!(shl, 3i64, 4i64, 48i64;
+        shl, try_shl, shl_scalar; shl_assign, try_shl_assign, shl_assign_scalar);
+    test!(shr, -48i64, 4i64, -3i64;
+        shr, try_shr, shr_scalar; shr_assign, try_shr_assign, shr_assign_scalar);
+
+    // Floats work too
+    test!(add_f64, 0.5f64, 0.25f64, 0.75f64;
+        add, try_add, add_scalar; add_assign, try_add_assign, add_assign_scalar);
+
+    // Beyond sizes 0..10, vectors of any length, and any values
+    #[test]
+    fn test_properties() {
+        use testing::prop::{self, ints, vecs, Gen};
+
+        let same_length = ints(0..=100usize).flat_map(|n| {
+            let values = || vecs(ints(-1000..=1000i64), n..=n);
+            (values(), values())
+        });
+        prop::check("vector_add_same_length", same_length, |(xs, ys)| {
+            let zs = super::try_add(&xs, &ys).unwrap();
+            let mut assigned = xs.clone();
+            super::add_assign(&mut assigned, &ys);
+            assert_eq!(zs, assigned);
+            for i in 0..xs.len() {
+                assert_eq!(zs[i], xs[i] + ys[i]);
+            }
+        });
+
+        let any_length = (vecs(ints(0..=255u8), 0..=20), vecs(ints(0..=255u8), 0..=20));
+        prop::check(
+            "vector_bitxor_lengths",
+            any_length,
+            |(xs, ys)| match super::try_bitxor(&xs, &ys) {
+                Ok(zs) => zs.len() == xs.len() && xs.len() == ys.len(),
+                Err(e) => (e.left, e.right) == (xs.len(), ys.len()) && e.left != e.right,
+            },
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let e = super::try_mul_assign(&mut [1, 2, 3], &[4, 5]).unwrap_err();
+        assert_eq!(
+            e,
+            DimensionMismatch {
+                func: "try_mul_assign",
+                op: "*=",
+                left: 3,
+                right: 2,
+            }
+        );
+        assert_eq!(e.to_string(), "try_mul_assign: dimension mismatch: 3 *= 2");
+    }
+}
@@ -1,26 +1,37 @@
-fn main() {
+use std::fmt;
+use std::io::{self, Write};
+
+mod validated;
+
+use validated::{Report, Validate, Validated};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let strings = vec!["tofu", "93", "18"];
     let numbers: Vec<_> = strings.into_iter().map(|s| s.parse::<i32>()).collect();
-    println!("Results: {:?}", numbers);
+    writeln!(out, "Results: {:?}", numbers)?;
 
     let strings = vec!["tofu", "93", "18"];
     let numbers: Vec<_> = strings
         .into_iter()
         .filter_map(|s| s.parse::<i32>().ok())
         .collect();
-    println!("Results: {:?}", numbers);
+    writeln!(out, "Results: {:?}", numbers)?;
 
     let strings = vec!["tofu", "93", "18"];
     let numbers: Result<Vec<_>, _> = strings.into_iter().map(|s| s.parse::<i32>()).collect();
-    println!("Results: {:?}", numbers);
+    writeln!(out, "Results: {:?}", numbers)?;
 
     let strings = vec!["tofu", "93", "18"];
     let (numbers, errors): (Vec<_>, Vec<_>) = strings
         .into_iter()
         .map(|s| s.parse::<i32>())
         .partition(Result::is_ok);
-    println!("Numbers: {:?}", numbers);
-    println!("Errors: {:?}", errors);
+    writeln!(out, "Numbers: {:?}", numbers)?;
+    writeln!(out, "Errors: {:?}", errors)?;
 
     let strings = vec!["tofu", "93", "18"];
     let (numbers, errors): (Vec<_>, Vec<_>) = strings
@@ -29,6 +40,81 @@ fn main() {
         .partition(Result::is_ok);
     let numbers: Vec<_> = numbers.into_iter().map(Result::unwrap).collect();
     let errors: Vec<_> = errors.into_iter().map(Result::unwrap_err).collect();
-    println!("Numbers: {:?}", numbers);
-    println!("Errors: {:?}", errors);
+    writeln!(out, "Numbers: {:?}", numbers)?;
+    writeln!(out, "Errors: {:?}", errors)?;
+
+    // A `Validated` keeps every error, and which input it came from, with no
+    // need to unwrap anything
+    let strings = vec!["tofu", "93", "", "18"];
+    let numbers = strings.into_iter().validate(|s| s.parse::<i32>());
+    match &numbers {
+        Validated::Valid(numbers) => writeln!(out, "Numbers: {:?}", numbers)?,
+        Validated::Invalid(errors) => writeln!(out, "{}", Report(errors))?,
+    }
+
+    // Several validations can be combined, to report every bad field of a
+    // form at once
+    for (name, age, email) in [
+        ("Ferris", "7", "ferris@rust-lang.org"),
+        ("", "-3", "ferris"),
+    ] {
+        match sign_up(name, age, email) {
+            Ok(user) => writeln!(
+                out,
+                "Welcome, {} ({}, {})!",
+                user.name, user.age, user.email
+            )?,
+            Err(errors) => writeln!(out, "{}", Report(&errors))?,
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct User {
+    name: String,
+    age: u8,
+    email: String,
 }
+
+#[derive(Debug)]
+struct FieldError {
+    field: &'static str,
+    message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+fn field_error(field: &'static str, message: impl ToString) -> FieldError {
+    FieldError {
+        field,
+        message: message.to_string(),
+    }
+}
+
+fn sign_up(name: &str, age: &str, email: &str) -> Result<User, Vec<FieldError>> {
+    let name = if name.is_empty() {
+        Err(field_error("name", "must not be empty"))
+    } else {
+        Ok(name.to_owned())
+    };
+    let age = age.parse::<u8>().map_err(|e| field_error("age", e));
+    let email = match email.split_once('@') {
+        Some((user, domain)) if !user.is_empty() && domain.contains('.') => Ok(email.to_owned()),
+        _ => Err(field_error("email", "must look like user@example.com")),
+    };
+
+    Validated::from(name)
+        .zip(age.into())
+        .zip(email.into())
+        .map(|((name, age), email)| User { name, age, email })
+        .into_result()
+}
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -0,0 +1,171 @@
+// Validation that keeps going: where collecting into a `Result` stops at the
+// first error, a `Validated` keeps every error, so that they can all be
+// shown at once.
+use std::fmt;
+use std::iter::FromIterator;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Validated<T, E> {
+    Valid(T),
+    // Never empty
+    Invalid(Vec<E>),
+}
+
+use Validated::{Invalid, Valid};
+
+impl<T, E> Validated<T, E> {
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Validated<U, E> {
+        match self {
+            Valid(value) => Valid(f(value)),
+            Invalid(errors) => Invalid(errors),
+        }
+    }
+
+    // Combine two validations. Unlike `Result::and`, the errors of both
+    // sides are kept, the ones of `self` first.
+    pub fn zip<U>(self, other: Validated<U, E>) -> Validated<(T, U), E> {
+        match (self, other) {
+            (Valid(a), Valid(b)) => Valid((a, b)),
+            (Valid(_), Invalid(errors)) | (Invalid(errors), Valid(_)) => Invalid(errors),
+            (Invalid(mut errors), Invalid(more)) => {
+                errors.extend(more);
+                Invalid(errors)
+            }
+        }
+    }
+
+    pub fn into_result(self) -> Result<T, Vec<E>> {
+        match self {
+            Valid(value) => Ok(value),
+            Invalid(errors) => Err(errors),
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for Validated<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Valid(value),
+            Err(e) => Invalid(vec![e]),
+        }
+    }
+}
+
+// Collecting `Result`s gives all of the values, or all of the errors
+impl<T, E> FromIterator<Result<T, E>> for Validated<Vec<T>, E> {
+    fn from_iter<I: IntoIterator<Item = Result<T, E>>>(iter: I) -> Self {
+        let mut values = Vec::new();
+        let mut errors = Vec::new();
+        for result in iter {
+            match result {
+                Ok(value) => values.push(value),
+                Err(e) => errors.push(e),
+            }
+        }
+        if errors.is_empty() {
+            Valid(values)
+        } else {
+            Invalid(errors)
+        }
+    }
+}
+
+// An error together with where it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure<I, E> {
+    pub index: usize,
+    pub input: I,
+    pub error: E,
+}
+
+impl<I: fmt::Debug, E: fmt::Display> fmt::Display for Failure<I, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "item {} ({:?}): {}", self.index, self.input, self.error)
+    }
+}
+
+pub trait Validate: Iterator + Sized {
+    // Run `check` on every item, and collect the values, or the errors with
+    // the index and the item they came from
+    fn validate<T, E, F>(self, mut check: F) -> Validated<Vec<T>, Failure<Self::Item, E>>
+    where
+        F: FnMut(&Self::Item) -> Result<T, E>,
+    {
+        self.enumerate()
+            .map(|(index, input)| {
+                check(&input).map_err(|error| Failure {
+                    index,
+                    input,
+                    error,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<I: Iterator> Validate for I {}
+
+// Lists errors one per line, with a count first:
+//
+//     2 errors:
+//       1. item 0 ("tofu"): invalid digit found in string
+//       2. item 2 (""): cannot parse integer from empty string
+pub struct Report<'a, E>(pub &'a [E]);
+
+impl<E: fmt::Display> fmt::Display for Report<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.len() {
+            0 => return write!(f, "no errors"),
+            1 => write!(f, "1 error:")?,
+            n => write!(f, "{} errors:", n)?,
+        }
+        // Align the messages when there are ten errors or more
+        let width = self.0.len().to_string().len();
+        for (i, error) in self.0.iter().enumerate() {
+            write!(f, "\n  {:>width$}. {}", i + 1, error, width = width)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect() {
+        let all: Validated<Vec<i32>, _> = ["1", "2"].iter().map(|s| s.parse::<i32>()).collect();
+        assert_eq!(all, Valid(vec![1, 2]));
+
+        let some: Validated<Vec<i32>, _> =
+            ["x", "2", ""].iter().map(|s| s.parse::<i32>()).collect();
+        assert_eq!(some.into_result().unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_validate() {
+        let numbers = vec!["tofu", "93", "", "18"]
+            .into_iter()
+            .validate(|s| s.parse::<i32>());
+        let failures = &numbers.into_result().unwrap_err();
+        assert_eq!(failures.len(), 2);
+        assert_eq!((failures[0].index, failures[0].input), (0, "tofu"));
+        assert_eq!((failures[1].index, failures[1].input), (2, ""));
+        assert_eq!(
+            Report(failures).to_string(),
+            "2 errors:\n  \
+             1. item 0 (\"tofu\"): invalid digit found in string\n  \
+             2. item 2 (\"\"): cannot parse integer from empty string"
+        );
+    }
+
+    #[test]
+    fn test_zip() {
+        let ok: Validated<i32, &str> = Valid(1);
+        let bad: Validated<i32, &str> = Invalid(vec!["a", "b"]);
+        let worse: Validated<i32, &str> = Invalid(vec!["c"]);
+        assert_eq!(ok.clone().zip(ok.clone()), Valid((1, 1)));
+        assert_eq!(ok.zip(bad.clone()), Invalid(vec!["a", "b"]));
+        assert_eq!(bad.zip(worse), Invalid(vec!["a", "b", "c"]));
+    }
+}
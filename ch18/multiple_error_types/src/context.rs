@@ -0,0 +1,252 @@
+// Adding context to errors: `.context("while parsing item 3")` wraps an
+// error, or turns a `None`, into a `ContextError` that says what was being
+// done, and where. The wrapped error stays available through `source()`, and
+// `Report` prints the whole chain of sources.
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::error;
+use std::fmt;
+use std::panic::Location;
+
+type BoxError = Box<dyn error::Error + Send + Sync + 'static>;
+
+pub struct ContextError {
+    message: String,
+    location: &'static Location<'static>,
+    // Only captured when enabled with `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`
+    backtrace: Option<Backtrace>,
+    // `None` when the context was added to an `Option`
+    source: Option<BoxError>,
+}
+
+impl ContextError {
+    #[track_caller]
+    pub fn new<M: Into<String>>(message: M, source: Option<BoxError>) -> ContextError {
+        let backtrace = Backtrace::capture();
+        ContextError {
+            message: message.into(),
+            location: Location::caller(),
+            backtrace: match backtrace.status() {
+                BacktraceStatus::Captured => Some(backtrace),
+                _ => None,
+            },
+            source,
+        }
+    }
+
+    // Where the context was added
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+
+    pub fn report(&self) -> Report<'_> {
+        Report::new(self)
+    }
+}
+
+// Only the message: the sources have their own `Display`, which `Report`
+// puts together
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// Like a derived `Debug`, with the location written as `file:line:col`.
+// The backtrace is left out: `Report` prints it.
+impl fmt::Debug for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ContextError")
+            .field("message", &self.message)
+            .field("location", &format_args!("{}", self.location))
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.source {
+            Some(source) => Some(source.as_ref()),
+            None => None,
+        }
+    }
+}
+
+// The `.context()` extension, for both `Result` and `Option`
+pub trait Context<T> {
+    fn context<M: Into<String>>(self, message: M) -> Result<T, ContextError>;
+
+    // Like `context`, but only builds the message if there is an error
+    fn with_context<M, F>(self, message: F) -> Result<T, ContextError>
+    where
+        M: Into<String>,
+        F: FnOnce() -> M;
+}
+
+impl<T, E> Context<T> for Result<T, E>
+where
+    E: error::Error + Send + Sync + 'static,
+{
+    #[track_caller]
+    fn context<M: Into<String>>(self, message: M) -> Result<T, ContextError> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(e) => Err(ContextError::new(message, Some(Box::new(e)))),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<M, F>(self, message: F) -> Result<T, ContextError>
+    where
+        M: Into<String>,
+        F: FnOnce() -> M,
+    {
+        match self {
+            Ok(value) => Ok(value),
+            Err(e) => Err(ContextError::new(message(), Some(Box::new(e)))),
+        }
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    #[track_caller]
+    fn context<M: Into<String>>(self, message: M) -> Result<T, ContextError> {
+        match self {
+            Some(value) => Ok(value),
+            None => Err(ContextError::new(message, None)),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<M, F>(self, message: F) -> Result<T, ContextError>
+    where
+        M: Into<String>,
+        F: FnOnce() -> M,
+    {
+        match self {
+            Some(value) => Ok(value),
+            None => Err(ContextError::new(message(), None)),
+        }
+    }
+}
+
+// Prints an error and all of its sources. `{}` puts them on one line:
+//
+//     while doubling the first item: invalid digit found in string
+//
+// and `{:#}` prints them as a tree, with the location of every context and
+// the first backtrace that was captured:
+//
+//     while doubling the first item, at src/main.rs:10:5
+//     └── invalid digit found in string
+pub struct Report<'a> {
+    error: &'a (dyn error::Error + 'static),
+}
+
+impl<'a> Report<'a> {
+    pub fn new(error: &'a (dyn error::Error + 'static)) -> Report<'a> {
+        Report { error }
+    }
+
+    fn chain(&self) -> impl Iterator<Item = &'a (dyn error::Error + 'static)> {
+        let mut next = Some(self.error);
+        std::iter::from_fn(move || {
+            let current = next?;
+            next = current.source();
+            Some(current)
+        })
+    }
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !f.alternate() {
+            for (depth, error) in self.chain().enumerate() {
+                if depth != 0 {
+                    write!(f, ": ")?;
+                }
+                write!(f, "{}", error)?;
+            }
+            return Ok(());
+        }
+
+        let mut backtrace = None;
+        for (depth, error) in self.chain().enumerate() {
+            if depth != 0 {
+                write!(f, "\n{}└── ", "    ".repeat(depth - 1))?;
+            }
+            write!(f, "{}", error)?;
+            if let Some(context) = error.downcast_ref::<ContextError>() {
+                write!(f, ", at {}", context.location())?;
+                backtrace = backtrace.or(context.backtrace());
+            }
+        }
+        if let Some(backtrace) = backtrace {
+            write!(f, "\n\nstack backtrace:\n{}", backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    fn parse_item(items: &[&str], index: usize) -> Result<i32, ContextError> {
+        let item = items
+            .get(index)
+            .with_context(|| format!("there is no item {}", index))?;
+        item.parse::<i32>()
+            .with_context(|| format!("while parsing item {}", index))
+    }
+
+    #[test]
+    fn test_context() {
+        assert_eq!(parse_item(&["1", "2"], 1).unwrap(), 2);
+
+        let e = parse_item(&["1", "x"], 1).unwrap_err();
+        assert_eq!(e.to_string(), "while parsing item 1");
+        assert_eq!(e.location().file(), file!());
+        assert!(e.source().is_some());
+
+        let e = parse_item(&["1"], 3).unwrap_err();
+        assert_eq!(e.to_string(), "there is no item 3");
+        assert!(e.source().is_none());
+    }
+
+    #[test]
+    fn test_report() {
+        let e = parse_item(&["x"], 0)
+            .context("while reading the input")
+            .unwrap_err();
+        assert_eq!(
+            e.report().to_string(),
+            "while reading the input: while parsing item 0: invalid digit found in string"
+        );
+
+        // The backtrace depends on the environment, so leave it out
+        let tree = format!("{:#}", e.report());
+        let tree = tree.split("\n\nstack backtrace:").next().unwrap();
+        let lines: Vec<_> = tree.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("while reading the input, at src/context.rs:"));
+        assert!(lines[1].starts_with("└── while parsing item 0, at src/context.rs:"));
+        assert_eq!(lines[2], "    └── invalid digit found in string");
+    }
+
+    #[test]
+    fn test_debug() {
+        let e = parse_item(&["x"], 0).unwrap_err();
+        let debug = format!("{:?}", e);
+        assert!(debug.starts_with(
+            "ContextError { message: \"while parsing item 0\", location: src/context.rs:"
+        ));
+        assert!(debug.ends_with(", source: Some(ParseIntError { kind: InvalidDigit }), .. }"));
+        assert!(!debug.contains("backtrace"));
+    }
+}
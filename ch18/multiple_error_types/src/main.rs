@@ -1,79 +1,105 @@
 use std::error;
-use std::error::Error as _;
 use std::fmt;
+use std::io::{self, Write};
 use std::num::ParseIntError;
 
-fn main() {
+mod context;
+
+use context::{Context, ContextError, Report};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let numbers = vec!["42", "93", "18"];
     let empty: Vec<&str> = vec![];
     let strings = vec!["tofu", "93", "18"];
-    println!("The first doubled is {}", double_first1(numbers));
-    // println!("The first doubled is {}", double_first1(empty));
+    writeln!(out, "The first doubled is {}", double_first1(numbers))?;
+    // writeln!(out, "The first doubled is {}", double_first1(empty))?;
     // Error 1: the input vector is empty
-    // println!("The first doubled is {}", double_first1(strings));
+    // writeln!(out, "The first doubled is {}", double_first1(strings))?;
     // Error 2: the element doesn't parse to a number
 
     let numbers = vec!["42", "93", "18"];
     let empty: Vec<&str> = vec![];
     let strings = vec!["tofu", "93", "18"];
-    println!("The first doubled is {:?}", double_first2(numbers));
-    // println!("The first doubled is {:?}", double_first2(empty));
+    writeln!(out, "The first doubled is {:?}", double_first2(numbers))?;
+    // writeln!(out, "The first doubled is {:?}", double_first2(empty))?;
     // Error 1: the input vector is empty
-    // println!("The first doubled is {:?}", double_first2(strings));
+    // writeln!(out, "The first doubled is {:?}", double_first2(strings))?;
     // Error 2: the element doesn't parse to a number
 
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
-    println!("The first doubled is {:?}", double_first3(numbers));
-    println!("The first doubled is {:?}", double_first3(empty));
-    println!("The first doubled is {:?}", double_first3(strings));
+    writeln!(out, "The first doubled is {:?}", double_first3(numbers))?;
+    writeln!(out, "The first doubled is {:?}", double_first3(empty))?;
+    writeln!(out, "The first doubled is {:?}", double_first3(strings))?;
 
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
-    print4(double_first4(numbers));
-    print4(double_first4(empty));
-    print4(double_first4(strings));
+    print4(out, double_first4(numbers))?;
+    print4(out, double_first4(empty))?;
+    print4(out, double_first4(strings))?;
 
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
-    print5(double_first5(numbers));
-    print5(double_first5(empty));
-    print5(double_first5(strings));
+    print5(out, double_first5(numbers))?;
+    print5(out, double_first5(empty))?;
+    print5(out, double_first5(strings))?;
 
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
-    print6(double_first6(numbers));
-    print6(double_first6(empty));
-    print6(double_first6(strings));
+    print6(out, double_first6(numbers))?;
+    print6(out, double_first6(empty))?;
+    print6(out, double_first6(strings))?;
 
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
-    print7(double_first7(numbers));
-    print7(double_first7(empty));
-    print7(double_first7(strings));
+    print7(out, double_first7(numbers))?;
+    print7(out, double_first7(empty))?;
+    print7(out, double_first7(strings))?;
+
+    Ok(())
 }
 
+// `.context()` turns a `None` or an error into a `ContextError`, which says
+// what went wrong in the words of the caller, and where. Unwrapping it
+// shows the error that caused it too.
 fn double_first1(vec: Vec<&str>) -> i32 {
-    let first = vec.first().unwrap(); // Generate error 1
-    2 * first.parse::<i32>().unwrap() // Generate error 2
+    let first = vec.first().context("the input vector is empty").unwrap(); // Generate error 1
+    2 * first
+        .parse::<i32>()
+        .with_context(|| format!("while parsing {:?}", first))
+        .unwrap() // Generate error 2
 }
 
-fn double_first2(vec: Vec<&str>) -> Option<Result<i32, ParseIntError>> {
-    vec.first().map(|first| first.parse::<i32>().map(|n| 2 * n))
+fn double_first2(vec: Vec<&str>) -> Option<Result<i32, ContextError>> {
+    vec.first().map(|first| {
+        first
+            .parse::<i32>()
+            .with_context(|| format!("while parsing {:?}", first))
+            .map(|n| 2 * n)
+    })
 }
 
-fn double_first3(vec: Vec<&str>) -> Result<Option<i32>, ParseIntError> {
-    let opt = vec.first().map(|first| first.parse::<i32>().map(|n| 2 * n));
+fn double_first3(vec: Vec<&str>) -> Result<Option<i32>, ContextError> {
+    let opt = vec.first().map(|first| {
+        first
+            .parse::<i32>()
+            .with_context(|| format!("while parsing {:?}", first))
+            .map(|n| 2 * n)
+    });
 
     opt.map_or(Ok(None), |r| r.map(Some))
 }
 
-type Result4<T> = std::result::Result<T, DoubleError4>;
+type Result4<T> = std::result::Result<T, ContextError>;
 
 // Define our error types. These may be customized for our error handling cases.
 // Now we will be able to write our own errors, defer to an underlying error
@@ -84,30 +110,35 @@ struct DoubleError4;
 // Generation of an error is completely separate from how it is displayed.
 // There's no need to be concerned about cluttering complex logic with the display style.
 //
-// Note that we don't store any extra info about the errors. This means we can't state
-// which string failed to parse without modifying our types to carry that information.
+// Note that we don't store any extra info about the errors. Rather than modifying
+// our types to carry which string failed to parse, we wrap them in a context.
 impl fmt::Display for DoubleError4 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "invalid first item to double")
     }
 }
 
+// Only errors can be the source of a context
+impl error::Error for DoubleError4 {}
+
 fn double_first4(vec: Vec<&str>) -> Result4<i32> {
     vec.first()
         // Change the error to our new type.
         .ok_or(DoubleError4)
+        .context("while taking the first item")
         .and_then(|s| {
             s.parse::<i32>()
                 // Update to the new error type here also.
                 .map_err(|_| DoubleError4)
+                .with_context(|| format!("while parsing {:?}", s))
                 .map(|i| 2 * i)
         })
 }
 
-fn print4(result: Result4<i32>) {
+fn print4(out: &mut impl Write, result: Result4<i32>) -> io::Result<()> {
     match result {
-        Ok(n) => println!("The first doubled is {}", n),
-        Err(e) => println!("Error: {}", e),
+        Ok(n) => writeln!(out, "The first doubled is {}", n),
+        Err(e) => writeln!(out, "Error: {}", e.report()),
     }
 }
 
@@ -127,18 +158,21 @@ impl error::Error for EmptyVec5 {}
 
 fn double_first5(vec: Vec<&str>) -> Result5<i32> {
     vec.first()
-        .ok_or_else(|| EmptyVec5.into()) // Converts to Box
+        .ok_or(EmptyVec5)
+        .context("while taking the first item")
+        .map_err(|e| e.into()) // Converts to Box
         .and_then(|s| {
             s.parse::<i32>()
+                .with_context(|| format!("while parsing {:?}", s))
                 .map_err(|e| e.into()) // Converts to Box
                 .map(|i| 2 * i)
         })
 }
 
-fn print5(result: Result5<i32>) {
+fn print5(out: &mut impl Write, result: Result5<i32>) -> io::Result<()> {
     match result {
-        Ok(n) => println!("The first doubled is {}", n),
-        Err(e) => println!("Error: {}", e),
+        Ok(n) => writeln!(out, "The first doubled is {}", n),
+        Err(e) => writeln!(out, "Error: {}", Report::new(e.as_ref())),
     }
 }
 
@@ -159,19 +193,24 @@ impl error::Error for EmptyVec6 {}
 // The same structure as before but rather than chain all `Results`
 // and `Options` along, we `?` to get the inner value out immediately.
 fn double_first6(vec: Vec<&str>) -> Result6<i32> {
-    let first = vec.first().ok_or(EmptyVec6)?;
-    let parsed = first.parse::<i32>()?;
+    let first = vec
+        .first()
+        .ok_or(EmptyVec6)
+        .context("while taking the first item")?;
+    let parsed = first
+        .parse::<i32>()
+        .with_context(|| format!("while parsing {:?}", first))?;
     Ok(2 * parsed)
 }
 
-fn print6(result: Result6<i32>) {
+fn print6(out: &mut impl Write, result: Result6<i32>) -> io::Result<()> {
     match result {
-        Ok(n) => println!("The first doubled is {}", n),
-        Err(e) => println!("Error: {}", e),
+        Ok(n) => writeln!(out, "The first doubled is {}", n),
+        Err(e) => writeln!(out, "Error: {}", Report::new(e.as_ref())),
     }
 }
 
-type Result7<T> = std::result::Result<T, DoubleError7>;
+type Result7<T> = std::result::Result<T, ContextError>;
 
 #[derive(Debug)]
 enum DoubleError7 {
@@ -214,22 +253,92 @@ impl From<ParseIntError> for DoubleError7 {
 }
 
 fn double_first7(vec: Vec<&str>) -> Result7<i32> {
-    let first = vec.first().ok_or(DoubleError7::EmptyVec)?;
-    // Here we implicitly use the `ParseIntError` implementation of `From` (which
-    // we defined above) in order to create a `DoubleError`.
-    let parsed = first.parse::<i32>()?;
+    let first = vec
+        .first()
+        .ok_or(DoubleError7::EmptyVec)
+        .context("while taking the first item")?;
+    // Here we explicitly use the `ParseIntError` implementation of `From` (which
+    // we defined above) in order to create a `DoubleError`, and then say which
+    // string it was.
+    let parsed = first
+        .parse::<i32>()
+        .map_err(DoubleError7::from)
+        .with_context(|| format!("while parsing {:?}", first))?;
 
     Ok(2 * parsed)
 }
 
-fn print7(result: Result7<i32>) {
+// The report walks the whole chain of sources, not just the first one
+fn print7(out: &mut impl Write, result: Result7<i32>) -> io::Result<()> {
     match result {
-        Ok(n) => println!("The first doubled is {}", n),
-        Err(e) => {
-            println!("Error: {}", e);
-            if let Some(source) = e.source() {
-                println!("  Caused by: {}", source);
+        Ok(n) => writeln!(out, "The first doubled is {}", n),
+        Err(e) => writeln!(out, "Error: {:#}", e.report()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // The backtraces are only there with `RUST_BACKTRACE` set, and the
+    // locations change with every edit, so both are left out
+    fn stable(output: &str) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut frames = false;
+        // Each line with its line ending, which is kept
+        for line in output.split_inclusive('\n') {
+            let text = line.trim_end_matches(['\r', '\n']);
+            if text == "stack backtrace:" {
+                // and the empty line before it
+                lines.pop();
+                frames = true;
+            } else if !(frames && (text.is_empty() || text.starts_with(' '))) {
+                frames = false;
+                lines.push(redact_locations(line));
+            }
+        }
+        lines.concat()
+    }
+
+    // `src/main.rs:264:10` becomes `src/main.rs:LINE:COL`
+    fn redact_locations(line: &str) -> String {
+        let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let mut redacted = String::new();
+        let mut rest = line;
+        while let Some(at) = rest.find(".rs:") {
+            let (file, after) = rest.split_at(at + ".rs:".len());
+            redacted.push_str(file);
+            let line_len = digits(after);
+            let col_len = after
+                .get(line_len + 1..)
+                .filter(|_| line_len > 0 && after[line_len..].starts_with(':'))
+                .map_or(0, digits);
+            if col_len == 0 {
+                rest = after;
+                continue;
             }
+            redacted.push_str("LINE:COL");
+            rest = &after[line_len + 1 + col_len..];
         }
+        redacted.push_str(rest);
+        redacted
+    }
+
+    #[test]
+    fn test_stable() {
+        let output = "Error: while taking the first item, at src/main.rs:264:10\n\
+                      └── please use a vector\n\
+                      \n\
+                      stack backtrace:\n   \
+                      0: multiple_error_types::main\n             \
+                      at ./src/main.rs:11:5\n\
+                      \n\
+                      The first doubled is 84 (main.rs:1)\n";
+        assert_eq!(
+            stable(output),
+            "Error: while taking the first item, at src/main.rs:LINE:COL\n\
+             └── please use a vector\n\
+             The first doubled is 84 (main.rs:1)\n"
+        );
     }
+
+    testing::snapshot_test!(super::run, |snapshot| snapshot.filter(stable));
 }
@@ -1,19 +1,39 @@
 #![allow(dead_code)]
 
-fn main() {
+use std::io::{self, Write};
+use std::panic::AssertUnwindSafe;
+
+mod pipeline;
+
+use derive_macros::Builder;
+use panics::boundary::run_guarded;
+use pipeline::Pipeline;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let water = Some("water");
     let lemonade = Some("lemonade");
     let void = None;
 
-    give_adult(water);
-    give_adult(lemonade);
-    give_adult(void);
+    give_adult(out, water)?;
+    give_adult(out, lemonade)?;
+    give_adult(out, void)?;
 
     let coffee = Some("coffee");
     let nothing: Option<&str> = None;
 
-    drink(coffee);
-    // drink(nothing); // panics
+    drink(out, coffee)?;
+    // drink(out, nothing)?; // panics
+
+    // Behind a panic boundary, the panic is reported and we carry on. `out`
+    // is only borrowed by the closure, so it's fine to use after a panic.
+    match run_guarded(AssertUnwindSafe(|| drink(out, nothing))) {
+        Ok(written) => written?,
+        Err(report) => report.eprint(),
+    }
 
     let p = Person {
         job: Some(Job {
@@ -26,6 +46,24 @@ fn main() {
 
     assert_eq!(p.work_phone_area_code(), Some(61));
 
+    // The same person from a builder, where each nested builder is filled in
+    // by a closure
+    let p = Person::builder()
+        .job_with(|job| job.phone_number_with(|phone| phone.area_code(61).number(439222222)))
+        .build()
+        .unwrap();
+
+    assert_eq!(p.work_phone_area_code(), Some(61));
+
+    // A phone number without a number isn't much use
+    match Person::builder()
+        .job_with(|job| job.phone_number_with(|phone| phone.area_code(61)))
+        .build()
+    {
+        Ok(_) => writeln!(out, "Built a person with half a phone number?")?,
+        Err(e) => writeln!(out, "{}", e)?,
+    }
+
     let apple = Some(Food1::Apple);
     let carrot = Some(Food1::Carrot);
     let potato: Option<Food1> = None;
@@ -35,38 +73,63 @@ fn main() {
     // Let's try the simpler looking `process()` now.
     let cooked_potato = process(potato);
 
-    eat1(cooked_apple);
-    eat1(cooked_carrot);
-    eat1(cooked_potato);
+    eat1(out, cooked_apple)?;
+    eat1(out, cooked_carrot)?;
+    eat1(out, cooked_potato)?;
 
     let (cordon_bleu, steak, sushi) = (Food2::CordonBleu, Food2::Steak, Food2::Sushi);
 
-    eat2(cordon_bleu, Day::Monday);
-    eat2(steak, Day::Tuesday);
-    eat2(sushi, Day::Wednesday);
+    eat2(out, cordon_bleu, Day::Monday)?;
+    eat2(out, steak, Day::Tuesday)?;
+    eat2(out, sushi, Day::Wednesday)?;
+
+    // The same steps as a pipeline, run over a whole basket at once. Some
+    // food goes bad while waiting, and cooking may fail.
+    let kitchen = Pipeline::new()
+        .stage("inspect", |food: Option<Food1>| food)
+        .map("peel", Peeled)
+        .map("chop", |Peeled(food)| Chopped(food))
+        .stage("cook", |Chopped(food)| match food {
+            Food1::Potato => Err("the potato got burnt"),
+            food => Ok(Cooked(food)),
+        });
+    // Compile-time Error! Chopping needs peeled food:
+    // let kitchen = Pipeline::new().map("chop", |Peeled(food)| Chopped(food)).map("peel", Peeled);
+
+    let basket = vec![
+        Some(Food1::Apple),
+        None,
+        Some(Food1::Potato),
+        Some(Food1::Carrot),
+    ];
+    let batch = kitchen.run_parallel(basket, 2);
+    for food in batch.outputs {
+        eat1(out, Some(food))?;
+    }
+    writeln!(out, "{}", batch.report)
 }
 
 // The adult has seen it all, and can handle any drink well.
 // All drinks are handled explicitly using `match`.
-fn give_adult(drink: Option<&str>) {
+fn give_adult(out: &mut impl Write, drink: Option<&str>) -> io::Result<()> {
     // Specify a course of action for each case.
     match drink {
-        Some("lemonade") => println!("Yuck! Too sugary."),
-        Some(inner) => println!("{}? How nice.", inner),
-        None => println!("No drink? Oh well."),
+        Some("lemonade") => writeln!(out, "Yuck! Too sugary."),
+        Some(inner) => writeln!(out, "{}? How nice.", inner),
+        None => writeln!(out, "No drink? Oh well."),
     }
 }
 
 // Others will `panic` before drinking sugary drinks.
 // All drinks are handled implicitly using `unwrap`.
-fn drink(drink: Option<&str>) {
+fn drink(out: &mut impl Write, drink: Option<&str>) -> io::Result<()> {
     // `unwrap` returns a `panic` when it receives a `None`.
     let inside = drink.unwrap();
     if inside == "lemonade" {
         panic!("AAAaaaaa!!!!");
     }
 
-    println!("I love {}s!!!!!", inside);
+    writeln!(out, "I love {}s!!!!!", inside)
 }
 
 fn next_birthday(current_age: Option<u8>) -> Option<String> {
@@ -76,16 +139,19 @@ fn next_birthday(current_age: Option<u8>) -> Option<String> {
     Some(format!("Next year I will be {}", next_age))
 }
 
+#[derive(Builder)]
 struct Person {
+    #[builder(nested)]
     job: Option<Job>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Builder)]
 struct Job {
+    #[builder(nested)]
     phone_number: Option<PhoneNumber>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Builder)]
 struct PhoneNumber {
     area_code: Option<u8>,
     number: u32,
@@ -147,10 +213,10 @@ fn process(food: Option<Food1>) -> Option<Cooked> {
 }
 
 // Check whether there's food or not before trying to eat it!
-fn eat1(food: Option<Cooked>) {
+fn eat1(out: &mut impl Write, food: Option<Cooked>) -> io::Result<()> {
     match food {
-        Some(food) => println!("Mmm. I love {:?}", food),
-        None => println!("Oh no! It wasn't edible."),
+        Some(food) => writeln!(out, "Mmm. I love {:?}", food),
+        None => writeln!(out, "Oh no! It wasn't edible."),
     }
 }
 
@@ -200,9 +266,12 @@ fn cookable_v2(food: Food2) -> Option<Food2> {
     have_recipe(food).and_then(have_ingredients)
 }
 
-fn eat2(food: Food2, day: Day) {
+fn eat2(out: &mut impl Write, food: Food2, day: Day) -> io::Result<()> {
     match cookable_v2(food) {
-        Some(food) => println!("Yay! On {:?} we get to eat {:?}.", day, food),
-        None => println!("Oh no. We don't get to eat on {:?}?", day),
+        Some(food) => writeln!(out, "Yay! On {:?} we get to eat {:?}.", day, food),
+        None => writeln!(out, "Oh no. We don't get to eat on {:?}?", day),
     }
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
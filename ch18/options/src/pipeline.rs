@@ -0,0 +1,282 @@
+// A pipeline of stages, like `peel`, `chop` and `cook`, that is built once
+// and then run over a batch of items. Each stage takes what the previous one
+// produced, so stages can only be added in an order that type checks.
+//
+// A stage may return a plain value, an `Option` or a `Result`. `None` drops
+// the item, `Err` fails it, and the report of a batch says which stage did
+// either, to which item.
+use std::fmt;
+use std::thread;
+
+// What a stage returns
+pub trait Outcome {
+    type Output;
+
+    // `Err(None)` drops the item, `Err(Some(message))` fails it
+    fn into_outcome(self) -> Result<Self::Output, Option<String>>;
+}
+
+impl<T> Outcome for Option<T> {
+    type Output = T;
+
+    fn into_outcome(self) -> Result<T, Option<String>> {
+        self.ok_or(None)
+    }
+}
+
+impl<T, E: fmt::Display> Outcome for Result<T, E> {
+    type Output = T;
+
+    fn into_outcome(self) -> Result<T, Option<String>> {
+        self.map_err(|e| Some(e.to_string()))
+    }
+}
+
+// Where an item stopped, and why
+struct Stop {
+    stage: usize,
+    error: Option<String>,
+}
+
+type Run<In, Out> = Box<dyn Fn(In) -> Result<Out, Stop> + Send + Sync>;
+
+// Turns `In`s into `Out`s. The stages are `Send + Sync` so that a batch can
+// be split across threads.
+pub struct Pipeline<In, Out> {
+    run: Run<In, Out>,
+    stages: Vec<&'static str>,
+}
+
+impl<T: 'static> Pipeline<T, T> {
+    // A pipeline with no stages yet, which passes items through
+    pub fn new() -> Self {
+        Pipeline {
+            run: Box::new(Ok),
+            stages: Vec::new(),
+        }
+    }
+}
+
+impl<T: 'static> Default for Pipeline<T, T> {
+    fn default() -> Self {
+        Pipeline::new()
+    }
+}
+
+impl<In: 'static, Out: 'static> Pipeline<In, Out> {
+    // Add a stage that takes what the pipeline produces so far
+    pub fn stage<F, R>(self, name: &'static str, f: F) -> Pipeline<In, R::Output>
+    where
+        F: Fn(Out) -> R + Send + Sync + 'static,
+        R: Outcome,
+    {
+        let index = self.stages.len();
+        let previous = self.run;
+        let mut stages = self.stages;
+        stages.push(name);
+        Pipeline {
+            run: Box::new(move |item| {
+                f(previous(item)?).into_outcome().map_err(|error| Stop {
+                    stage: index,
+                    error,
+                })
+            }),
+            stages,
+        }
+    }
+
+    // Like `stage`, for stages that always succeed
+    pub fn map<F, U>(self, name: &'static str, f: F) -> Pipeline<In, U>
+    where
+        F: Fn(Out) -> U + Send + Sync + 'static,
+        U: 'static,
+    {
+        self.stage(name, move |item| Some(f(item)))
+    }
+
+    // Run every item through the pipeline, in order
+    pub fn run(&self, items: Vec<In>) -> Batch<Out> {
+        let results = items.into_iter().map(|item| (self.run)(item)).collect();
+        self.collect(results)
+    }
+
+    // Like `run`, but spread over up to `threads` threads. The outputs and
+    // the report are the same as for `run`, in the same order.
+    pub fn run_parallel(&self, items: Vec<In>, threads: usize) -> Batch<Out>
+    where
+        In: Send,
+        Out: Send,
+    {
+        let chunk_size = items.len().div_ceil(threads.max(1)).max(1);
+        let mut chunks = Vec::new();
+        let mut items = items.into_iter();
+        loop {
+            let chunk: Vec<In> = items.by_ref().take(chunk_size).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            chunks.push(chunk);
+        }
+
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = chunks
+                .into_iter()
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .into_iter()
+                            .map(|item| (self.run)(item))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        self.collect(results)
+    }
+
+    fn collect(&self, results: Vec<Result<Out, Stop>>) -> Batch<Out> {
+        let mut report = Report {
+            stages: self
+                .stages
+                .iter()
+                .map(|&name| StageReport {
+                    name,
+                    input: 0,
+                    dropped: Vec::new(),
+                    failed: Vec::new(),
+                })
+                .collect(),
+        };
+        let mut outputs = Vec::new();
+        for (index, result) in results.into_iter().enumerate() {
+            // Every stage up to the one that stopped the item saw it
+            let reached = match &result {
+                Ok(_) => self.stages.len(),
+                Err(stop) => stop.stage + 1,
+            };
+            for stage in &mut report.stages[..reached] {
+                stage.input += 1;
+            }
+            match result {
+                Ok(output) => outputs.push(output),
+                Err(Stop { stage, error: None }) => report.stages[stage].dropped.push(index),
+                Err(Stop {
+                    stage,
+                    error: Some(message),
+                }) => report.stages[stage].failed.push((index, message)),
+            }
+        }
+        Batch { outputs, report }
+    }
+}
+
+// The outputs of a batch, and what happened to the other items
+pub struct Batch<T> {
+    pub outputs: Vec<T>,
+    pub report: Report,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageReport {
+    pub name: &'static str,
+    // How many items reached this stage
+    pub input: usize,
+    // The indices of the items the stage dropped
+    pub dropped: Vec<usize>,
+    // The indices of the items the stage failed, with the error
+    pub failed: Vec<(usize, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub stages: Vec<StageReport>,
+}
+
+// A table with a row per stage, and then every failure:
+//
+//     stage        in  out  dropped  failed
+//     peel          3    2        1       0
+//     ...
+//     item 2 failed at cook: burnt
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<10}{:>5}{:>5}{:>9}{:>8}",
+            "stage", "in", "out", "dropped", "failed"
+        )?;
+        for stage in &self.stages {
+            let stopped = stage.dropped.len() + stage.failed.len();
+            write!(
+                f,
+                "\n{:<10}{:>5}{:>5}{:>9}{:>8}",
+                stage.name,
+                stage.input,
+                stage.input - stopped,
+                stage.dropped.len(),
+                stage.failed.len()
+            )?;
+        }
+        for stage in &self.stages {
+            for (index, message) in &stage.failed {
+                write!(f, "\nitem {} failed at {}: {}", index, stage.name, message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline() -> Pipeline<i32, String> {
+        Pipeline::new()
+            .stage("positive", |n: i32| if n > 0 { Some(n) } else { None })
+            .stage("small", |n| {
+                if n < 100 {
+                    Ok(n as u8)
+                } else {
+                    Err(format!("{} is too big", n))
+                }
+            })
+            .map("format", |n| format!("<{}>", n))
+    }
+
+    #[test]
+    fn test_run() {
+        let batch = pipeline().run(vec![1, -2, 300, 4, 0]);
+        assert_eq!(batch.outputs, vec!["<1>", "<4>"]);
+
+        let stages = &batch.report.stages;
+        assert_eq!(stages[0].input, 5);
+        assert_eq!(stages[0].dropped, vec![1, 4]);
+        assert_eq!(stages[1].input, 3);
+        assert_eq!(stages[1].failed, vec![(2, "300 is too big".to_owned())]);
+        assert_eq!(stages[2].input, 2);
+        assert_eq!(
+            batch.report.to_string(),
+            "stage        in  out  dropped  failed\n\
+             positive      5    3        2       0\n\
+             small         3    2        0       1\n\
+             format        2    2        0       0\n\
+             item 2 failed at small: 300 is too big"
+        );
+    }
+
+    #[test]
+    fn test_run_parallel() {
+        let items: Vec<i32> = (-50..150).collect();
+        let serial = pipeline().run(items.clone());
+        for threads in [1, 3, 8, 1000] {
+            let parallel = pipeline().run_parallel(items.clone(), threads);
+            assert_eq!(parallel.outputs, serial.outputs);
+            assert_eq!(parallel.report, serial.report);
+        }
+        assert!(pipeline().run_parallel(vec![], 4).outputs.is_empty());
+    }
+}
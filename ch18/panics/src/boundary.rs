@@ -0,0 +1,221 @@
+// A panic boundary: `run_guarded` runs a closure and turns a panic inside it
+// into a `PanicReport`, so that one bad record doesn't take a whole batch
+// down with it.
+//
+// `catch_unwind` alone only gets the panic payload. The message, location,
+// thread and backtrace are only known to the panic hook, so a hook is
+// installed that hands them over when the panicking thread is inside
+// `run_guarded`. Panics anywhere else go to the previous hook as usual.
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::{Cell, RefCell};
+use std::error;
+use std::fmt;
+use std::panic::{self, PanicHookInfo, UnwindSafe};
+use std::process;
+use std::sync::Once;
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for PanicLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+#[derive(Debug)]
+pub struct PanicReport {
+    pub message: String,
+    pub location: Option<PanicLocation>,
+    // `None` for threads without a name
+    pub thread: Option<String>,
+    // Only captured when enabled with `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`.
+    // Boxed, as it is large and rarely there.
+    pub backtrace: Option<Box<Backtrace>>,
+}
+
+impl PanicReport {
+    fn from_hook(info: &PanicHookInfo) -> PanicReport {
+        let backtrace = Backtrace::capture();
+        PanicReport {
+            message: payload_message(info.payload()),
+            location: info.location().map(|location| PanicLocation {
+                file: location.file().to_owned(),
+                line: location.line(),
+                column: location.column(),
+            }),
+            thread: thread::current().name().map(str::to_owned),
+            backtrace: match backtrace.status() {
+                BacktraceStatus::Captured => Some(Box::new(backtrace)),
+                _ => None,
+            },
+        }
+    }
+
+    // Write the report to stderr
+    pub fn eprint(&self) {
+        eprintln!("{}", self);
+    }
+}
+
+// `panic!` payloads are a `&str` or a `String`, but `panic_any` can throw
+// anything
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_owned()
+    }
+}
+
+// Like the default hook, as in
+//
+//     thread 'main' panicked at src/main.rs:12:9:
+//     AAAaaaaa!!!!
+//
+// followed by the backtrace if there is one
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "thread '{}' panicked",
+            self.thread.as_deref().unwrap_or("<unnamed>")
+        )?;
+        if let Some(location) = &self.location {
+            write!(f, " at {}", location)?;
+        }
+        write!(f, ":\n{}", self.message)?;
+        if let Some(backtrace) = &self.backtrace {
+            write!(f, "\nstack backtrace:\n{}", backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for PanicReport {}
+
+thread_local! {
+    // How many `run_guarded` calls this thread is in
+    static GUARDS: Cell<usize> = const { Cell::new(0) };
+    // What the hook saw of the last panic in a guarded call
+    static LAST_PANIC: RefCell<Option<PanicReport>> = const { RefCell::new(None) };
+}
+
+fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if GUARDS.with(Cell::get) > 0 {
+                let report = PanicReport::from_hook(info);
+                LAST_PANIC.with(|last| *last.borrow_mut() = Some(report));
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+// Run `f`, and return what it returns, or a report of how it panicked. The
+// report isn't printed, call `eprint` for that.
+pub fn run_guarded<T, F>(f: F) -> Result<T, PanicReport>
+where
+    F: FnOnce() -> T + UnwindSafe,
+{
+    install_hook();
+    // Forget any panic caught by a `catch_unwind` of the caller's own
+    LAST_PANIC.with(|last| last.borrow_mut().take());
+    GUARDS.with(|guards| guards.set(guards.get() + 1));
+    let result = panic::catch_unwind(f);
+    GUARDS.with(|guards| guards.set(guards.get() - 1));
+
+    result.map_err(|payload| {
+        // The hook can't have run if the panic came from `resume_unwind`
+        LAST_PANIC
+            .with(|last| last.borrow_mut().take())
+            .unwrap_or_else(|| PanicReport {
+                message: payload_message(payload.as_ref()),
+                location: None,
+                thread: thread::current().name().map(str::to_owned),
+                backtrace: None,
+            })
+    })
+}
+
+// Run `f`, and if it panics, print the report to stderr and exit the
+// process with `code`
+pub fn run_or_exit<T, F>(code: i32, f: F) -> T
+where
+    F: FnOnce() -> T + UnwindSafe,
+{
+    match run_guarded(f) {
+        Ok(value) => value,
+        Err(report) => {
+            report.eprint();
+            process::exit(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ok() {
+        assert_eq!(run_guarded(|| 6 * 7).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_report() {
+        let line = line!() + 1;
+        let report = run_guarded(|| panic!("bad record {}", 3)).unwrap_err();
+        assert_eq!(report.message, "bad record 3");
+        let location = report.location.as_ref().unwrap();
+        assert_eq!((location.file.as_str(), location.line), (file!(), line));
+        assert_eq!(
+            report.thread.as_deref(),
+            Some("boundary::tests::test_report")
+        );
+
+        let text = report.to_string();
+        assert!(text.starts_with(&format!(
+            "thread 'boundary::tests::test_report' panicked at {}:\nbad record 3",
+            location
+        )));
+    }
+
+    #[test]
+    fn test_threads_and_payloads() {
+        let report = thread::Builder::new()
+            .name("worker".to_owned())
+            .spawn(|| run_guarded(|| panic::panic_any(7)).unwrap_err())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(report.thread.as_deref(), Some("worker"));
+        assert_eq!(report.message, "Box<dyn Any>");
+
+        let report = run_guarded(|| panic::resume_unwind(Box::new("resumed"))).unwrap_err();
+        assert_eq!(report.message, "resumed");
+        assert_eq!(report.location, None);
+    }
+
+    #[test]
+    fn test_nested() {
+        let outer = run_guarded(|| {
+            let inner = run_guarded(|| panic!("inner"));
+            assert_eq!(inner.unwrap_err().message, "inner");
+            panic!("outer");
+        });
+        assert_eq!(outer.unwrap_err().message, "outer");
+    }
+}
@@ -0,0 +1,2 @@
+// A panic boundary, in a library so that other examples can use it too
+pub mod boundary;
@@ -1,13 +1,53 @@
-fn main() {
-    drink("water");
-    drink("lemonade");
+use std::io::{self, Write};
+use std::panic::AssertUnwindSafe;
+
+use panics::boundary::{run_guarded, run_or_exit};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
 }
 
-fn drink(beverage: &str) {
+fn run(out: &mut impl Write) -> io::Result<()> {
+    drink(out, "water")?;
+
+    // A panic inside `run_guarded` comes back as a report instead of ending
+    // the program, so the next drink is still served. `out` is only borrowed
+    // by the closure, so it's fine to use after a panic.
+    for beverage in ["lemonade", "tea"] {
+        match run_guarded(AssertUnwindSafe(|| drink(out, beverage))) {
+            Ok(written) => written?,
+            Err(report) => report.eprint(),
+        }
+    }
+
+    // Outside of a boundary, the panic ends the program as before, here with
+    // exit code 3 rather than 101
+    run_or_exit(3, AssertUnwindSafe(|| drink(out, "lemonade")))
+}
+
+fn drink(out: &mut impl Write, beverage: &str) -> io::Result<()> {
     // You shouldn't drink too much sugary beverages.
     if beverage == "lemonade" {
         panic!("AAAaaaaa!!!!");
     }
 
-    println!("Some refreshing {} is all I need.", beverage);
+    writeln!(out, "Some refreshing {} is all I need.", beverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::drink;
+
+    // `main` ends the process with `run_or_exit`, so the drinks are served
+    // here instead, the last one panicking
+    #[test]
+    fn test_output() {
+        let output = testing::output::capture(|out| {
+            for beverage in ["water", "tea", "lemonade"] {
+                drink(out, beverage)?;
+            }
+            Ok(())
+        });
+        testing::snapshot!("main").assert(&output);
+    }
 }
@@ -1,39 +1,72 @@
+use std::io::{self, Write};
 use std::num::ParseIntError;
 
-fn main() {
+mod number;
+
+use number::{parse_and_add, parse_and_div, parse_and_multiply, parse_and_sub, NumberError};
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let twenty = multiply1("10", "2");
-    println!("double is {}", twenty);
+    writeln!(out, "double is {}", twenty)?;
 
     // let tt = multiply1("t", "2"); // panics
-    // println!("double is {}", tt);
+    // writeln!(out, "double is {}", tt)?;
 
     // This still presents a reasonable answer.
     let twenty = multiply2("10", "2");
-    print(twenty);
+    print(out, twenty)?;
 
     // The following now provides a much more helpful error message.
     let tt = multiply2("t", "2");
-    print(tt);
+    print(out, tt)?;
 
     // This still presents a reasonable answer.
     let twenty = multiply3("10", "2");
-    print(twenty);
+    print(out, twenty)?;
 
     // The following now provides a much more helpful error message.
     let tt = multiply3("t", "2");
-    print(tt);
+    print(out, tt)?;
 
-    print(multiply4("10", "2"));
-    print(multiply4("t", "2"));
+    print(out, multiply4("10", "2"))?;
+    print(out, multiply4("t", "2"))?;
 
-    print(multiply5("10", "2"));
-    print(multiply5("t", "2"));
+    print(out, multiply5("10", "2"))?;
+    print(out, multiply5("t", "2"))?;
 
-    print(multiply6("10", "2"));
-    print(multiply6("t", "2"));
+    print(out, multiply6("10", "2"))?;
+    print(out, multiply6("t", "2"))?;
 
     // print7(multiply7("10", "2"));
     // print7(multiply7("t", "2"));
+
+    // All of the above are `i32` only, and `"100000" * "100000"` wraps
+    // around in release builds. The `number` module works for every
+    // primitive number type, and reports the overflow instead.
+    print_number(out, parse_and_multiply::<i32>("100000", "100000"))?;
+    print_number(out, parse_and_multiply::<i64>("100000", "100000"))?;
+    print_number(out, parse_and_add::<u8>("0xf0", "0b1111"))?;
+    print_number(out, parse_and_sub::<u32>("1_000", "-1"))?;
+    print_number(out, parse_and_div::<i16>("0o777", "0"))?;
+    print_number(out, parse_and_div::<f64>("1_000.5", "0.5"))?;
+    print_number(out, parse_and_multiply::<i32>("10", "0x1g"))?;
+    print_number(out, parse_and_multiply::<i8>("-0x", "2"))?;
+
+    Ok(())
+}
+
+fn print_number<T: std::fmt::Display>(
+    out: &mut impl Write,
+    result: Result<T, NumberError>,
+) -> io::Result<()> {
+    match result {
+        Ok(n) => writeln!(out, "n is {}", n),
+        Err(e) => writeln!(out, "Error: {}", e),
+    }
 }
 
 fn multiply1(first_number_str: &str, second_number_str: &str) -> i32 {
@@ -64,10 +97,10 @@ fn multiply2(first_number_str: &str, second_number_str: &str) -> Result<i32, Par
     }
 }
 
-fn print(result: Result<i32, ParseIntError>) {
+fn print(out: &mut impl Write, result: Result<i32, ParseIntError>) -> io::Result<()> {
     match result {
-        Ok(n) => println!("n is {}", n),
-        Err(e) => println!("Error: {}", e),
+        Ok(n) => writeln!(out, "n is {}", n),
+        Err(e) => writeln!(out, "Error: {}", e),
     }
 }
 
@@ -128,3 +161,6 @@ fn multiply6(first_number_str: &str, second_number_str: &str) -> Result<i32, Par
 //         Err(e) => println!("Error: {}", e),
 //     }
 // }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
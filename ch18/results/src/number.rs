@@ -0,0 +1,394 @@
+// Parsing numbers of any primitive type, and doing arithmetic on them that
+// reports overflow instead of wrapping around or panicking.
+//
+// Integers may have a `0x`, `0o` or `0b` prefix after the sign, and, as in
+// Rust source, `_` between digits: `-0x_ff`, `1_000_000`. Floats are always
+// decimal, and may also have `_` separators: `6.022_14e23`.
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumberError {
+    // No digits at all, e.g. `""`, `"-"` or `"0x"`. The offset is where the
+    // digits should have started.
+    Empty { input: String, offset: usize },
+    // A byte that can't be there, e.g. the `9` in `"0o19"`
+    InvalidDigit { input: String, offset: usize },
+    // A valid number that doesn't fit the type. The offset is the digit at
+    // which it stopped fitting, or the start of a float.
+    OutOfRange { input: String, offset: usize },
+    // The numbers parsed, but the result of the arithmetic doesn't fit
+    Overflow { expression: String },
+    DivisionByZero { expression: String },
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumberError::Empty { input, offset } => {
+                write!(f, "no digits at byte {} of {:?}", offset, input)
+            }
+            NumberError::InvalidDigit { input, offset } => {
+                write!(f, "invalid digit at byte {} of {:?}", offset, input)
+            }
+            NumberError::OutOfRange { input, offset } => {
+                write!(f, "{:?} is out of range from byte {}", input, offset)
+            }
+            NumberError::Overflow { expression } => write!(f, "{} overflows", expression),
+            NumberError::DivisionByZero { expression } => {
+                write!(f, "{} divides by zero", expression)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NumberError {}
+
+// The primitive number types, with checked arithmetic. For floats, a result
+// that is not finite counts as an overflow.
+pub trait Number: Copy + PartialEq + fmt::Display {
+    const ZERO: Self;
+
+    fn parse_number(s: &str) -> Result<Self, NumberError>;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+}
+
+// Split off the sign, returning whether it is negative and where the rest
+// starts
+fn sign(s: &str) -> (bool, usize) {
+    match s.as_bytes().first() {
+        Some(b'-') => (true, 1),
+        Some(b'+') => (false, 1),
+        _ => (false, 0),
+    }
+}
+
+fn invalid(s: &str, offset: usize) -> NumberError {
+    NumberError::InvalidDigit {
+        input: s.to_owned(),
+        offset,
+    }
+}
+
+fn empty(s: &str, offset: usize) -> NumberError {
+    NumberError::Empty {
+        input: s.to_owned(),
+        offset,
+    }
+}
+
+fn out_of_range(s: &str, offset: usize) -> NumberError {
+    NumberError::OutOfRange {
+        input: s.to_owned(),
+        offset,
+    }
+}
+
+// What the integer types need to be parsed digit by digit
+trait Integer: Number {
+    fn from_digit(digit: u32) -> Self;
+}
+
+fn parse_integer<T: Integer>(s: &str) -> Result<T, NumberError> {
+    let (negative, mut start) = sign(s);
+    let radix = match s.get(start..start + 2) {
+        Some("0x") | Some("0X") => 16,
+        Some("0o") | Some("0O") => 8,
+        Some("0b") | Some("0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        start += 2;
+    }
+
+    let radix_t = T::from_digit(radix);
+    let mut value = T::ZERO;
+    let mut digits = 0;
+    for (offset, c) in s[start..].char_indices() {
+        let offset = start + offset;
+        // `_` may only follow a digit or another `_`, except right after a
+        // prefix, as in `0x_ff`
+        if c == '_' && (digits > 0 || radix != 10) {
+            continue;
+        }
+        let digit = c.to_digit(radix).ok_or_else(|| invalid(s, offset))?;
+        // Accumulate negative numbers as negative, so that `MIN` fits
+        value = value
+            .checked_mul(radix_t)
+            .and_then(|value| {
+                if negative {
+                    value.checked_sub(T::from_digit(digit))
+                } else {
+                    value.checked_add(T::from_digit(digit))
+                }
+            })
+            .ok_or_else(|| out_of_range(s, offset))?;
+        digits += 1;
+    }
+    if digits == 0 {
+        return Err(empty(s, s.len().min(start)));
+    }
+    Ok(value)
+}
+
+// Check `s` against `[+-]digits[.digits][(e|E)[+-]digits]`, with `_` after
+// any digit, and parse it without the `_`s
+fn parse_float<T: FromStr + Number>(s: &str, is_finite: fn(T) -> bool) -> Result<T, NumberError> {
+    let (_, start) = sign(s);
+    let bytes = s.as_bytes();
+    let mut clean = String::with_capacity(s.len());
+    clean.push_str(&s[..start]);
+
+    // How many digits the mantissa has, before and after the `.`
+    let mut i = start;
+    let mut mantissa_digits = 0;
+    let digits = |i: &mut usize, clean: &mut String| {
+        let mut count = 0;
+        while *i < bytes.len() && (bytes[*i].is_ascii_digit() || (bytes[*i] == b'_' && count > 0)) {
+            if bytes[*i] != b'_' {
+                clean.push(bytes[*i] as char);
+                count += 1;
+            }
+            *i += 1;
+        }
+        count
+    };
+    mantissa_digits += digits(&mut i, &mut clean);
+    if i < bytes.len() && bytes[i] == b'.' {
+        clean.push('.');
+        i += 1;
+        mantissa_digits += digits(&mut i, &mut clean);
+    }
+    if mantissa_digits == 0 {
+        return Err(if i == bytes.len() {
+            empty(s, start)
+        } else {
+            invalid(s, i)
+        });
+    }
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        clean.push('e');
+        i += 1;
+        if i < bytes.len() && (bytes[i] == b'+' || bytes[i] == b'-') {
+            clean.push(bytes[i] as char);
+            i += 1;
+        }
+        if digits(&mut i, &mut clean) == 0 {
+            return Err(if i == bytes.len() {
+                empty(s, i)
+            } else {
+                invalid(s, i)
+            });
+        }
+    }
+    if i < bytes.len() {
+        return Err(invalid(s, i));
+    }
+
+    // Only a valid float is left, so this can't fail
+    let value = clean.parse::<T>().map_err(|_| invalid(s, start))?;
+    if is_finite(value) {
+        Ok(value)
+    } else {
+        Err(out_of_range(s, start))
+    }
+}
+
+macro_rules! impl_integer {
+    ($($t:ty)*) => {$(
+        impl Number for $t {
+            const ZERO: Self = 0;
+
+            fn parse_number(s: &str) -> Result<Self, NumberError> {
+                parse_integer(s)
+            }
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_div(self, rhs)
+            }
+        }
+
+        impl Integer for $t {
+            fn from_digit(digit: u32) -> Self {
+                // Digits are below 16, which every integer type holds
+                digit as $t
+            }
+        }
+    )*};
+}
+
+impl_integer!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+macro_rules! impl_float {
+    ($($t:ty)*) => {$(
+        impl Number for $t {
+            const ZERO: Self = 0.0;
+
+            fn parse_number(s: &str) -> Result<Self, NumberError> {
+                parse_float(s, <$t>::is_finite)
+            }
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                Some(self + rhs).filter(|x| x.is_finite())
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                Some(self - rhs).filter(|x| x.is_finite())
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                Some(self * rhs).filter(|x| x.is_finite())
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                Some(self / rhs).filter(|x| x.is_finite())
+            }
+        }
+    )*};
+}
+
+impl_float!(f32 f64);
+
+pub fn parse_number<T: Number>(s: &str) -> Result<T, NumberError> {
+    T::parse_number(s)
+}
+
+fn parse_and<T: Number>(
+    first: &str,
+    second: &str,
+    symbol: &str,
+    op: fn(T, T) -> Option<T>,
+) -> Result<T, NumberError> {
+    let first: T = parse_number(first)?;
+    let second: T = parse_number(second)?;
+    let expression = || format!("{} {} {}", first, symbol, second);
+    if symbol == "/" && second == T::ZERO {
+        return Err(NumberError::DivisionByZero {
+            expression: expression(),
+        });
+    }
+    op(first, second).ok_or_else(|| NumberError::Overflow {
+        expression: expression(),
+    })
+}
+
+pub fn parse_and_add<T: Number>(first: &str, second: &str) -> Result<T, NumberError> {
+    parse_and(first, second, "+", T::checked_add)
+}
+
+pub fn parse_and_sub<T: Number>(first: &str, second: &str) -> Result<T, NumberError> {
+    parse_and(first, second, "-", T::checked_sub)
+}
+
+pub fn parse_and_multiply<T: Number>(first: &str, second: &str) -> Result<T, NumberError> {
+    parse_and(first, second, "*", T::checked_mul)
+}
+
+pub fn parse_and_div<T: Number>(first: &str, second: &str) -> Result<T, NumberError> {
+    parse_and(first, second, "/", T::checked_div)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offset<T: Number + fmt::Debug>(s: &str) -> (&'static str, usize) {
+        match parse_number::<T>(s).unwrap_err() {
+            NumberError::Empty { offset, .. } => ("empty", offset),
+            NumberError::InvalidDigit { offset, .. } => ("invalid", offset),
+            NumberError::OutOfRange { offset, .. } => ("range", offset),
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(parse_number::<i32>("-42"), Ok(-42));
+        assert_eq!(parse_number::<u8>("+0xff"), Ok(255));
+        assert_eq!(parse_number::<i16>("-0o17"), Ok(-15));
+        assert_eq!(parse_number::<u64>("0b1010_1010"), Ok(170));
+        assert_eq!(parse_number::<usize>("1_000_000"), Ok(1_000_000));
+        assert_eq!(
+            parse_number::<i128>("0x_7fff_ffff_ffff_ffff_ffff_ffff_ffff_ffff"),
+            Ok(i128::MAX)
+        );
+        assert_eq!(parse_number::<i8>("-128"), Ok(i8::MIN));
+        assert_eq!(parse_number::<u32>("-0"), Ok(0));
+
+        assert_eq!(offset::<i32>(""), ("empty", 0));
+        assert_eq!(offset::<i32>("-"), ("empty", 1));
+        assert_eq!(offset::<i32>("0x"), ("empty", 2));
+        assert_eq!(offset::<i32>("12x4"), ("invalid", 2));
+        assert_eq!(offset::<i32>("0o19"), ("invalid", 3));
+        assert_eq!(offset::<i32>("_1"), ("invalid", 0));
+        assert_eq!(offset::<i32>(" 1"), ("invalid", 0));
+        assert_eq!(offset::<i8>("128"), ("range", 2));
+        assert_eq!(offset::<u8>("1000"), ("range", 3));
+        assert_eq!(offset::<u32>("-5"), ("range", 1));
+    }
+
+    #[test]
+    fn test_floats() {
+        assert_eq!(parse_number::<f64>("-1.5e3"), Ok(-1500.0));
+        assert_eq!(parse_number::<f64>("6.022_14e2_3"), Ok(6.02214e23));
+        assert_eq!(parse_number::<f32>(".5"), Ok(0.5));
+        assert_eq!(parse_number::<f32>("5."), Ok(5.0));
+
+        assert_eq!(offset::<f64>(""), ("empty", 0));
+        assert_eq!(offset::<f64>("1e"), ("empty", 2));
+        assert_eq!(offset::<f64>("1.2.3"), ("invalid", 3));
+        assert_eq!(offset::<f64>("0x10"), ("invalid", 1));
+        assert_eq!(offset::<f64>("inf"), ("invalid", 0));
+        assert_eq!(offset::<f64>("-_1"), ("invalid", 1));
+        assert_eq!(offset::<f32>("-1e39"), ("range", 1));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(
+            parse_and_multiply::<i64>("100000", "100000"),
+            Ok(10_000_000_000)
+        );
+        assert_eq!(
+            parse_and_multiply::<i32>("100000", "100000"),
+            Err(NumberError::Overflow {
+                expression: "100000 * 100000".to_owned()
+            })
+        );
+        assert_eq!(parse_and_add::<u8>("0xf0", "0x0f"), Ok(255));
+        assert!(matches!(
+            parse_and_sub::<u8>("1", "2"),
+            Err(NumberError::Overflow { .. })
+        ));
+        assert!(matches!(
+            parse_and_div::<i8>("-128", "-1"),
+            Err(NumberError::Overflow { .. })
+        ));
+        assert_eq!(
+            parse_and_div::<i32>("1", "0").unwrap_err().to_string(),
+            "1 / 0 divides by zero"
+        );
+        assert!(matches!(
+            parse_and_div::<f64>("1", "0"),
+            Err(NumberError::DivisionByZero { .. })
+        ));
+        assert!(matches!(
+            parse_and_multiply::<f32>("1e30", "1e30"),
+            Err(NumberError::Overflow { .. })
+        ));
+        assert_eq!(parse_and_div::<f64>("1", "4"), Ok(0.25));
+        assert_eq!(
+            parse_and_multiply::<i32>("10", "t")
+                .unwrap_err()
+                .to_string(),
+            "invalid digit at byte 0 of \"t\""
+        );
+    }
+}
@@ -0,0 +1,538 @@
+// Floating point and integer operations that return an error instead of
+// `NaN`, an infinity or a wrapped-around integer. Every error says which
+// operation failed, with its operands, and why.
+//
+// No function here returns `NaN` or an infinity. When an infinity is the
+// answer the caller wants, as for `1 / 0` in IEEE 754, they can ask for it
+// with `Error::infinity`.
+use std::error;
+use std::f64::consts::PI;
+use std::fmt;
+
+// Mathematical "errors" we want to catch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    DivisionByZero,
+    NonPositiveLogarithm,
+    NegativeSquareRoot,
+    // A logarithm base that is not positive, or is 1
+    InvalidBase,
+    // A negative number to a fractional power
+    NegativeBase,
+    ZeroToNegativePower,
+    // Outside of [-1, 1], for `asin` and `acos`
+    OutOfDomain,
+    // Too close to a pole to tell the sign of the result, as for `tan(PI / 2)`
+    Pole,
+    // An operand that is `NaN` or infinite
+    NotFinite,
+    // The result is too large for the type
+    Overflow,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            MathError::DivisionByZero => "division by zero",
+            MathError::NonPositiveLogarithm => "logarithm of non-positive number",
+            MathError::NegativeSquareRoot => "square root of negative number",
+            MathError::InvalidBase => "logarithm base must be positive and not 1",
+            MathError::NegativeBase => "negative number to a fractional power",
+            MathError::ZeroToNegativePower => "zero to a negative power",
+            MathError::OutOfDomain => "operand outside of [-1, 1]",
+            MathError::Pole => "too close to a pole",
+            MathError::NotFinite => "operand is not a finite number",
+            MathError::Overflow => "result is too large",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+// An integer operand. No single type holds both `i128::MIN` and
+// `u128::MAX`, so signed and unsigned operands are kept apart.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Int {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+impl fmt::Display for Int {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Int::Signed(n) => write!(f, "{}", n),
+            Int::Unsigned(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+// Just the number, as in `IntMul(255, 2)`
+impl fmt::Debug for Int {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// The operation that failed, with its operands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Div(f64, f64),
+    Sqrt(f64),
+    Ln(f64),
+    Log { x: f64, base: f64 },
+    Pow(f64, f64),
+    Asin(f64),
+    Acos(f64),
+    Tan(f64),
+    Gamma(f64),
+    Factorial(u64),
+    IntAdd(Int, Int),
+    IntSub(Int, Int),
+    IntMul(Int, Int),
+    IntDiv(Int, Int),
+    IntRem(Int, Int),
+    IntNeg(Int),
+    IntPow(Int, u32),
+}
+
+impl Op {
+    // What IEEE 754 arithmetic gives for a floating point operation, `NaN`
+    // and infinities included
+    fn ieee(self) -> Option<f64> {
+        match self {
+            Op::Div(x, y) => Some(x / y),
+            Op::Sqrt(x) => Some(x.sqrt()),
+            Op::Ln(x) => Some(x.ln()),
+            Op::Log { x, base } => Some(x.ln() / base.ln()),
+            Op::Pow(x, y) => Some(x.powf(y)),
+            Op::Asin(x) => Some(x.asin()),
+            Op::Acos(x) => Some(x.acos()),
+            Op::Tan(x) => Some(x.tan()),
+            Op::Gamma(x) if x <= 0.0 && x == x.floor() => Some(f64::INFINITY.copysign(x)),
+            Op::Gamma(x) if x > MAX_GAMMA => Some(f64::INFINITY),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Op::Div(x, y) => write!(f, "{} / {}", x, y),
+            Op::Sqrt(x) => write!(f, "sqrt({})", x),
+            Op::Ln(x) => write!(f, "ln({})", x),
+            Op::Log { x, base } => write!(f, "log_{}({})", base, x),
+            Op::Pow(x, y) => write!(f, "pow({}, {})", x, y),
+            Op::Asin(x) => write!(f, "asin({})", x),
+            Op::Acos(x) => write!(f, "acos({})", x),
+            Op::Tan(x) => write!(f, "tan({})", x),
+            Op::Gamma(x) => write!(f, "gamma({})", x),
+            Op::Factorial(n) => write!(f, "{}!", n),
+            Op::IntAdd(a, b) => write!(f, "{} + {}", a, b),
+            Op::IntSub(a, b) => write!(f, "{} - {}", a, b),
+            Op::IntMul(a, b) => write!(f, "{} * {}", a, b),
+            Op::IntDiv(a, b) => write!(f, "{} / {}", a, b),
+            Op::IntRem(a, b) => write!(f, "{} % {}", a, b),
+            Op::IntNeg(a) => write!(f, "-({})", a),
+            Op::IntPow(a, b) => write!(f, "{}^{}", a, b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Error {
+    pub op: Op,
+    pub kind: MathError,
+}
+
+impl Error {
+    // The infinity IEEE 754 arithmetic gives for the failed operation, if
+    // it gives one, e.g. `Some(-inf)` for `-1 / 0`, but `None` for `0 / 0`
+    pub fn infinity(&self) -> Option<f64> {
+        self.op.ieee().filter(|x| x.is_infinite())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.op, self.kind)
+    }
+}
+
+impl error::Error for Error {}
+
+pub type MathResult<T = f64> = Result<T, Error>;
+
+fn fail<T>(op: Op, kind: MathError) -> MathResult<T> {
+    Err(Error { op, kind })
+}
+
+// Reject `NaN` and infinite operands, and infinite results of finite ones
+fn finite(op: Op, operands: &[f64], result: impl FnOnce() -> f64) -> MathResult {
+    if !operands.iter().all(|x| x.is_finite()) {
+        return fail(op, MathError::NotFinite);
+    }
+    let result = result();
+    if result.is_finite() {
+        Ok(result)
+    } else {
+        fail(op, MathError::Overflow)
+    }
+}
+
+pub fn div(x: f64, y: f64) -> MathResult {
+    let op = Op::Div(x, y);
+    if y == 0.0 {
+        // This operation would `fail`, instead let's return the reason of
+        // the failure wrapped in `Err`
+        fail(op, MathError::DivisionByZero)
+    } else {
+        // This operation is valid, return the result wrapped in `Ok`
+        finite(op, &[x, y], || x / y)
+    }
+}
+
+pub fn sqrt(x: f64) -> MathResult {
+    let op = Op::Sqrt(x);
+    if x < 0.0 {
+        fail(op, MathError::NegativeSquareRoot)
+    } else {
+        finite(op, &[x], || x.sqrt())
+    }
+}
+
+pub fn ln(x: f64) -> MathResult {
+    let op = Op::Ln(x);
+    if x <= 0.0 {
+        fail(op, MathError::NonPositiveLogarithm)
+    } else {
+        finite(op, &[x], || x.ln())
+    }
+}
+
+pub fn log(x: f64, base: f64) -> MathResult {
+    let op = Op::Log { x, base };
+    if x <= 0.0 {
+        fail(op, MathError::NonPositiveLogarithm)
+    } else if base <= 0.0 || base == 1.0 {
+        fail(op, MathError::InvalidBase)
+    } else {
+        finite(op, &[x, base], || x.ln() / base.ln())
+    }
+}
+
+pub fn pow(x: f64, y: f64) -> MathResult {
+    let op = Op::Pow(x, y);
+    if x < 0.0 && y.is_finite() && y != y.trunc() {
+        fail(op, MathError::NegativeBase)
+    } else if x == 0.0 && y < 0.0 {
+        fail(op, MathError::ZeroToNegativePower)
+    } else {
+        finite(op, &[x, y], || x.powf(y))
+    }
+}
+
+pub fn asin(x: f64) -> MathResult {
+    let op = Op::Asin(x);
+    if x.abs() > 1.0 {
+        fail(op, MathError::OutOfDomain)
+    } else {
+        finite(op, &[x], || x.asin())
+    }
+}
+
+pub fn acos(x: f64) -> MathResult {
+    let op = Op::Acos(x);
+    if x.abs() > 1.0 {
+        fail(op, MathError::OutOfDomain)
+    } else {
+        finite(op, &[x], || x.acos())
+    }
+}
+
+// The poles of `tan` are at odd multiples of PI / 2, which no `f64` is
+// exactly, so `tan` itself never overflows. Near a pole, though, the result
+// is the reciprocal of the distance to it, and once that distance is below
+// the rounding error of `x` even its sign is noise.
+pub fn tan(x: f64) -> MathResult {
+    let op = Op::Tan(x);
+    let result = finite(op, &[x], || x.tan())?;
+    if 1.0 / result.abs() <= 4.0 * f64::EPSILON * x.abs().max(1.0) {
+        fail(op, MathError::Pole)
+    } else {
+        Ok(result)
+    }
+}
+
+// The largest argument for which gamma fits in an `f64`
+const MAX_GAMMA: f64 = 171.624_376_956_302_7;
+
+// The Lanczos approximation with g = 7 and 9 coefficients, good to about 15
+// significant digits, and 13 close to `MAX_GAMMA`
+const LANCZOS_G: f64 = 7.0;
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+pub fn gamma(x: f64) -> MathResult {
+    let op = Op::Gamma(x);
+    if !x.is_finite() {
+        fail(op, MathError::NotFinite)
+    } else if x <= 0.0 && x == x.floor() {
+        fail(op, MathError::Pole)
+    } else if x > MAX_GAMMA {
+        fail(op, MathError::Overflow)
+    } else if x.fract() == 0.0 && x <= 21.0 {
+        // Small integers are exact, from the factorial
+        Ok(factorial(x as u64 - 1)? as f64)
+    } else {
+        finite(op, &[x], || lanczos(x))
+    }
+}
+
+fn lanczos(x: f64) -> f64 {
+    if x < 0.5 {
+        // The reflection formula: gamma(x) gamma(1 - x) = PI / sin(PI x)
+        PI / ((PI * x).sin() * lanczos(1.0 - x))
+    } else {
+        let x = x - 1.0;
+        let t = x + LANCZOS_G + 0.5;
+        let sum = LANCZOS[1..]
+            .iter()
+            .enumerate()
+            .fold(LANCZOS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.0));
+        // Split t^(x + 0.5) in two so that it doesn't overflow before e^-t
+        // brings it back down
+        let half = t.powf((x + 0.5) / 2.0);
+        (2.0 * PI).sqrt() * half * (half * (-t).exp()) * sum
+    }
+}
+
+// Exact, up to 20! which is the largest that fits in a `u64`
+pub fn factorial(n: u64) -> MathResult<u64> {
+    (2..=n)
+        .try_fold(1u64, |product, k| product.checked_mul(k))
+        .map_or(fail(Op::Factorial(n), MathError::Overflow), Ok)
+}
+
+// Integer types with the standard `checked_*` methods
+pub trait CheckedInt: Copy + PartialEq {
+    const ZERO: Self;
+    fn widen(self) -> Int;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn checked_pow(self, exp: u32) -> Option<Self>;
+}
+
+macro_rules! impl_checked_int {
+    ($variant:ident, $wide:ty: $($t:ty)*) => {$(
+        impl CheckedInt for $t {
+            const ZERO: Self = 0;
+            fn widen(self) -> Int {
+                Int::$variant(self as $wide)
+            }
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_div(self, rhs)
+            }
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_rem(self, rhs)
+            }
+            fn checked_neg(self) -> Option<Self> {
+                <$t>::checked_neg(self)
+            }
+            fn checked_pow(self, exp: u32) -> Option<Self> {
+                <$t>::checked_pow(self, exp)
+            }
+        }
+    )*};
+}
+
+impl_checked_int!(Signed, i128: i8 i16 i32 i64 i128 isize);
+impl_checked_int!(Unsigned, u128: u8 u16 u32 u64 u128 usize);
+
+// The standard `checked_*` methods return `None` for any failure. These say
+// which operation failed, and whether it was a division by zero or an
+// overflow.
+pub fn checked_add<T: CheckedInt>(a: T, b: T) -> MathResult<T> {
+    a.checked_add(b).map_or(
+        fail(Op::IntAdd(a.widen(), b.widen()), MathError::Overflow),
+        Ok,
+    )
+}
+
+pub fn checked_sub<T: CheckedInt>(a: T, b: T) -> MathResult<T> {
+    a.checked_sub(b).map_or(
+        fail(Op::IntSub(a.widen(), b.widen()), MathError::Overflow),
+        Ok,
+    )
+}
+
+pub fn checked_mul<T: CheckedInt>(a: T, b: T) -> MathResult<T> {
+    a.checked_mul(b).map_or(
+        fail(Op::IntMul(a.widen(), b.widen()), MathError::Overflow),
+        Ok,
+    )
+}
+
+pub fn checked_div<T: CheckedInt>(a: T, b: T) -> MathResult<T> {
+    let op = Op::IntDiv(a.widen(), b.widen());
+    if b == T::ZERO {
+        return fail(op, MathError::DivisionByZero);
+    }
+    // The only other failure is `MIN / -1`
+    a.checked_div(b).map_or(fail(op, MathError::Overflow), Ok)
+}
+
+pub fn checked_rem<T: CheckedInt>(a: T, b: T) -> MathResult<T> {
+    let op = Op::IntRem(a.widen(), b.widen());
+    if b == T::ZERO {
+        return fail(op, MathError::DivisionByZero);
+    }
+    a.checked_rem(b).map_or(fail(op, MathError::Overflow), Ok)
+}
+
+pub fn checked_neg<T: CheckedInt>(a: T) -> MathResult<T> {
+    a.checked_neg()
+        .map_or(fail(Op::IntNeg(a.widen()), MathError::Overflow), Ok)
+}
+
+pub fn checked_pow<T: CheckedInt>(a: T, exp: u32) -> MathResult<T> {
+    a.checked_pow(exp)
+        .map_or(fail(Op::IntPow(a.widen(), exp), MathError::Overflow), Ok)
+}
+
+// `op(x, y)` === `sqrt(ln(x / y))`. The error names the step that failed,
+// with the operand it got from the step before.
+pub fn op(x: f64, y: f64) -> MathResult {
+    // if `div` "fails", then `DivisionByZero` will be `return`ed
+    let ratio = div(x, y)?;
+
+    // if `ln` "fails", then `NonPositiveLogarithm` will be `return`ed
+    let ln = ln(ratio)?;
+
+    sqrt(ln)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind<T>(result: MathResult<T>) -> MathError {
+        match result {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => e.kind,
+        }
+    }
+
+    #[test]
+    fn test_domains() {
+        assert_eq!(kind(div(1.0, 0.0)), MathError::DivisionByZero);
+        assert_eq!(kind(div(f64::MAX, 0.5)), MathError::Overflow);
+        assert_eq!(kind(sqrt(-1.0)), MathError::NegativeSquareRoot);
+        assert_eq!(kind(ln(0.0)), MathError::NonPositiveLogarithm);
+        assert_eq!(kind(log(8.0, 1.0)), MathError::InvalidBase);
+        assert_eq!(kind(log(8.0, -2.0)), MathError::InvalidBase);
+        assert_eq!(kind(pow(-8.0, 1.0 / 3.0)), MathError::NegativeBase);
+        assert_eq!(kind(pow(0.0, -1.0)), MathError::ZeroToNegativePower);
+        assert_eq!(kind(pow(10.0, 400.0)), MathError::Overflow);
+        assert_eq!(kind(asin(1.5)), MathError::OutOfDomain);
+        assert_eq!(kind(acos(-1.0001)), MathError::OutOfDomain);
+        assert_eq!(kind(tan(PI / 2.0)), MathError::Pole);
+        assert_eq!(kind(tan(-3.0 * PI / 2.0)), MathError::Pole);
+        assert_eq!(kind(gamma(-2.0)), MathError::Pole);
+        assert_eq!(kind(gamma(172.0)), MathError::Overflow);
+        assert_eq!(kind(sqrt(f64::NAN)), MathError::NotFinite);
+        assert_eq!(kind(ln(f64::INFINITY)), MathError::NotFinite);
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(log(8.0, 2.0), Ok(3.0));
+        assert_eq!(pow(-2.0, 3.0), Ok(-8.0));
+        assert_eq!(asin(1.0), Ok(PI / 2.0));
+        assert!((tan(PI / 4.0).unwrap() - 1.0).abs() < 1e-15);
+        assert!(tan(PI / 2.0 - 1e-7).unwrap() > 1e6);
+        assert_eq!(factorial(0), Ok(1));
+        assert_eq!(factorial(20), Ok(2_432_902_008_176_640_000));
+        assert_eq!(kind(factorial(21)), MathError::Overflow);
+        assert_eq!(gamma(5.0), Ok(24.0));
+
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-12 * b.abs();
+        assert!(close(gamma(0.5).unwrap(), PI.sqrt()));
+        assert!(close(gamma(-0.5).unwrap(), -2.0 * PI.sqrt()));
+        assert!(close(gamma(4.5).unwrap(), 11.631_728_396_567_446));
+        assert!(close(gamma(171.5).unwrap(), 9.483_367_566_824_801e307));
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(checked_add(2u8, 3), Ok(5));
+        assert_eq!(kind(checked_add(i32::MAX, 1)), MathError::Overflow);
+        assert_eq!(kind(checked_sub(0u32, 1)), MathError::Overflow);
+        assert_eq!(kind(checked_mul(1i64 << 32, 1 << 32)), MathError::Overflow);
+        assert_eq!(kind(checked_div(1, 0)), MathError::DivisionByZero);
+        assert_eq!(kind(checked_div(i8::MIN, -1)), MathError::Overflow);
+        assert_eq!(kind(checked_rem(5usize, 0)), MathError::DivisionByZero);
+        assert_eq!(kind(checked_neg(i16::MIN)), MathError::Overflow);
+        assert_eq!(checked_pow(3u64, 4), Ok(81));
+        assert_eq!(
+            checked_add(i32::MAX, 1).unwrap_err().to_string(),
+            "2147483647 + 1: result is too large"
+        );
+
+        // The widest types keep their operands whole
+        assert_eq!(
+            checked_sub(i128::MIN, 1).unwrap_err().op,
+            Op::IntSub(Int::Signed(i128::MIN), Int::Signed(1))
+        );
+        assert_eq!(
+            checked_add(u128::MAX, 1).unwrap_err().to_string(),
+            "340282366920938463463374607431768211455 + 1: result is too large"
+        );
+        assert_eq!(checked_mul(u128::MAX, 1), Ok(u128::MAX));
+        assert_eq!(kind(checked_rem(1i128, 0)), MathError::DivisionByZero);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            op(1.0, 10.0).unwrap_err().to_string(),
+            "sqrt(-2.3025850929940455): square root of negative number"
+        );
+        assert_eq!(op(0.0, 1.0).unwrap_err().op, Op::Ln(0.0));
+        assert_eq!(op(1.0, 0.0).unwrap_err().op, Op::Div(1.0, 0.0));
+        assert!((op(10.0, 1.0).unwrap() - 10f64.ln().sqrt()).abs() < 1e-15);
+
+        // Infinities only when asked for
+        assert_eq!(
+            div(-1.0, 0.0).unwrap_err().infinity(),
+            Some(f64::NEG_INFINITY)
+        );
+        assert_eq!(div(0.0, 0.0).unwrap_err().infinity(), None);
+        assert_eq!(gamma(-3.0).unwrap_err().infinity(), Some(f64::NEG_INFINITY));
+        assert_eq!(
+            pow(0.0, -2.0).or_else(|e| e.infinity().ok_or(e)),
+            Ok(f64::INFINITY)
+        );
+    }
+}
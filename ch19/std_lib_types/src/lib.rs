@@ -0,0 +1,2 @@
+// Checked math, in a library so that other examples can use it too
+pub mod checked;
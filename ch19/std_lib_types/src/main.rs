@@ -1,12 +1,19 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::{self, Write};
 use std::mem;
 use std::rc::Rc;
 use std::str;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
-fn main() {
+use std_lib_types::checked;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // (all the type annotations are superfluous)
     // Stack allocated variables
     let point: Point = origin();
@@ -27,76 +34,82 @@ fn main() {
     // Double indirection
     let box_in_a_box: Box<Box<Point>> = Box::new(boxed_origin());
 
-    println!(
+    writeln!(
+        out,
         "Point occupies {} bytes on the stack",
         mem::size_of_val(&point)
-    );
-    println!(
+    )?;
+    writeln!(
+        out,
         "Rectangle occupies {} bytes on the stack",
         mem::size_of_val(&rectangle)
-    );
+    )?;
 
     // box size == pointer size
-    println!(
+    writeln!(
+        out,
         "Boxed point occupies {} bytes on the stack",
         mem::size_of_val(&boxed_point)
-    );
-    println!(
+    )?;
+    writeln!(
+        out,
         "Boxed rectangle occupies {} bytes on the stack",
         mem::size_of_val(&boxed_rectangle)
-    );
-    println!(
+    )?;
+    writeln!(
+        out,
         "Boxed box occupies {} bytes on the stack",
         mem::size_of_val(&box_in_a_box)
-    );
+    )?;
 
     // Copy the data contained in `boxed_point` into `unboxed_point`
     let unboxed_point: Point = *boxed_point;
-    println!(
+    writeln!(
+        out,
         "Unboxed point occupies {} bytes on the stack",
         mem::size_of_val(&unboxed_point)
-    );
+    )?;
 
     // Iterators can be collected into vectors
     let collected_iterator: Vec<i32> = (0..10).collect();
-    println!("Collected (0..10) into: {:?}", collected_iterator);
+    writeln!(out, "Collected (0..10) into: {:?}", collected_iterator)?;
 
     // The `vec!` macro can be used to initialize a vector
     let mut xs = vec![1i32, 2, 3];
-    println!("Initial vector: {:?}", xs);
+    writeln!(out, "Initial vector: {:?}", xs)?;
 
     // Insert new element at the end of the vector
-    println!("Push 4 into the vector");
+    writeln!(out, "Push 4 into the vector")?;
     xs.push(4);
-    println!("Vector: {:?}", xs);
+    writeln!(out, "Vector: {:?}", xs)?;
 
     // Error! Immutable vectors can't grow
     // collected_iterator.push(0);
-    // FIXME ^ Comment out this line
+    // FIXME ^ Comment out this line (error[E0596])
 
     // The `len` method yields the number of elements currently stored in a vector
-    println!("Vector length: {}", xs.len());
+    writeln!(out, "Vector length: {}", xs.len())?;
 
     // Indexing is done using the square brackets (indexing starts at 0)
-    println!("Second element: {}", xs[1]);
+    writeln!(out, "Second element: {}", xs[1])?;
 
     // `pop` removes the last element from the vector and returns it
-    println!("Pop last element: {:?}", xs.pop());
+    writeln!(out, "Pop last element: {:?}", xs.pop())?;
 
     // Out of bounds indexing yields a panic
-    // println!("Fourth element: {}", xs[3]);
-    // FIXME ^ Comment out this line
+    // writeln!(out, "Fourth element: {}", xs[3])?;
+    // FIXME ^ Comment out this line (panics)
 
     // `Vector`s can be easily iterated over
-    println!("Contents of xs:");
+    writeln!(out, "Contents of xs:")?;
     for x in xs.iter() {
-        println!("> {}", x);
+        writeln!(out, "> {}", x)?;
     }
 
     // A `Vector` can also be iterated over while the iteration
     // count is enumerated in a separate variable (`i`)
     for (i, x) in xs.iter().enumerate() {
-        println!("In position {} we have value {}", i, x);
+        writeln!(out, "In position {} we have value {}", i, x)?;
     }
 
     // Thanks to `iter_mut`, mutable `Vector`s can also be iterated
@@ -104,17 +117,17 @@ fn main() {
     for x in xs.iter_mut() {
         *x *= 3;
     }
-    println!("Updated vector: {:?}", xs);
+    writeln!(out, "Updated vector: {:?}", xs)?;
 
     // (all the type annotations are superfluous)
     // A reference to a string allocated in read only memory
     let pangram: &'static str = "the quick brown fox jumps over the lazy dog";
-    println!("Pangram: {}", pangram);
+    writeln!(out, "Pangram: {}", pangram)?;
 
     // Iterate over words in reverse, no new string is allocated
-    println!("Words in reverse");
+    writeln!(out, "Words in reverse")?;
     for word in pangram.split_whitespace().rev() {
-        println!("> {}", word);
+        writeln!(out, "> {}", word)?;
     }
 
     // Copy chars into a vector, sort and remove duplicates
@@ -135,66 +148,71 @@ fn main() {
     // allocation is performed
     let chars_to_trim: &[char] = &[' ', ','];
     let trimmed_str: &str = string.trim_matches(chars_to_trim);
-    println!("Used characters: {}", trimmed_str);
+    writeln!(out, "Used characters: {}", trimmed_str)?;
 
     // Heap allocate a string
     let alice = String::from("I like dogs");
     // Allocate new memory and store the modified string there
     let bob: String = alice.replace("dog", "cat");
 
-    println!("Alice says: {}", alice);
-    println!("Bob says: {}", bob);
+    writeln!(out, "Alice says: {}", alice)?;
+    writeln!(out, "Bob says: {}", bob)?;
 
     // You can use escapes to write bytes by their hexadecimal values...
     let byte_escape = "I'm writing \x52\x75\x73\x74!";
-    println!("What are you doing\x3F (\\x3F means ?) {}", byte_escape);
+    writeln!(
+        out,
+        "What are you doing\x3F (\\x3F means ?) {}",
+        byte_escape
+    )?;
 
     // ...or Unicode code points.
     let unicode_codepoint = "\u{211D}";
     let character_name = "\"DOUBLE-STRUCK CAPITAL R\"";
 
-    println!(
+    writeln!(
+        out,
         "Unicode character {} (U+211D) is called {}",
         unicode_codepoint, character_name
-    );
+    )?;
 
     let long_string = "String literals
                         can span multiple lines.
                         The linebreak and indentation here ->\
                         <- can be escaped too!";
-    println!("{}", long_string);
+    writeln!(out, "{}", long_string)?;
 
     let raw_str = r"Escapes don't work here: \x3F \u{211D}";
-    println!("{}", raw_str);
+    writeln!(out, "{}", raw_str)?;
 
     // If you need quotes in a raw string, add a pair of #s
     let quotes = r#"And then I said: "There is no escape!""#;
-    println!("{}", quotes);
+    writeln!(out, "{}", quotes)?;
 
     // If you need "# in your string, just use more #s in the delimiter.
     // There is no limit for the number of #s you can use.
     let longer_delimiter = r###"A string with "# in it. And even "##!"###;
-    println!("{}", longer_delimiter);
+    writeln!(out, "{}", longer_delimiter)?;
 
     // Note that this is not actually a `&str`
     let bytestring: &[u8; 21] = b"this is a byte string";
 
     // Byte arrays don't have the `Display` trait, so printing them is a bit limited
-    println!("A byte string: {:?}", bytestring);
+    writeln!(out, "A byte string: {:?}", bytestring)?;
 
     // Byte strings can have byte escapes...
     let escaped = b"\x52\x75\x73\x74 as bytes";
     // ...but no unicode escapes
     // let escaped = b"\u{211D} is not allowed";
-    println!("Some escaped bytes: {:?}", escaped);
+    writeln!(out, "Some escaped bytes: {:?}", escaped)?;
 
     // Raw byte strings work just like raw strings
     let raw_bytestring = br"\u{211D} is not escaped here";
-    println!("{:?}", raw_bytestring);
+    writeln!(out, "{:?}", raw_bytestring)?;
 
     // Converting a byte array to `str` can fail
     if let Ok(my_str) = str::from_utf8(raw_bytestring) {
-        println!("And the same as text: '{}'", my_str);
+        writeln!(out, "And the same as text: '{}'", my_str)?;
     }
 
     let _quotes = br#"You can also use "fancier" formatting, \
@@ -205,12 +223,12 @@ fn main() {
 
     // But then they can't always be converted to `str`
     match str::from_utf8(shift_jis) {
-        Ok(my_str) => println!("Conversion successful: '{}'", my_str),
-        Err(e) => println!("Conversion failed: {:?}", e),
+        Ok(my_str) => writeln!(out, "Conversion successful: '{}'", my_str)?,
+        Err(e) => writeln!(out, "Conversion failed: {:?}", e)?,
     };
 
-    try_division(4, 2);
-    try_division(1, 0);
+    try_division(out, 4, 2)?;
+    try_division(out, 1, 0)?;
 
     // Binding `None` to a variable needs to be type annotated
     let none: Option<i32> = None;
@@ -219,19 +237,27 @@ fn main() {
     let optional_float = Some(0f32);
 
     // Unwrapping a `Some` variant will extract the value wrapped.
-    println!(
+    writeln!(
+        out,
         "{:?} unwraps to {:?}",
         optional_float,
         optional_float.unwrap()
-    );
+    )?;
 
     // Unwrapping a `None` variant will `panic!`
-    // println!("{:?} unwraps to {:?}", none, none.unwrap());
+    // writeln!(out, "{:?} unwraps to {:?}", none, none.unwrap())?;
 
     // Will this fail?
-    // println!("{}", op(1.0, 10.0));
+    // writeln!(out, "{}", op(1.0, 10.0))?;
 
-    // checked::op(1.0, 10.0);
+    // `checked::op` doesn't panic, it says which step failed
+    match checked::op(1.0, 10.0) {
+        Ok(value) => writeln!(out, "{}", value)?,
+        Err(why) => writeln!(out, "op(1.0, 10.0) failed: {}", why)?,
+    }
+    writeln!(out, "log_2(1024) = {:?}", checked::log(1024.0, 2.0))?;
+    writeln!(out, "5.5! = {:?}", checked::gamma(6.5))?;
+    writeln!(out, "{:?}", checked::checked_mul(u8::MAX, 2))?;
 
     // Heap allocated integer
     let _x = Box::new(0i32);
@@ -239,7 +265,7 @@ fn main() {
     // This operation will trigger a task failure
     // division(3, 0);
 
-    println!("This point won't be reached!");
+    writeln!(out, "This point won't be reached!")?;
 
     // `_x` should get destroyed at this point
 
@@ -252,8 +278,8 @@ fn main() {
 
     // Takes a reference and returns Option<&V>
     match contacts.get(&"Daniel") {
-        Some(&number) => println!("Calling Daniel: {}", call(number)),
-        _ => println!("Don't have Daniel's number."),
+        Some(&number) => writeln!(out, "Calling Daniel: {}", call(number))?,
+        _ => writeln!(out, "Don't have Daniel's number.")?,
     }
 
     // `HashMap::insert()` returns `None`
@@ -261,8 +287,8 @@ fn main() {
     contacts.insert("Daniel", "164-6743");
 
     match contacts.get(&"Ashley") {
-        Some(&number) => println!("Calling Ashley: {}", call(number)),
-        _ => println!("Don't have Ashley's number."),
+        Some(&number) => writeln!(out, "Calling Ashley: {}", call(number))?,
+        _ => writeln!(out, "Don't have Ashley's number.")?,
     }
 
     contacts.remove(&"Ashley");
@@ -270,7 +296,7 @@ fn main() {
     // `HashMap::iter()` returns an iterator that yields
     // (&'a key, &'a value) pairs in arbitrary order.
     for (contact, &number) in contacts.iter() {
-        println!("Calling {}: {}", contact, call(number));
+        writeln!(out, "Calling {}: {}", contact, call(number))?;
     }
 
     let mut accounts: Accounts = HashMap::new();
@@ -287,9 +313,9 @@ fn main() {
 
     accounts.insert(account, account_info);
 
-    try_logon(&accounts, "j.everyman", "psasword123");
+    try_logon(out, &accounts, "j.everyman", "psasword123")?;
 
-    try_logon(&accounts, "j.everyman", "password123");
+    try_logon(out, &accounts, "j.everyman", "password123")?;
 
     let mut a: HashSet<i32> = vec![1i32, 2, 3].into_iter().collect();
     let mut b: HashSet<i32> = vec![2i32, 3, 4].into_iter().collect();
@@ -300,82 +326,102 @@ fn main() {
     // `HashSet::insert()` returns false if
     // there was a value already present.
     // assert!(b.insert(4), "Value 4 is already in set B!");
-    // FIXME ^ Comment out this line
+    // FIXME ^ Comment out this line (panics)
 
     b.insert(5);
 
     // If a collection's element type implements `Debug`,
     // then the collection implements `Debug`.
     // It usually prints its elements in the format `[elem1, elem2, ...]`
-    println!("A: {:?}", a);
-    println!("B: {:?}", b);
+    writeln!(out, "A: {:?}", a)?;
+    writeln!(out, "B: {:?}", b)?;
 
     // Print [1, 2, 3, 4, 5] in arbitrary order
-    println!("Union: {:?}", a.union(&b).collect::<Vec<&i32>>());
+    writeln!(out, "Union: {:?}", a.union(&b).collect::<Vec<&i32>>())?;
 
     // This should print [1]
-    println!("Difference: {:?}", a.difference(&b).collect::<Vec<&i32>>());
+    writeln!(
+        out,
+        "Difference: {:?}",
+        a.difference(&b).collect::<Vec<&i32>>()
+    )?;
 
     // Print [2, 3, 4] in arbitrary order.
-    println!(
+    writeln!(
+        out,
         "Intersection: {:?}",
         a.intersection(&b).collect::<Vec<&i32>>()
-    );
+    )?;
 
     // Print [1, 5]
-    println!(
+    writeln!(
+        out,
         "Symmetric Difference: {:?}",
         a.symmetric_difference(&b).collect::<Vec<&i32>>()
-    );
+    )?;
 
     let rc_examples = "Rc examples".to_string();
     {
-        println!("--- rc_a is created ---");
+        writeln!(out, "--- rc_a is created ---")?;
 
         let rc_a: Rc<String> = Rc::new(rc_examples);
-        println!("Reference Count of rc_a: {}", Rc::strong_count(&rc_a));
+        writeln!(out, "Reference Count of rc_a: {}", Rc::strong_count(&rc_a))?;
 
         {
-            println!("--- rc_a is cloned to rc_b ---");
+            writeln!(out, "--- rc_a is cloned to rc_b ---")?;
 
             let rc_b: Rc<String> = Rc::clone(&rc_a);
-            println!("Reference Count of rc_b: {}", Rc::strong_count(&rc_b));
-            println!("Reference Count of rc_a: {}", Rc::strong_count(&rc_a));
+            writeln!(out, "Reference Count of rc_b: {}", Rc::strong_count(&rc_b))?;
+            writeln!(out, "Reference Count of rc_a: {}", Rc::strong_count(&rc_a))?;
 
             // Two `Rc`s are equal if their inner values are equal
-            println!("rc_a and rc_b are equal: {}", rc_a.eq(&rc_b));
+            writeln!(out, "rc_a and rc_b are equal: {}", rc_a.eq(&rc_b))?;
 
             // We can use methods of a value directly
-            println!("Length of the value inside rc_a: {}", rc_a.len());
-            println!("Value of rc_b: {}", rc_b);
+            writeln!(out, "Length of the value inside rc_a: {}", rc_a.len())?;
+            writeln!(out, "Value of rc_b: {}", rc_b)?;
 
-            println!("--- rc_b is dropped out of scope ---");
+            writeln!(out, "--- rc_b is dropped out of scope ---")?;
         }
 
-        println!("Reference Count of rc_a: {}", Rc::strong_count(&rc_a));
+        writeln!(out, "Reference Count of rc_a: {}", Rc::strong_count(&rc_a))?;
 
-        println!("--- rc_a is dropped out of scope ---");
+        writeln!(out, "--- rc_a is dropped out of scope ---")?;
     }
 
     // Error! `rc_examples` already moved into `rc_a`
     // And when `rc_a` is dropped, `rc_examples` is dropped together
-    // println!("rc_examples: {}", rc_examples);
-    // TODO ^ Try uncommenting this line
+    // writeln!(out, "rc_examples: {}", rc_examples)?;
+    // TODO ^ Try uncommenting this line (error[E0382])
 
     // This variable declaration is where its value is specified.
     let apple = Arc::new("the same apple");
 
+    // The threads may outlive `run`, so they can't borrow `out`. They print
+    // to `printed` instead, shared through an `Arc` as well, which is copied
+    // to `out` once they are done. Writing to a `Vec` never fails.
+    let printed = Arc::new(Mutex::new(Vec::new()));
+    let mut handles = Vec::new();
+
     for _ in 0..10 {
         // Here there is no value specification as it is a pointer to a reference
         // in the memory heap.
         let apple = Arc::clone(&apple);
+        let printed = Arc::clone(&printed);
 
-        thread::spawn(move || {
+        handles.push(thread::spawn(move || {
             // As Arc was used, threads can be spawned using the value allocated
             // in the Arc variable pointer's location.
-            println!("{:?}", apple);
-        });
+            writeln!(printed.lock().unwrap(), "{:?}", apple).unwrap();
+        }));
     }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    out.write_all(&printed.lock().unwrap())?;
+
+    Ok(())
 }
 
 #[allow(dead_code)]
@@ -414,76 +460,12 @@ fn checked_division(dividend: i32, divisor: i32) -> Option<i32> {
 }
 
 // This function handles a division that may not succeed
-fn try_division(dividend: i32, divisor: i32) {
+fn try_division(out: &mut impl Write, dividend: i32, divisor: i32) -> io::Result<()> {
     // `Option` values can be pattern matched, just like other enums
     match checked_division(dividend, divisor) {
-        None => println!("{} / {} failed!", dividend, divisor),
+        None => writeln!(out, "{} / {} failed!", dividend, divisor),
         Some(quotient) => {
-            println!("{} / {} = {}", dividend, divisor, quotient)
-        }
-    }
-}
-
-mod checked {
-    // Mathematical "errors" we want to catch
-    #[derive(Debug)]
-    pub enum MathError {
-        DivisionByZero,
-        NonPositiveLogarithm,
-        NegativeSquareRoot,
-    }
-
-    pub type MathResult = Result<f64, MathError>;
-
-    pub fn div(x: f64, y: f64) -> MathResult {
-        if y == 0.0 {
-            // This operation would `fail`, instead let's return the reason of
-            // the failure wrapped in `Err`
-            Err(MathError::DivisionByZero)
-        } else {
-            // This operation is valid, return the result wrapped in `Ok`
-            Ok(x / y)
-        }
-    }
-
-    pub fn sqrt(x: f64) -> MathResult {
-        if x < 0.0 {
-            Err(MathError::NegativeSquareRoot)
-        } else {
-            Ok(x.sqrt())
-        }
-    }
-
-    pub fn ln(x: f64) -> MathResult {
-        if x <= 0.0 {
-            Err(MathError::NonPositiveLogarithm)
-        } else {
-            Ok(x.ln())
-        }
-    }
-
-    // Intermediate function
-    fn op_(x: f64, y: f64) -> MathResult {
-        // if `div` "fails", then `DivisionByZero` will be `return`ed
-        let ratio = div(x, y)?;
-
-        // if `ln` "fails", then `NonPositiveLogarithm` will be `return`ed
-        let ln = ln(ratio)?;
-
-        sqrt(ln)
-    }
-
-    pub fn op(x: f64, y: f64) {
-        match op_(x, y) {
-            Err(why) => panic!(
-                "{}",
-                match why {
-                    MathError::NonPositiveLogarithm => "logarithm of non-positive number",
-                    MathError::DivisionByZero => "division by zero",
-                    MathError::NegativeSquareRoot => "square root of negative number",
-                }
-            ),
-            Ok(value) => println!("{}", value),
+            writeln!(out, "{} / {} = {}", dividend, divisor, quotient)
         }
     }
 }
@@ -541,19 +523,44 @@ struct AccountInfo<'a> {
 
 type Accounts<'a> = HashMap<Account<'a>, AccountInfo<'a>>;
 
-fn try_logon<'a>(accounts: &Accounts<'a>, username: &'a str, password: &'a str) {
-    println!("Username: {}", username);
-    println!("Password: {}", password);
-    println!("Attempting logon...");
+fn try_logon<'a>(
+    out: &mut impl Write,
+    accounts: &Accounts<'a>,
+    username: &'a str,
+    password: &'a str,
+) -> io::Result<()> {
+    writeln!(out, "Username: {}", username)?;
+    writeln!(out, "Password: {}", password)?;
+    writeln!(out, "Attempting logon...")?;
 
     let logon = Account { username, password };
 
     match accounts.get(&logon) {
         Some(account_info) => {
-            println!("Successful logon!");
-            println!("Name: {}", account_info.name);
-            println!("Email: {}", account_info.email);
+            writeln!(out, "Successful logon!")?;
+            writeln!(out, "Name: {}", account_info.name)?;
+            writeln!(out, "Email: {}", account_info.email)?;
         }
-        _ => println!("Login failed!"),
+        _ => writeln!(out, "Login failed!")?,
     }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    const SETS: [&str; 5] = [
+        "A: ",
+        "B: ",
+        "Union: ",
+        "Intersection: ",
+        "Symmetric Difference: ",
+    ];
+
+    // `HashMap` and `HashSet` are printed in the order of their hashes,
+    // which changes from run to run
+    testing::snapshot_test!(super::run, |snapshot| {
+        snapshot
+            .sort_runs(|line| line.starts_with("Calling "))
+            .sort_items(|line| SETS.iter().any(|set| line.starts_with(set)))
+    });
 }
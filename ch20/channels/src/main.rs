@@ -1,19 +1,31 @@
+use std::io::{self, Write};
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 static NTHREADS: i32 = 3;
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Channels have two endpoints: the `Sender<T>` and the `Receiver<T>`,
     // where `T` is the type of the message to be transferred
     // (type annotation is superfluous)
     let (tx, rx): (Sender<i32>, Receiver<i32>) = mpsc::channel();
     let mut children = Vec::new();
 
+    // The threads may outlive `run`, so they can't borrow `out`. They print
+    // to `printed` instead, and it is copied to `out` once they are done.
+    // Writing to a `Vec` never fails.
+    let printed = Arc::new(Mutex::new(Vec::new()));
+
     for id in 0..NTHREADS {
         // The sender endpoint can be copied
         let thread_tx = tx.clone();
+        let printed = Arc::clone(&printed);
 
         // Each thread will send its id via the channel
         let child = thread::spawn(move || {
@@ -23,7 +35,7 @@ fn main() {
 
             // Sending is a non-blocking operation, the thread will continue
             // immediately after sending its message
-            println!("thread {} finished", id);
+            writeln!(printed.lock().unwrap(), "thread {} finished", id).unwrap();
         });
 
         children.push(child);
@@ -41,7 +53,19 @@ fn main() {
     for child in children {
         child.join().expect("oops! the child thread panicked");
     }
+    out.write_all(&printed.lock().unwrap())?;
 
     // Show the order in which the messages were sent
-    println!("{:?}", ids);
+    writeln!(out, "{:?}", ids)
+}
+
+#[cfg(test)]
+mod tests {
+    // The threads finish, and their messages arrive, in whatever order they
+    // run, so only which ones arrived is checked
+    testing::snapshot_test!(super::run, |snapshot| {
+        snapshot
+            .sort_runs(|line| line.starts_with("thread "))
+            .sort_items(|line| line.starts_with('['))
+    });
 }
@@ -1,9 +1,14 @@
+use std::io;
 use std::io::prelude::*;
 use std::process::{Command, Stdio};
 
 static PANGRAM: &'static str = "the quick brown fox jumped over the lazy dog\n";
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     let output = Command::new("rustc")
         .arg("--version")
         .output()
@@ -12,11 +17,11 @@ fn main() {
     if output.status.success() {
         let s = String::from_utf8_lossy(&output.stdout);
 
-        print!("rustc succeeded and stdout was:\n{}", s);
+        write!(out, "rustc succeeded and stdout was:\n{}", s)?;
     } else {
         let s = String::from_utf8_lossy(&output.stderr);
 
-        print!("rustc failed and stderr was:\n{}", s);
+        write!(out, "rustc failed and stderr was:\n{}", s)?;
     }
 
     // Spawn the `wc` command
@@ -35,7 +40,7 @@ fn main() {
     // must have one, we can directly `unwrap` it.
     match process.stdin.unwrap().write_all(PANGRAM.as_bytes()) {
         Err(why) => panic!("couldn't write to wc stdin: {}", why),
-        Ok(_) => println!("sent pangram to wc"),
+        Ok(_) => writeln!(out, "sent pangram to wc")?,
     }
 
     // Because `stdin` does not live after the above calls, it is `drop`ed,
@@ -48,11 +53,27 @@ fn main() {
     let mut s = String::new();
     match process.stdout.unwrap().read_to_string(&mut s) {
         Err(why) => panic!("couldn't read wc stdout: {}", why),
-        Ok(_) => print!("wc responded with:\n{}", s),
+        Ok(_) => write!(out, "wc responded with:\n{}", s)?,
     }
 
     let mut child = Command::new("sleep").arg("5").spawn().unwrap();
     let _result = child.wait().unwrap();
 
-    println!("reached end of main");
+    writeln!(out, "reached end of main")
+}
+
+#[cfg(test)]
+mod tests {
+    // Whichever rustc is installed
+    fn redact_version(output: &str) -> String {
+        output
+            .lines()
+            .map(|line| match line.starts_with("rustc 1.") {
+                true => "rustc <version>\n".to_owned(),
+                false => format!("{}\n", line),
+            })
+            .collect()
+    }
+
+    testing::snapshot_test!(super::run, |snapshot| snapshot.filter(redact_version));
 }
@@ -12,7 +12,11 @@ use std::io::prelude::*;
 use std::io::{self, BufRead};
 use std::path::Path;
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Create a path to the desired file
     let path = Path::new("hello.txt");
     let display = path.display();
@@ -27,7 +31,7 @@ fn main() {
     let mut s = String::new();
     match file.read_to_string(&mut s) {
         Err(why) => panic!("couldn't read {}: {}", display, why),
-        Ok(_) => print!("{} contains:\n{}", display, s),
+        Ok(_) => write!(out, "{} contains:\n{}", display, s)?,
     }
 
     // `file` goes out of scope, and the "hello.txt" file gets closed
@@ -44,7 +48,7 @@ fn main() {
     // Write the `LOREM_IPSUM` string to `file`, returns `io::Result<()>`
     match file.write_all(LOREM_IPSUM.as_bytes()) {
         Err(why) => panic!("couldn't write to {}: {}", display, why),
-        Ok(_) => println!("successfully wrote to {}", display),
+        Ok(_) => writeln!(out, "successfully wrote to {}", display)?,
     }
 
     // File hosts must exist in current path before this produces output
@@ -52,10 +56,12 @@ fn main() {
         // Consumes the iterator, returns an (Optional) String
         for line in lines {
             if let Ok(ip) = line {
-                println!("{}", ip);
+                writeln!(out, "{}", ip)?;
             }
         }
     }
+
+    Ok(())
 }
 
 // The output is wrapped in a Result to allow matching on errors
@@ -67,3 +73,6 @@ where
     let file = File::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -5,67 +5,62 @@ use std::io::prelude::*;
 use std::os::unix;
 use std::path::Path;
 
-fn main() {
-    println!("`mkdir a`");
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "`mkdir a`")?;
     // Create a directory, returns `io::Result<()>`
     match fs::create_dir("a") {
-        Err(why) => println!("! {:?}", why.kind()),
+        Err(why) => writeln!(out, "! {:?}", why.kind())?,
         Ok(_) => {}
     }
 
-    println!("`echo hello > a/b.txt`");
-    // The previous match can be simplified using the `unwrap_or_else` method
-    echo("hello", &Path::new("a/b.txt")).unwrap_or_else(|why| {
-        println!("! {:?}", why.kind());
-    });
+    writeln!(out, "`echo hello > a/b.txt`")?;
+    // The previous match can be simplified using the `or_else` method
+    echo("hello", &Path::new("a/b.txt")).or_else(|why| writeln!(out, "! {:?}", why.kind()))?;
 
-    println!("`mkdir -p a/c/d`");
+    writeln!(out, "`mkdir -p a/c/d`")?;
     // Recursively create a directory, returns `io::Result<()>`
-    fs::create_dir_all("a/c/d").unwrap_or_else(|why| {
-        println!("! {:?}", why.kind());
-    });
+    fs::create_dir_all("a/c/d").or_else(|why| writeln!(out, "! {:?}", why.kind()))?;
 
-    println!("`touch a/c/e.txt`");
-    touch(&Path::new("a/c/e.txt")).unwrap_or_else(|why| {
-        println!("! {:?}", why.kind());
-    });
+    writeln!(out, "`touch a/c/e.txt`")?;
+    touch(&Path::new("a/c/e.txt")).or_else(|why| writeln!(out, "! {:?}", why.kind()))?;
 
-    println!("`ln -s ../b.txt a/c/b.txt`");
+    writeln!(out, "`ln -s ../b.txt a/c/b.txt`")?;
     // Create a symbolic link, returns `io::Result<()>`
     if cfg!(target_family = "unix") {
-        unix::fs::symlink("../b.txt", "a/c/b.txt").unwrap_or_else(|why| {
-            println!("! {:?}", why.kind());
-        });
+        unix::fs::symlink("../b.txt", "a/c/b.txt")
+            .or_else(|why| writeln!(out, "! {:?}", why.kind()))?;
     }
 
-    println!("`cat a/c/b.txt`");
+    writeln!(out, "`cat a/c/b.txt`")?;
     match cat(&Path::new("a/c/b.txt")) {
-        Err(why) => println!("! {:?}", why.kind()),
-        Ok(s) => println!("> {}", s),
+        Err(why) => writeln!(out, "! {:?}", why.kind())?,
+        Ok(s) => writeln!(out, "> {}", s)?,
     }
 
-    println!("`ls a`");
+    writeln!(out, "`ls a`")?;
     // Read the contents of a directory, returns `io::Result<Vec<Path>>`
     match fs::read_dir("a") {
-        Err(why) => println!("! {:?}", why.kind()),
+        Err(why) => writeln!(out, "! {:?}", why.kind())?,
         Ok(paths) => {
             for path in paths {
-                println!("> {:?}", path.unwrap().path());
+                writeln!(out, "> {:?}", path.unwrap().path())?;
             }
         }
     }
 
-    println!("`rm a/c/e.txt`");
+    writeln!(out, "`rm a/c/e.txt`")?;
     // Remove a file, returns `io::Result<()>`
-    fs::remove_file("a/c/e.txt").unwrap_or_else(|why| {
-        println!("! {:?}", why.kind());
-    });
+    fs::remove_file("a/c/e.txt").or_else(|why| writeln!(out, "! {:?}", why.kind()))?;
 
-    println!("`rmdir a/c/d`");
+    writeln!(out, "`rmdir a/c/d`")?;
     // Remove an empty directory, returns `io::Result<()>`
-    fs::remove_dir("a/c/d").unwrap_or_else(|why| {
-        println!("! {:?}", why.kind());
-    });
+    fs::remove_dir("a/c/d").or_else(|why| writeln!(out, "! {:?}", why.kind()))?;
+
+    Ok(())
 }
 
 // A simple implementation of `% cat path`
@@ -97,3 +92,25 @@ fn touch(path: &Path) -> io::Result<()> {
         Err(e) => Err(e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use testing::temp_dir::TempDir;
+
+    // `run` works in the current directory, so it is run in an empty one,
+    // as it would be the first time
+    #[test]
+    fn test_output() {
+        let dir = TempDir::new();
+        let previous = env::current_dir().unwrap();
+        env::set_current_dir(dir.path()).unwrap();
+        let output = testing::output::capture(super::run);
+        env::set_current_dir(previous).unwrap();
+
+        // `ls a` lists the entries in no particular order
+        testing::snapshot!("main")
+            .sort_runs(|line| line.starts_with("> \""))
+            .assert(&output);
+    }
+}
@@ -0,0 +1,422 @@
+// Complex numbers over `f32` or `f64`, with the same memory layout as C's
+// `float complex` and `double complex`, so that they can be passed to and
+// from C functions such as `csqrtf`. Every function here is written in Rust
+// on top of the real functions of the standard library.
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use formatting::flags::{number, pad};
+
+// The real number types a `Complex` can be built from
+pub trait Float:
+    Copy
+    + PartialOrd
+    + fmt::Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TWO: Self;
+    const HALF: Self;
+
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn ln_1p(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn sinh(self) -> Self;
+    fn cosh(self) -> Self;
+    fn asinh(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn copysign(self, sign: Self) -> Self;
+    fn from_f32(x: f32) -> Self;
+}
+
+macro_rules! impl_float {
+    ($t:ident) => {
+        impl Float for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const TWO: Self = 2.0;
+            const HALF: Self = 0.5;
+
+            fn abs(self) -> Self {
+                $t::abs(self)
+            }
+            fn sqrt(self) -> Self {
+                $t::sqrt(self)
+            }
+            fn exp(self) -> Self {
+                $t::exp(self)
+            }
+            fn ln(self) -> Self {
+                $t::ln(self)
+            }
+            fn ln_1p(self) -> Self {
+                $t::ln_1p(self)
+            }
+            fn sin(self) -> Self {
+                $t::sin(self)
+            }
+            fn cos(self) -> Self {
+                $t::cos(self)
+            }
+            fn tan(self) -> Self {
+                $t::tan(self)
+            }
+            fn sinh(self) -> Self {
+                $t::sinh(self)
+            }
+            fn cosh(self) -> Self {
+                $t::cosh(self)
+            }
+            fn asinh(self) -> Self {
+                $t::asinh(self)
+            }
+            fn powf(self, n: Self) -> Self {
+                $t::powf(self, n)
+            }
+            fn atan2(self, other: Self) -> Self {
+                $t::atan2(self, other)
+            }
+            fn hypot(self, other: Self) -> Self {
+                $t::hypot(self, other)
+            }
+            fn copysign(self, sign: Self) -> Self {
+                $t::copysign(self, sign)
+            }
+            fn from_f32(x: f32) -> Self {
+                x as $t
+            }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
+
+// `re + im * i`. `#[repr(C)]` lays the two parts out like C does.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T: Float> Complex<T> {
+    pub fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+
+    // The imaginary unit
+    pub fn i() -> Self {
+        Complex::new(T::ZERO, T::ONE)
+    }
+
+    // The number with absolute value `r` at angle `theta` from the real axis
+    pub fn from_polar(r: T, theta: T) -> Self {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    // The absolute value and the angle, the inverse of `from_polar`
+    pub fn to_polar(self) -> (T, T) {
+        (self.norm(), self.arg())
+    }
+
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    // The absolute value `|z|`, without overflowing for large parts
+    pub fn norm(self) -> T {
+        self.re.hypot(self.im)
+    }
+
+    // `|z|²`, cheaper than `norm` when only comparing sizes
+    pub fn norm_sqr(self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+
+    // The angle from the positive real axis, in `-π..=π`
+    pub fn arg(self) -> T {
+        self.im.atan2(self.re)
+    }
+
+    pub fn scale(self, t: T) -> Self {
+        Complex::new(self.re * t, self.im * t)
+    }
+
+    pub fn exp(self) -> Self {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+
+    // The principal logarithm, with the imaginary part in `-π..=π`
+    pub fn ln(self) -> Self {
+        Complex::new(self.norm().ln(), self.arg())
+    }
+
+    // The principal square root, with a non-negative real part. Computing
+    // it from the parts rather than as `exp(ln(z) / 2)` keeps it exact for
+    // perfect squares and accurate close to the axes.
+    pub fn sqrt(self) -> Self {
+        if self.re == T::ZERO && self.im == T::ZERO {
+            return Complex::new(T::ZERO, self.im);
+        }
+        let t = ((self.re.abs() + self.norm()) * T::HALF).sqrt();
+        if self.re >= T::ZERO {
+            Complex::new(t, self.im / (T::TWO * t))
+        } else {
+            Complex::new(self.im.abs() / (T::TWO * t), t.copysign(self.im))
+        }
+    }
+
+    // `self` to the power of a complex number, using the principal
+    // logarithm. `0` to any power with a positive real part is `0`.
+    pub fn pow(self, exponent: Self) -> Self {
+        if self.re == T::ZERO && self.im == T::ZERO && exponent.re > T::ZERO {
+            return Complex::new(T::ZERO, T::ZERO);
+        }
+        (self.ln() * exponent).exp()
+    }
+
+    // `self` to a real power
+    pub fn powf(self, exponent: T) -> Self {
+        let (r, theta) = self.to_polar();
+        Complex::from_polar(r.powf(exponent), theta * exponent)
+    }
+
+    pub fn sin(self) -> Self {
+        Complex::new(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+
+    pub fn cos(self) -> Self {
+        Complex::new(
+            self.re.cos() * self.im.cosh(),
+            -(self.re.sin() * self.im.sinh()),
+        )
+    }
+
+    pub fn tan(self) -> Self {
+        // tan(z) = -i tanh(iz)
+        let t = Complex::new(-self.im, self.re).tanh();
+        Complex::new(t.im, -t.re)
+    }
+
+    pub fn sinh(self) -> Self {
+        Complex::new(
+            self.re.sinh() * self.im.cos(),
+            self.re.cosh() * self.im.sin(),
+        )
+    }
+
+    pub fn cosh(self) -> Self {
+        Complex::new(
+            self.re.cosh() * self.im.cos(),
+            self.re.sinh() * self.im.sin(),
+        )
+    }
+
+    pub fn tanh(self) -> Self {
+        // The textbook `(sinh(2a) + i sin(2b)) / (cosh(2a) + cos(2b))`
+        // overflows for large `a` even though the result tends to ±1, so
+        // this uses Kahan's rearrangement instead, and the limit beyond 20
+        // where it no longer changes in `f32` or `f64`
+        if self.re.abs() > T::from_f32(20.0) {
+            let tiny =
+                T::from_f32(4.0) * self.im.sin() * self.im.cos() * (-T::TWO * self.re.abs()).exp();
+            return Complex::new(T::ONE.copysign(self.re), tiny);
+        }
+        let t = self.im.tan();
+        let beta = T::ONE + t * t;
+        let s = self.re.sinh();
+        let rho = (T::ONE + s * s).sqrt();
+        let denominator = T::ONE + beta * s * s;
+        Complex::new(beta * rho * s / denominator, t / denominator)
+    }
+
+    // The inverse functions below use W. Kahan's formulas ("Branch Cuts for
+    // Complex Elementary Functions", 1987), which follow the same branch
+    // cuts as C99 and avoid the cancellation of the textbook logarithm
+    // forms. `1 - z` is written out as `(1 - x) - yi` rather than computed
+    // by `Sub`, because on a branch cut the sign of a zero imaginary part
+    // decides the side, and `0.0 - 0.0` would lose it.
+
+    pub fn asin(self) -> Self {
+        let s1 = Complex::new(T::ONE - self.re, -self.im).sqrt();
+        let s2 = Complex::new(T::ONE + self.re, self.im).sqrt();
+        Complex::new(self.re.atan2((s1 * s2).re), (s1.conj() * s2).im.asinh())
+    }
+
+    pub fn acos(self) -> Self {
+        let s1 = Complex::new(T::ONE - self.re, -self.im).sqrt();
+        let s2 = Complex::new(T::ONE + self.re, self.im).sqrt();
+        Complex::new(T::TWO * s1.re.atan2(s2.re), (s2.conj() * s1).im.asinh())
+    }
+
+    pub fn atan(self) -> Self {
+        // atan(z) = -i atanh(iz)
+        let t = Complex::new(-self.im, self.re).atanh();
+        Complex::new(t.im, -t.re)
+    }
+
+    pub fn asinh(self) -> Self {
+        // asinh(z) = -i asin(iz)
+        let t = Complex::new(-self.im, self.re).asin();
+        Complex::new(t.im, -t.re)
+    }
+
+    pub fn acosh(self) -> Self {
+        let s1 = Complex::new(self.re - T::ONE, self.im).sqrt();
+        let s2 = Complex::new(self.re + T::ONE, self.im).sqrt();
+        Complex::new((s1.conj() * s2).re.asinh(), T::TWO * s1.im.atan2(s2.re))
+    }
+
+    pub fn atanh(self) -> Self {
+        // atanh is odd; working on Re z >= 0 keeps the argument of `ln_1p`
+        // positive, so it never cancels against the 1.
+        if self.re < T::ZERO {
+            return -(-self).atanh();
+        }
+        let (x, y) = (self.re, self.im);
+        let one_minus_x = T::ONE - x;
+        let four = T::from_f32(4.0);
+        Complex::new(
+            (four * x / (one_minus_x * one_minus_x + y * y)).ln_1p() / four,
+            (T::TWO * y).atan2(one_minus_x * (T::ONE + x) - y * y) * T::HALF,
+        )
+    }
+}
+
+impl<T: Float> Add for Complex<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl<T: Float> Sub for Complex<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl<T: Float> Mul for Complex<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl<T: Float> Div for Complex<T> {
+    type Output = Self;
+
+    // Smith's algorithm, which avoids overflow in `|rhs|²`
+    fn div(self, rhs: Self) -> Self {
+        if rhs.re.abs() >= rhs.im.abs() {
+            let r = rhs.im / rhs.re;
+            let d = rhs.re + rhs.im * r;
+            Complex::new((self.re + self.im * r) / d, (self.im - self.re * r) / d)
+        } else {
+            let r = rhs.re / rhs.im;
+            let d = rhs.re * r + rhs.im;
+            Complex::new((self.re * r + self.im) / d, (self.im * r - self.re) / d)
+        }
+    }
+}
+
+impl<T: Float> Neg for Complex<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+// Arithmetic with a real number on the right
+impl<T: Float> Add<T> for Complex<T> {
+    type Output = Self;
+
+    fn add(self, rhs: T) -> Self {
+        Complex::new(self.re + rhs, self.im)
+    }
+}
+
+impl<T: Float> Sub<T> for Complex<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: T) -> Self {
+        Complex::new(self.re - rhs, self.im)
+    }
+}
+
+impl<T: Float> Mul<T> for Complex<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        self.scale(rhs)
+    }
+}
+
+impl<T: Float> Div<T> for Complex<T> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self {
+        Complex::new(self.re / rhs, self.im / rhs)
+    }
+}
+
+impl<T: Float> From<T> for Complex<T> {
+    fn from(re: T) -> Self {
+        Complex::new(re, T::ZERO)
+    }
+}
+
+// Compact, as in `-1+0i`
+impl<T: Float> fmt::Debug for Complex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.im < T::ZERO {
+            write!(f, "{}-{}i", self.re, -self.im)
+        } else {
+            write!(f, "{}+{}i", self.re, self.im)
+        }
+    }
+}
+
+// Spaced out, as in `3.3 + 7.2i` or `3.3 - 7.2i`. The precision applies to both parts, the
+// sign option to the real part only, and the width pads the whole number.
+// `{:#}` writes each part on a line of its own.
+impl<T: Float> fmt::Display for Complex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let re = number(f, self.re);
+        let unsigned = |x: T| match f.precision() {
+            Some(precision) => format!("{:.*}", precision, x),
+            None => format!("{}", x),
+        };
+        if f.alternate() {
+            pad(f, &format!("real: {}\nimag: {}", re, unsigned(self.im)))
+        } else if self.im < T::ZERO {
+            pad(f, &format!("{} - {}i", re, unsigned(-self.im)))
+        } else {
+            pad(f, &format!("{} + {}i", re, unsigned(self.im)))
+        }
+    }
+}
@@ -0,0 +1,5 @@
+// The complex number type used by the example. It lives in a library so
+// that the tests can use it too.
+pub mod complex;
+
+pub use complex::{Complex, Float};
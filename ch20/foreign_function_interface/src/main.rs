@@ -1,16 +1,38 @@
-use std::fmt;
+use std::io::{self, Write};
 
-fn main() {
+use foreign_function_interface::Complex;
+
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // z = -1 + 0i
     let z = Complex { re: -1., im: 0. };
 
     // calling a foreign function is an unsafe operation
     let z_sqrt = unsafe { csqrtf(z) };
 
-    println!("the square root of {:?} is {:?}", z, z_sqrt);
+    writeln!(out, "the square root of {:?} is {:?}", z, z_sqrt)?;
 
     // calling safe API wrapped around unsafe operation
-    println!("cos({:?}) = {:?}", z, cos(z));
+    writeln!(out, "cos({:?}) = {:?}", z, cos(z))?;
+
+    // `Complex` has the same functions written in Rust, which agree with
+    // the C ones
+    writeln!(out, "in Rust: sqrt = {:?}, cos = {:?}", z.sqrt(), z.cos())?;
+
+    // It is generic over `f32` and `f64`, and supports the usual arithmetic
+    let w = Complex::new(3.0f64, 4.0);
+    writeln!(out, "{} * {} = {}", w, w.conj(), w * w.conj())?;
+    writeln!(out, "|{}| = {}, arg = {:.4}", w, w.norm(), w.arg())?;
+    writeln!(
+        out,
+        "exp(i * pi) = {:.3}",
+        (Complex::i() * std::f64::consts::PI).exp()
+    )?;
+    writeln!(out, "ln({}) = {:.4}", w, w.ln())?;
+    writeln!(out, "{} ^ i = {:.4}", w, w.pow(Complex::i()))
 }
 
 // this extern block links to the libm library
@@ -18,31 +40,16 @@ fn main() {
 extern "C" {
     // this is a foreign function
     // that computes the square root of a single precision complex number
-    fn csqrtf(z: Complex) -> Complex;
+    fn csqrtf(z: Complex<f32>) -> Complex<f32>;
 
-    fn ccosf(z: Complex) -> Complex;
+    fn ccosf(z: Complex<f32>) -> Complex<f32>;
 }
 
 // Since calling foreign functions is considered unsafe,
 // it's common to write safe wrappers around them.
-fn cos(z: Complex) -> Complex {
+fn cos(z: Complex<f32>) -> Complex<f32> {
     unsafe { ccosf(z) }
 }
 
-// Minimal implementation of single precision complex numbers
-#[repr(C)]
-#[derive(Clone, Copy)]
-struct Complex {
-    re: f32,
-    im: f32,
-}
-
-impl fmt::Debug for Complex {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.im < 0. {
-            write!(f, "{}-{}i", self.re, -self.im)
-        } else {
-            write!(f, "{}+{}i", self.re, self.im)
-        }
-    }
-}
+#[cfg(test)]
+testing::snapshot_test!(run);
@@ -0,0 +1,57 @@
+// `#[derive(Display)]` from chapter 17 reproduces the `Display` of `Complex`
+use derive_macros::Display;
+use foreign_function_interface::{Complex, Float};
+
+// The precision, but not the sign
+fn unsigned<T: Float>(f: &std::fmt::Formatter, x: T) -> String {
+    match f.precision() {
+        Some(precision) => format!("{:.*}", precision, x),
+        None => format!("{}", x),
+    }
+}
+
+#[derive(Display)]
+#[display(
+    "{re} {sign} {magnitude}i",
+    alternate = "real: {re}\nimag: {im}",
+    bound = "T: Float",
+    sign = if self.im < T::ZERO { '-' } else { '+' },
+    magnitude = unsigned(f, if self.im < T::ZERO { -self.im } else { self.im }),
+    im = unsigned(f, self.im),
+)]
+struct Derived<T> {
+    #[display(number)]
+    re: T,
+    im: T,
+}
+
+// Both values give the same output for each of the format strings
+macro_rules! assert_same {
+    ($real:expr, $derived:expr, $($format:literal),+) => {$(
+        assert_eq!(format!($format, $real), format!($format, $derived), "{}", $format);
+    )+};
+}
+
+#[test]
+fn test_derived() {
+    for (re, im) in [(3.3, 7.25), (3.0, -4.0), (-0.5, 0.0)] {
+        assert_same!(
+            Complex::new(re, im),
+            Derived { re, im },
+            "{}",
+            "{:^15}",
+            "{:>14.3}",
+            "{:>+14.2}",
+            "{:#.1}"
+        );
+    }
+    assert_same!(
+        Complex::new(1.5f32, -2.0),
+        Derived {
+            re: 1.5f32,
+            im: -2.0
+        },
+        "{}",
+        "{:+.2}"
+    );
+}
@@ -0,0 +1,194 @@
+// Cross-checks every function of `Complex` against its counterpart in the C
+// library, over a grid of inputs in all four quadrants.
+use foreign_function_interface::Complex;
+
+#[link(name = "m")]
+extern "C" {
+    fn csqrtf(z: Complex<f32>) -> Complex<f32>;
+    fn cexpf(z: Complex<f32>) -> Complex<f32>;
+    fn clogf(z: Complex<f32>) -> Complex<f32>;
+    fn cpowf(z: Complex<f32>, w: Complex<f32>) -> Complex<f32>;
+    fn csinf(z: Complex<f32>) -> Complex<f32>;
+    fn ccosf(z: Complex<f32>) -> Complex<f32>;
+    fn ctanf(z: Complex<f32>) -> Complex<f32>;
+    fn csinhf(z: Complex<f32>) -> Complex<f32>;
+    fn ccoshf(z: Complex<f32>) -> Complex<f32>;
+    fn ctanhf(z: Complex<f32>) -> Complex<f32>;
+    fn casinf(z: Complex<f32>) -> Complex<f32>;
+    fn cacosf(z: Complex<f32>) -> Complex<f32>;
+    fn catanf(z: Complex<f32>) -> Complex<f32>;
+    fn casinhf(z: Complex<f32>) -> Complex<f32>;
+    fn cacoshf(z: Complex<f32>) -> Complex<f32>;
+    fn catanhf(z: Complex<f32>) -> Complex<f32>;
+    fn cabsf(z: Complex<f32>) -> f32;
+    fn cargf(z: Complex<f32>) -> f32;
+    fn conjf(z: Complex<f32>) -> Complex<f32>;
+
+    fn csqrt(z: Complex<f64>) -> Complex<f64>;
+    fn cexp(z: Complex<f64>) -> Complex<f64>;
+    fn clog(z: Complex<f64>) -> Complex<f64>;
+    fn cpow(z: Complex<f64>, w: Complex<f64>) -> Complex<f64>;
+    fn csin(z: Complex<f64>) -> Complex<f64>;
+    fn ccos(z: Complex<f64>) -> Complex<f64>;
+    fn ctan(z: Complex<f64>) -> Complex<f64>;
+    fn csinh(z: Complex<f64>) -> Complex<f64>;
+    fn ccosh(z: Complex<f64>) -> Complex<f64>;
+    fn ctanh(z: Complex<f64>) -> Complex<f64>;
+    fn casin(z: Complex<f64>) -> Complex<f64>;
+    fn cacos(z: Complex<f64>) -> Complex<f64>;
+    fn catan(z: Complex<f64>) -> Complex<f64>;
+    fn casinh(z: Complex<f64>) -> Complex<f64>;
+    fn cacosh(z: Complex<f64>) -> Complex<f64>;
+    fn catanh(z: Complex<f64>) -> Complex<f64>;
+    fn cabs(z: Complex<f64>) -> f64;
+    fn carg(z: Complex<f64>) -> f64;
+    fn conj(z: Complex<f64>) -> Complex<f64>;
+}
+
+// The parts of the grid points. Zero is left out of the imaginary parts, so
+// that no point lies on a branch cut, where the sign of a zero decides the
+// result.
+const RE: [f64; 11] = [
+    -7.5, -2.0, -1.0, -0.75, -0.1, 0.0, 0.3, 0.999, 1.0, 2.5, 9.0,
+];
+const IM: [f64; 10] = [-8.0, -3.0, -1.0, -0.5, -0.01, 0.01, 0.25, 1.0, 2.0, 6.5];
+
+fn grid() -> Vec<Complex<f64>> {
+    RE.iter()
+        .flat_map(|&re| IM.iter().map(move |&im| Complex::new(re, im)))
+        .collect()
+}
+
+fn grid_f32() -> Vec<Complex<f32>> {
+    grid()
+        .into_iter()
+        .map(|z| Complex::new(z.re as f32, z.im as f32))
+        .collect()
+}
+
+// Relative error, or absolute error for results close to zero
+fn error(ours: f64, theirs: f64) -> f64 {
+    (ours - theirs).abs() / theirs.abs().max(1.0)
+}
+
+fn check(name: &str, z: Complex<f64>, ours: Complex<f64>, theirs: Complex<f64>, tolerance: f64) {
+    let worst = error(ours.re, theirs.re).max(error(ours.im, theirs.im));
+    assert!(
+        worst <= tolerance,
+        "{}({:?}): ours {:?}, libm {:?}",
+        name,
+        z,
+        ours,
+        theirs
+    );
+}
+
+macro_rules! cross_check {
+    ($test:ident, $method:ident, $c64:ident, $c32:ident) => {
+        #[test]
+        fn $test() {
+            for z in grid() {
+                let theirs = unsafe { $c64(z) };
+                check(stringify!($c64), z, z.$method(), theirs, 1e-12);
+            }
+            for z in grid_f32() {
+                let theirs = unsafe { $c32(z) };
+                let ours = z.$method();
+                let widen = |c: Complex<f32>| Complex::new(c.re as f64, c.im as f64);
+                check(stringify!($c32), widen(z), widen(ours), widen(theirs), 1e-5);
+            }
+        }
+    };
+}
+
+cross_check!(test_sqrt, sqrt, csqrt, csqrtf);
+cross_check!(test_exp, exp, cexp, cexpf);
+cross_check!(test_ln, ln, clog, clogf);
+cross_check!(test_sin, sin, csin, csinf);
+cross_check!(test_cos, cos, ccos, ccosf);
+cross_check!(test_tan, tan, ctan, ctanf);
+cross_check!(test_sinh, sinh, csinh, csinhf);
+cross_check!(test_cosh, cosh, ccosh, ccoshf);
+cross_check!(test_tanh, tanh, ctanh, ctanhf);
+cross_check!(test_asin, asin, casin, casinf);
+cross_check!(test_acos, acos, cacos, cacosf);
+cross_check!(test_atan, atan, catan, catanf);
+cross_check!(test_asinh, asinh, casinh, casinhf);
+cross_check!(test_acosh, acosh, cacosh, cacoshf);
+cross_check!(test_atanh, atanh, catanh, catanhf);
+cross_check!(test_conj, conj, conj, conjf);
+
+#[test]
+fn test_norm_and_arg() {
+    for z in grid() {
+        assert!(error(z.norm(), unsafe { cabs(z) }) <= 1e-15);
+        assert!(error(z.arg(), unsafe { carg(z) }) <= 1e-15);
+    }
+    for z in grid_f32() {
+        assert!(error(z.norm() as f64, unsafe { cabsf(z) } as f64) <= 1e-6);
+        assert!(error(z.arg() as f64, unsafe { cargf(z) } as f64) <= 1e-6);
+    }
+}
+
+#[test]
+fn test_pow() {
+    let exponents = [
+        Complex::new(2.0, 0.0),
+        Complex::new(0.5, 0.0),
+        Complex::new(-1.5, 0.25),
+        Complex::new(0.0, 1.0),
+        Complex::new(1.25, -0.75),
+    ];
+    for z in grid() {
+        for w in exponents {
+            check("cpow", z, z.pow(w), unsafe { cpow(z, w) }, 1e-11);
+        }
+    }
+    for z in grid_f32() {
+        for w in exponents {
+            let w = Complex::new(w.re as f32, w.im as f32);
+            let theirs = unsafe { cpowf(z, w) };
+            let widen = |c: Complex<f32>| Complex::new(c.re as f64, c.im as f64);
+            check("cpowf", widen(z), widen(z.pow(w)), widen(theirs), 1e-4);
+        }
+    }
+}
+
+#[test]
+fn test_arithmetic() {
+    let a = Complex::new(3.0, 4.0);
+    let b = Complex::new(1.0, -2.0);
+    assert_eq!(a + b, Complex::new(4.0, 2.0));
+    assert_eq!(a - b, Complex::new(2.0, 6.0));
+    assert_eq!(a * b, Complex::new(11.0, -2.0));
+    assert_eq!((a * b) / b, a);
+    assert_eq!(-a, Complex::new(-3.0, -4.0));
+    assert_eq!(a * 2.0, Complex::new(6.0, 8.0));
+    assert_eq!(a.norm(), 5.0);
+    // Smith's division doesn't overflow where |b|² would
+    let huge = Complex::new(1e300, 1e300);
+    assert_eq!(huge / huge, Complex::new(1.0, 0.0));
+
+    let (r, theta) = a.to_polar();
+    let back = Complex::from_polar(r, theta);
+    assert!((back - a).norm() < 1e-12);
+    assert!((a.powf(2.0) - a * a).norm() < 1e-12);
+}
+
+#[test]
+fn test_layout_matches_c() {
+    assert_eq!(std::mem::size_of::<Complex<f32>>(), 8);
+    assert_eq!(std::mem::align_of::<Complex<f32>>(), 4);
+    assert_eq!(std::mem::size_of::<Complex<f64>>(), 16);
+    assert_eq!(std::mem::align_of::<Complex<f64>>(), 8);
+}
+
+#[test]
+fn test_display() {
+    let z = Complex::new(3.3, 7.25);
+    assert_eq!(format!("{}", z), "3.3 + 7.25i");
+    assert_eq!(format!("{:?}", Complex::new(1.0, -2.0)), "1-2i");
+    assert_eq!(format!("{:>+14.2}", z), " +3.30 + 7.25i");
+    assert_eq!(format!("{}", Complex::new(3.0, -4.0)), "3 - 4i");
+    assert_eq!(format!("{:#.1}", z), "real: 3.3\nimag: 7.2");
+}
@@ -1,6 +1,11 @@
+use std::io::{self, Write};
 use std::path::Path;
 
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
     // Create a `Path` from an `&'static str`
     let path = Path::new(".");
 
@@ -14,6 +19,11 @@ fn main() {
     // Convert the path into a string slice
     match new_path.to_str() {
         None => panic!("new path is not a valid UTF-8 sequence"),
-        Some(s) => println!("new path is {}", s),
+        Some(s) => writeln!(out, "new path is {}", s)?,
     }
+
+    Ok(())
 }
+
+#[cfg(test)]
+testing::snapshot_test!(run);
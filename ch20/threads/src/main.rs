@@ -1,16 +1,29 @@
+use std::io::{self, Write};
+use std::mem;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 const NTHREADS: u32 = 10;
 
 // This is the `main` thread
-fn main() {
+fn main() -> io::Result<()> {
+    run(&mut io::stdout())
+}
+
+fn run(out: &mut impl Write) -> io::Result<()> {
+    // The threads may outlive `run`, so they can't borrow `out`. They print
+    // to `printed` instead, which they share through an `Arc`, and it is
+    // copied to `out` once they are done. Writing to a `Vec` never fails.
+    let printed = Arc::new(Mutex::new(Vec::new()));
+
     // Make a vector to hold the children which are spawned.
     let mut children = vec![];
 
     for i in 0..NTHREADS {
+        let printed = Arc::clone(&printed);
         // Spin up another thread
         children.push(thread::spawn(move || {
-            println!("this is thread number {}", i);
+            writeln!(printed.lock().unwrap(), "this is thread number {}", i).unwrap();
         }));
     }
 
@@ -18,6 +31,7 @@ fn main() {
         // Wait for the thread to finish. Returns a result.
         let _ = child.join();
     }
+    flush(out, &printed)?;
 
     // This is our data to process.
     // We will calculate the sum of all digits via a threaded  map-reduce algorithm.
@@ -52,7 +66,13 @@ fn main() {
     // "destructured" into two variables, "i" and "data_segment" with a
     // "destructuring assignment"
     for (i, data_segment) in chunked_data.enumerate() {
-        println!("data segment {} is \"{}\"", i, data_segment);
+        writeln!(
+            printed.lock().unwrap(),
+            "data segment {} is \"{}\"",
+            i,
+            data_segment
+        )?;
+        let printed = Arc::clone(&printed);
 
         // Process each data segment in a separate thread
         //
@@ -78,8 +98,15 @@ fn main() {
                 // .. and sum the resulting iterator of numbers
                 .sum();
 
-            // println! locks stdout, so no text-interleaving occurs
-            println!("processed segment {}, result={}", i, result);
+            // The lock is held for the whole line, as println! does with stdout,
+            // so no text-interleaving occurs
+            writeln!(
+                printed.lock().unwrap(),
+                "processed segment {}, result={}",
+                i,
+                result
+            )
+            .unwrap();
 
             // "return" not needed, because Rust is an "expression language", the
             // last evaluated expression in each block is automatically its value.
@@ -101,24 +128,38 @@ fn main() {
     // specifying the type of final_result
     let final_result = children.into_iter().map(|c| c.join().unwrap()).sum::<u32>();
 
-    println!("Final sum result: {}", final_result);
+    flush(out, &printed)?;
+    writeln!(out, "Final sum result: {}", final_result)?;
 
     // original.
     let mut children = vec![];
     let chunked_data = data.split_whitespace();
     for (i, data_segment) in chunked_data.enumerate() {
-        println!("data segment {} is \"{}\"", i, data_segment);
+        writeln!(
+            printed.lock().unwrap(),
+            "data segment {} is \"{}\"",
+            i,
+            data_segment
+        )?;
+        let printed = Arc::clone(&printed);
         children.push(thread::spawn(move || -> u32 {
             let result = data_segment
                 .chars()
                 .map(|c| c.to_digit(10).expect("should be a digit"))
                 .sum();
-            println!("processed segment {}, result={}", i, result);
+            writeln!(
+                printed.lock().unwrap(),
+                "processed segment {}, result={}",
+                i,
+                result
+            )
+            .unwrap();
             result
         }));
     }
     let final_result = children.into_iter().map(|c| c.join().unwrap()).sum::<u32>();
-    println!("Final sum result: {}", final_result);
+    flush(out, &printed)?;
+    writeln!(out, "Final sum result: {}", final_result)?;
 
     // split work into fixed number of threads using chunks.
     // allocate a lot of temporary storage for Vecs and Strings along the way.
@@ -134,18 +175,31 @@ fn main() {
         .map(|chk| chk.iter().collect::<String>())
         .collect::<Vec<String>>();
     for (i, data_segment) in chunked_data.into_iter().enumerate() {
-        println!("data segment {} is \"{}\"", i, data_segment);
+        writeln!(
+            printed.lock().unwrap(),
+            "data segment {} is \"{}\"",
+            i,
+            data_segment
+        )?;
+        let printed = Arc::clone(&printed);
         children.push(thread::spawn(move || -> u32 {
             let result = data_segment
                 .chars()
                 .map(|c| c.to_digit(10).expect("should be a digit"))
                 .sum();
-            println!("processed segment {}, result={}", i, result);
+            writeln!(
+                printed.lock().unwrap(),
+                "processed segment {}, result={}",
+                i,
+                result
+            )
+            .unwrap();
             result
         }));
     }
     let final_result = children.into_iter().map(|c| c.join().unwrap()).sum::<u32>();
-    println!("Final sum result: {}", final_result);
+    flush(out, &printed)?;
+    writeln!(out, "Final sum result: {}", final_result)?;
 
     // remove and reinsert whitespaces at appropriate locations.
     // https://stackoverflow.com/questions/57029974/how-to-split-string-into-chunks-in-rust-to-insert-spaces
@@ -169,16 +223,49 @@ fn main() {
     // split into two lines as temporary data created by collect does not live long enough if chained.
     let chunked_data = chunked_data.split_whitespace().map(|s| s.to_owned());
     for (i, data_segment) in chunked_data.enumerate() {
-        println!("data segment {} is \"{}\"", i, data_segment);
+        writeln!(
+            printed.lock().unwrap(),
+            "data segment {} is \"{}\"",
+            i,
+            data_segment
+        )?;
+        let printed = Arc::clone(&printed);
         children.push(thread::spawn(move || -> u32 {
             let result = data_segment
                 .chars()
                 .map(|c| c.to_digit(10).expect("should be a digit"))
                 .sum();
-            println!("processed segment {}, result={}", i, result);
+            writeln!(
+                printed.lock().unwrap(),
+                "processed segment {}, result={}",
+                i,
+                result
+            )
+            .unwrap();
             result
         }));
     }
     let final_result = children.into_iter().map(|c| c.join().unwrap()).sum::<u32>();
-    println!("Final sum result: {}", final_result);
+    flush(out, &printed)?;
+    writeln!(out, "Final sum result: {}", final_result)
+}
+
+// Copies what the threads have printed so far to `out`
+fn flush(out: &mut impl Write, printed: &Mutex<Vec<u8>>) -> io::Result<()> {
+    out.write_all(&mem::take(&mut *printed.lock().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    // The threads print in whatever order they run, and the segments are
+    // printed while they do
+    fn segments(line: &str) -> bool {
+        line.starts_with("data segment") || line.starts_with("processed segment")
+    }
+
+    testing::snapshot_test!(super::run, |snapshot| {
+        snapshot
+            .sort_runs(|line| line.starts_with("this is thread"))
+            .sort_runs(segments)
+    });
 }
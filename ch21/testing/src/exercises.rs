@@ -0,0 +1,500 @@
+// The exercises that are meant not to compile, or to panic: a line left
+// commented out, with a marker under it
+//
+//     // Error! `rc_examples` already moved into `rc_a`
+//     // println!("rc_examples: {}", rc_examples);
+//     // TODO ^ Try uncommenting this line (error[E0382])
+//
+// or `// ^ TODO: Try uncommenting these lines.` under several. The marker
+// ends with what is supposed to happen once the lines are uncommented:
+// `(error[E0382])` for an error with that code, `(error)` for an error
+// without a code, or `(panics)` for a program that builds but panics when
+// run.
+//
+// `Checker` uncomments each exercise in a scratch copy of its crate, runs
+// `cargo check`, or `cargo run` for a panic, and says which exercises don't
+// fail the way their marker says. Exercises are checked one at a time, so
+// each line is checked on its own.
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::temp_dir::TempDir;
+
+// How many lines above a marker are to be uncommented
+enum Above {
+    Lines(usize),
+    // All the commented lines right above
+    These,
+}
+
+// What a marker asks for, from its text. "FIXME ^ Comment out this line" asks
+// for the line to be commented out again, as it is already.
+fn marker(text: &str) -> Option<Above> {
+    let text = text.trim_start();
+    let starts = ["TODO", "FIXME", "^"];
+    if !text.contains('^') || !starts.iter().any(|start| text.starts_with(start)) {
+        return None;
+    }
+    let text = text.to_lowercase();
+    if let Some((_, rest)) = text.split_once("uncomment the above ") {
+        return rest
+            .split_whitespace()
+            .next()
+            .and_then(count)
+            .map(Above::Lines);
+    }
+    let text = text.trim_end_matches('.');
+    if text.contains("comment out this line")
+        || text.contains("uncommenting this line")
+        || text.ends_with("uncommenting this")
+    {
+        Some(Above::Lines(1))
+    } else if text.contains("uncommenting these") {
+        Some(Above::These)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    // The error code, if the error has one
+    Error(Option<String>),
+    Panic,
+}
+
+impl Expected {
+    fn parse(annotation: &str) -> Option<Expected> {
+        match annotation {
+            "panics" => Some(Expected::Panic),
+            "error" => Some(Expected::Error(None)),
+            _ => {
+                let code = annotation.strip_prefix("error[")?.strip_suffix(']')?;
+                Some(Expected::Error(Some(code.to_owned())))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expected::Error(Some(code)) => write!(f, "error[{}]", code),
+            Expected::Error(None) => write!(f, "error"),
+            Expected::Panic => write!(f, "panics"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exercise {
+    pub crate_dir: PathBuf,
+    // The file, from the crate directory
+    pub file: PathBuf,
+    // Where the marker is, counting from 1
+    pub line: usize,
+    // The lines to uncomment, counting from 0
+    pub lines: Vec<usize>,
+    // `None` if the marker doesn't say
+    pub expected: Option<Expected>,
+}
+
+impl fmt::Display for Exercise {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}",
+            self.crate_dir.join(&self.file).display(),
+            self.line
+        )
+    }
+}
+
+// `// foo();` -> `foo();`, keeping the indentation
+fn uncomment(line: &str) -> Option<String> {
+    let code = line.trim_start();
+    let indent = &line[..line.len() - code.len()];
+    let code = code.strip_prefix("//")?;
+    Some(format!(
+        "{}{}",
+        indent,
+        code.strip_prefix(' ').unwrap_or(code)
+    ))
+}
+
+// `three` in "Uncomment the above three lines" is fine too
+fn count(word: &str) -> Option<usize> {
+    const WORDS: [&str; 5] = ["one", "two", "three", "four", "five"];
+    word.parse()
+        .ok()
+        .or_else(|| WORDS.iter().position(|&w| w == word).map(|i| i + 1))
+}
+
+// The exercises of one file
+pub fn parse(crate_dir: &Path, file: &Path, source: &str) -> Vec<Exercise> {
+    let lines: Vec<&str> = source.lines().collect();
+    let commented = |j: usize| lines[j].trim_start().starts_with("//");
+    let mut exercises = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(text) = line.trim().strip_prefix("//") else {
+            continue;
+        };
+        // The annotation, if there is one, is left out of the text
+        let (text, expected) = match text
+            .strip_suffix(')')
+            .and_then(|text| text.rsplit_once('('))
+            .and_then(|(text, annotation)| Some((text, Expected::parse(annotation)?)))
+        {
+            Some((text, expected)) => (text.trim_end(), Some(expected)),
+            None => (text, None),
+        };
+        let from = match marker(text) {
+            Some(Above::Lines(n)) => i.saturating_sub(n),
+            // Up to the previous marker
+            Some(Above::These) => (0..i)
+                .rev()
+                .take_while(|&j| commented(j) && !lines[j].contains('^'))
+                .last()
+                .unwrap_or(i),
+            None => continue,
+        };
+        exercises.push(Exercise {
+            crate_dir: crate_dir.to_owned(),
+            file: file.to_owned(),
+            line: i + 1,
+            // Only the ones that are commented out, the others may be the
+            // code the exercise is about
+            lines: (from..i).filter(|&j| commented(j)).collect(),
+            expected,
+        });
+    }
+    exercises
+}
+
+fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            rust_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// The exercises of every crate under `root`, in the order of their paths
+pub fn find(root: &Path) -> io::Result<Vec<Exercise>> {
+    let mut exercises = Vec::new();
+    let mut entries: Vec<_> = fs::read_dir(root)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    entries.sort();
+    for path in entries {
+        if !path.is_dir() || path.ends_with("target") {
+            continue;
+        }
+        if path.join("Cargo.toml").is_file() {
+            let mut files = Vec::new();
+            rust_files(&path.join("src"), &mut files)?;
+            files.sort();
+            for file in files {
+                let source = fs::read_to_string(&file)?;
+                let relative = file.strip_prefix(&path).unwrap();
+                exercises.extend(parse(&path, relative, &source));
+            }
+        } else {
+            exercises.extend(find(&path)?);
+        }
+    }
+    Ok(exercises)
+}
+
+// What happened when an exercise was tried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    // The errors, with their codes if they have one
+    Errors(Vec<Option<String>>),
+    Panicked,
+    Ran,
+}
+
+impl Outcome {
+    fn matches(&self, expected: &Expected) -> bool {
+        match (self, expected) {
+            (Outcome::Errors(errors), Expected::Error(code)) => {
+                code.is_none() || errors.contains(code)
+            }
+            (Outcome::Panicked, Expected::Panic) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Errors(errors) => {
+                if errors.is_empty() {
+                    return write!(f, "fails");
+                }
+                write!(f, "fails with ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    match error {
+                        Some(code) => write!(f, "error[{}]", code)?,
+                        None => write!(f, "error")?,
+                    }
+                }
+                Ok(())
+            }
+            Outcome::Panicked => write!(f, "panics"),
+            Outcome::Ran => write!(f, "builds and runs"),
+        }
+    }
+}
+
+// The errors in the output of `cargo check --message-format=short`, as in
+//
+//     src/main.rs:31:21: error[E0505]: cannot move out of `boxed_i32` ...
+//     error: could not compile `borrowing` (bin "borrowing") due to ...
+//
+// The last line isn't an error of the code, so it has no position.
+pub fn errors(output: &str) -> Vec<Option<String>> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, error) = line.split_once(": error")?;
+            match error.strip_prefix('[') {
+                Some(rest) => Some(Some(rest.split_once(']')?.0.to_owned())),
+                None => error.starts_with(':').then_some(None),
+            }
+        })
+        .collect()
+}
+
+// Relative `path = "..."` dependencies, made absolute so that they still
+// work from the scratch copy
+fn absolute_paths(manifest: &str, crate_dir: &Path) -> String {
+    manifest
+        .lines()
+        .map(|line| {
+            let Some((before, rest)) = line.split_once("path = \"") else {
+                return format!("{}\n", line);
+            };
+            let Some((path, after)) = rest.split_once('"') else {
+                return format!("{}\n", line);
+            };
+            let path = crate_dir.join(path);
+            let path = path.canonicalize().unwrap_or(path);
+            format!(
+                "{}path = {:?}{}\n",
+                before,
+                path.display().to_string(),
+                after
+            )
+        })
+        .collect()
+}
+
+pub struct Checker {
+    cargo: String,
+    // Shared by all the crates, so dependencies are only built once
+    target_dir: PathBuf,
+}
+
+impl Checker {
+    // Uses the cargo that runs the tests, and builds in `target_dir`
+    pub fn new<P: AsRef<Path>>(target_dir: P) -> Checker {
+        Checker {
+            cargo: env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned()),
+            target_dir: target_dir.as_ref().to_owned(),
+        }
+    }
+
+    // A copy of the crate, without its `target`
+    fn scratch_copy(&self, crate_dir: &Path) -> io::Result<TempDir> {
+        let scratch = TempDir::with_name("exercise")?;
+        fn copy(from: &Path, to: &Path) -> io::Result<()> {
+            for entry in fs::read_dir(from)? {
+                let entry = entry?;
+                let (from, to) = (entry.path(), to.join(entry.file_name()));
+                if entry.file_type()?.is_dir() {
+                    if entry.file_name() != "target" {
+                        fs::create_dir(&to)?;
+                        copy(&from, &to)?;
+                    }
+                } else {
+                    fs::copy(&from, &to)?;
+                }
+            }
+            Ok(())
+        }
+        copy(crate_dir, scratch.path())?;
+        let manifest = fs::read_to_string(crate_dir.join("Cargo.toml"))?;
+        fs::write(
+            scratch.join("Cargo.toml"),
+            absolute_paths(&manifest, crate_dir),
+        )?;
+        Ok(scratch)
+    }
+
+    fn cargo(&self, dir: &Path, command: &str) -> io::Result<(bool, String)> {
+        let mut cargo = Command::new(&self.cargo);
+        cargo.arg(command).args(["--offline", "--quiet"]);
+        if command == "check" {
+            cargo.args(["--bins", "--message-format=short"]);
+        }
+        let output = cargo
+            .current_dir(dir)
+            .env("CARGO_TARGET_DIR", &self.target_dir)
+            .output()?;
+        Ok((
+            output.status.success(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))
+    }
+
+    fn outcome(&self, dir: &Path, expected: Option<&Expected>) -> io::Result<Outcome> {
+        let (success, output) = self.cargo(dir, "check")?;
+        if !success {
+            return Ok(Outcome::Errors(errors(&output)));
+        }
+        if expected != Some(&Expected::Panic) {
+            return Ok(Outcome::Ran);
+        }
+        let (success, output) = self.cargo(dir, "run")?;
+        Ok(match success {
+            false if output.contains("panicked") => Outcome::Panicked,
+            false => Outcome::Errors(errors(&output)),
+            true => Outcome::Ran,
+        })
+    }
+
+    // Try each exercise of one crate, and return what happened with those
+    // that don't fail the way they should
+    pub fn check_crate(&self, exercises: &[Exercise]) -> io::Result<Vec<(Exercise, String)>> {
+        let Some(first) = exercises.first() else {
+            return Ok(Vec::new());
+        };
+        let scratch = self.scratch_copy(&first.crate_dir)?;
+        let (success, output) = self.cargo(scratch.path(), "check")?;
+        if !success {
+            let problem = format!("the crate doesn't build as it is:\n{}", output);
+            return Ok(exercises
+                .iter()
+                .map(|e| (e.clone(), problem.clone()))
+                .collect());
+        }
+
+        let mut problems = Vec::new();
+        for exercise in exercises {
+            let path = scratch.join(&exercise.file);
+            let original = fs::read_to_string(&path)?;
+            let mut lines: Vec<String> = original.lines().map(str::to_owned).collect();
+            for &i in &exercise.lines {
+                lines[i] = uncomment(&lines[i]).unwrap();
+            }
+            fs::write(&path, lines.join("\n") + "\n")?;
+            let outcome = self.outcome(scratch.path(), exercise.expected.as_ref());
+            fs::write(&path, &original)?;
+            let outcome = outcome?;
+
+            let problem = match &exercise.expected {
+                _ if exercise.lines.is_empty() => {
+                    Some("there is no commented line to try".to_owned())
+                }
+                Some(expected) if outcome.matches(expected) => None,
+                Some(expected) => Some(format!("{}, but should give {}", outcome, expected)),
+                None => Some(format!(
+                    "{}, and the marker doesn't say what it should do",
+                    outcome
+                )),
+            };
+            if let Some(problem) = problem {
+                problems.push((exercise.clone(), problem));
+            }
+        }
+        Ok(problems)
+    }
+
+    // Every exercise, crate by crate
+    pub fn check(&self, exercises: &[Exercise]) -> io::Result<Vec<(Exercise, String)>> {
+        let mut problems = Vec::new();
+        for crate_exercises in exercises.chunk_by(|a, b| a.crate_dir == b.crate_dir) {
+            problems.extend(self.check_crate(crate_exercises)?);
+        }
+        Ok(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        // One line per string, so that the markers aren't found in this file
+        let source = [
+            "fn main() {",
+            "    let a = vec![1];",
+            "    let b = a;",
+            "    //println!(\"{:?}\", a);",
+            "    // TODO ^ Try uncommenting this line (error[E0382])",
+            "",
+            "    let tuple = (1, 2);",
+            "    // let x: (i32,) = tuple;",
+            "    // println!(\"{:?}\", x);",
+            "    // TODO ^ Uncomment the above 2 lines to see the compiler error",
+            "",
+            "    // let v = b[3];",
+            "    // FIXME ^ Comment out this line. (panics)",
+            "    //drop(tuple);",
+            "    //drop(b);",
+            "    // ^ TODO: Try uncommenting these lines.",
+            "}",
+        ]
+        .join("\n");
+        let exercises = parse(Path::new("ch15/moves"), Path::new("src/main.rs"), &source);
+        let found: Vec<_> = exercises
+            .iter()
+            .map(|e| (e.line, e.lines.clone(), e.expected.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (5, vec![3], Some(Expected::Error(Some("E0382".to_owned())))),
+                (10, vec![7, 8], None),
+                (13, vec![11], Some(Expected::Panic)),
+                // Not `// let v = b[3];`, which is above the previous marker
+                (16, vec![13, 14], None),
+            ]
+        );
+        assert_eq!(exercises[0].to_string(), "ch15/moves/src/main.rs:5");
+        assert_eq!(
+            uncomment("    //println!(\"{:?}\", a);").unwrap(),
+            "    println!(\"{:?}\", a);"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let output = "\
+src/main.rs:31:21: error[E0505]: cannot move out of `boxed_i32` because it is borrowed
+src/main.rs:40:5: error: expected expression, found `)`
+src/main.rs:3:9: warning: unused variable: `x`
+error: could not compile `borrowing` (bin \"borrowing\") due to 2 previous errors
+";
+        assert_eq!(errors(output), [Some("E0505".to_owned()), None]);
+        let outcome = Outcome::Errors(errors(output));
+        assert!(outcome.matches(&Expected::Error(Some("E0505".to_owned()))));
+        assert!(!outcome.matches(&Expected::Error(Some("E0382".to_owned()))));
+        assert_eq!(outcome.to_string(), "fails with error[E0505], error");
+    }
+}
@@ -0,0 +1,171 @@
+// Fixtures: what a test needs set up before it runs, and torn down after.
+//
+// * A `Fixture` is made for each test that asks for it, and torn down when
+//   it is dropped, at the end of the test, passing or failing. `TempDir` is
+//   one, and `guard` makes one out of any value and a teardown function.
+// * A `SharedFixture` is made once per test binary, by the first test that
+//   asks for it, and then shared, read-only, by all the others. It is never
+//   torn down, as statics aren't dropped; the process exiting does that.
+//
+// A test says which fixtures it needs with `fixture_test!`:
+//
+//     fixture_test! {
+//         fn test_fetch(server: &Backend, dir: TempDir) {
+//             ...
+//         }
+//     }
+//
+// A once-per-binary setup, like creating files shared by all the tests, is a
+// `SharedFixture` too, which `tests/common` asks for in its `setup()`.
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, OnceLock};
+
+use crate::temp_dir::TempDir;
+
+pub trait Fixture {
+    fn setup() -> Self;
+}
+
+impl Fixture for TempDir {
+    fn setup() -> TempDir {
+        TempDir::new()
+    }
+}
+
+pub trait SharedFixture: Send + Sync + 'static {
+    fn setup() -> Self;
+}
+
+// The `SharedFixture`, made by the first test that asks for it
+impl<T: SharedFixture> Fixture for &'static T {
+    fn setup() -> &'static T {
+        shared::<T>()
+    }
+}
+
+// The one `T` of the test binary. When several tests ask for it at once, one
+// makes it and the others wait.
+pub fn shared<T: SharedFixture>() -> &'static T {
+    type Cells = HashMap<TypeId, &'static (dyn Any + Send + Sync)>;
+    static CELLS: OnceLock<Mutex<Cells>> = OnceLock::new();
+
+    // Only finding the cell is done under the lock, so that making one
+    // fixture can ask for another
+    let cell = *CELLS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| Box::leak(Box::new(OnceLock::<T>::new())));
+    cell.downcast_ref::<OnceLock<T>>()
+        .unwrap()
+        .get_or_init(T::setup)
+}
+
+// A value that is torn down by `teardown` when dropped
+pub struct Guard<T, F: FnMut(&mut T)> {
+    value: T,
+    teardown: F,
+}
+
+pub fn guard<T, F: FnMut(&mut T)>(value: T, teardown: F) -> Guard<T, F> {
+    Guard { value, teardown }
+}
+
+impl<T, F: FnMut(&mut T)> Deref for Guard<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, F: FnMut(&mut T)> DerefMut for Guard<T, F> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T, F: FnMut(&mut T)> Drop for Guard<T, F> {
+    fn drop(&mut self) {
+        (self.teardown)(&mut self.value);
+    }
+}
+
+// A `#[test]` whose arguments are the fixtures it needs. They are set up in
+// order, and torn down in reverse order.
+#[macro_export]
+macro_rules! fixture_test {
+    ($(
+        $(#[$attr:meta])*
+        fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $body:block
+    )*) => {$(
+        #[test]
+        $(#[$attr])*
+        fn $name() {
+            $(let $arg = <$ty as $crate::fixture::Fixture>::setup();)*
+            $body
+        }
+    )*};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    static MADE: AtomicUsize = AtomicUsize::new(0);
+
+    struct Config(Vec<String>);
+
+    impl SharedFixture for Config {
+        fn setup() -> Config {
+            MADE.fetch_add(1, Ordering::SeqCst);
+            Config(vec!["a".to_owned(), "b".to_owned()])
+        }
+    }
+
+    // Made from another shared fixture
+    struct Count(usize);
+
+    impl SharedFixture for Count {
+        fn setup() -> Count {
+            Count(shared::<Config>().0.len())
+        }
+    }
+
+    #[test]
+    fn test_shared_once() {
+        let threads: Vec<_> = (0..8)
+            .map(|_| thread::spawn(|| shared::<Count>().0))
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 2);
+        }
+        assert!(std::ptr::eq(shared::<Config>(), shared::<Config>()));
+        assert_eq!(MADE.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_guard_on_panic() {
+        let log = Mutex::new(Vec::new());
+        let result = std::panic::catch_unwind(|| {
+            let mut first = guard(1, |n| log.lock().unwrap().push(*n));
+            let _second = guard(2, |n| log.lock().unwrap().push(*n));
+            *first += 10;
+            panic!("failing test");
+        });
+        assert!(result.is_err());
+        assert_eq!(*log.lock().unwrap(), [2, 11]);
+    }
+
+    fixture_test! {
+        fn test_declared(config: &Config, dir: TempDir) {
+            assert_eq!(config.0, ["a", "b"]);
+            assert!(dir.path().is_dir());
+        }
+    }
+}
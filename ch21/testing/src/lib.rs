@@ -1,6 +1,19 @@
 // cargo test
 // cargo test -- --ignored
 
+// Property-based testing, used by the tests below
+pub mod prop;
+// A temporary directory for each test
+pub mod temp_dir;
+// Fixtures for the integration tests, and a server for them to talk to
+pub mod fixture;
+pub mod stand_in;
+// Capturing what the examples print, and checking it against snapshots
+pub mod output;
+pub mod snapshot;
+// The exercises that are meant not to compile
+pub mod exercises;
+
 /// First line is a short summary describing function.
 ///
 /// The next lines present detailed documentation. Code blocks start with
@@ -70,6 +83,7 @@ pub fn div(a: i32, b: i32) -> i32 {
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::prop::{floats, ints};
     use pretty_assertions::assert_eq; // crate for test-only use. Cannot be used in non-test code.
 
     #[test]
@@ -114,6 +128,70 @@ mod tests {
         assert_eq!(add(2, 100), 102);
     }
 
+    // Instead of picking values by hand, let a property find them
+
+    fn config(seed: u64) -> prop::Config {
+        prop::Config {
+            seed,
+            replay: None,
+            regressions: None,
+            ..prop::Config::default()
+        }
+    }
+
+    #[test]
+    fn test_add_properties() {
+        // Small enough not to overflow
+        let small = || ints(-(1 << 30)..=(1 << 30) - 1);
+        prop::check("add_commutes", (small(), small()), |(a, b)| {
+            add(a, b) == add(b, a)
+        });
+        prop::check("add_zero", small(), |a| add(a, 0) == a);
+    }
+
+    #[test]
+    fn test_bad_add_found() {
+        // What `test_bad_add` would have shown, with the simplest values
+        let gen = (ints(-1000..=1000), ints(-1000..=1000));
+        let report = prop::run(&config(1), &gen, |(a, b)| bad_add(a, b) == a + b);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].value, (0, 1));
+    }
+
+    #[test]
+    fn test_sqrt_properties() {
+        prop::check("sqrt_squares", floats(0.0..=1e6), |x| {
+            let root = sqrt(x)?;
+            assert!((root * root - x).abs() <= 1e-9 * x.max(1.0));
+            Ok::<(), String>(())
+        });
+
+        let report = prop::run(&config(3), &floats(-1e6..=1e6), |x| sqrt(x).is_ok());
+        assert_eq!(report.failures[0].value, -1.0);
+    }
+
+    #[test]
+    fn test_divide_panics_found() {
+        // Both ways of panicking, each shrunk on its own
+        let gen = (ints(0..=u32::MAX), ints(0..=u32::MAX));
+        let report = prop::run(&config(4), &gen, |(a, b)| {
+            divide_non_zero_result(a, b);
+        });
+        let mut found: Vec<_> = report
+            .failures
+            .iter()
+            .map(|failure| (failure.value, failure.message.as_str()))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                ((0, 0), "Divide-by-zero error"),
+                ((0, 1), "Divide result is zero")
+            ]
+        );
+    }
+
     #[test]
     #[ignore]
     fn ignored_test() {
@@ -0,0 +1,88 @@
+// Running an example in its tests. Each example writes what it prints to the
+// `out` given to its `run`, which `main` calls with stdout:
+//
+//     fn main() -> io::Result<()> {
+//         run(&mut io::stdout())
+//     }
+//
+//     fn run(out: &mut impl Write) -> io::Result<()> {
+//         writeln!(out, "Hello World!")
+//     }
+//
+// and its tests with a buffer, whose contents are checked against the
+// snapshot `main`:
+//
+//     #[cfg(test)]
+//     testing::snapshot_test!(run);
+use std::io;
+use std::panic::AssertUnwindSafe;
+
+use panics::boundary::run_guarded;
+
+// What `run` writes. If it fails or panics, the output ends with a
+// `failed: <error>` or `panicked: <message>` line, so a snapshot also checks
+// how it fails.
+pub fn capture<F>(run: F) -> String
+where
+    F: FnOnce(&mut Vec<u8>) -> io::Result<()>,
+{
+    let mut out = Vec::new();
+    // Only `run` is in the boundary, and `out` holds whatever it wrote
+    let result = run_guarded(AssertUnwindSafe(|| run(&mut out)));
+    let mut output = String::from_utf8_lossy(&out).into_owned();
+    let end = match result {
+        Ok(Ok(())) => return output,
+        Ok(Err(e)) => format!("failed: {}", e),
+        Err(report) => format!("panicked: {}", report.message),
+    };
+    if !output.is_empty() && !output.ends_with('\n') {
+        output.push('\n');
+    }
+    output.push_str(&end);
+    output.push('\n');
+    output
+}
+
+// The test of an example: `run`, with the snapshot filters if any are given,
+// checked against the snapshot `main`
+//
+//     testing::snapshot_test!(run, |snapshot| snapshot.sort_lines());
+#[macro_export]
+macro_rules! snapshot_test {
+    ($run:path) => {
+        $crate::snapshot_test!($run, |snapshot| snapshot);
+    };
+    ($run:path, $filters:expr) => {
+        #[test]
+        fn test_output() {
+            let filters: fn($crate::snapshot::Snapshot) -> $crate::snapshot::Snapshot = $filters;
+            filters($crate::snapshot!("main")).assert(&$crate::output::capture($run));
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn run(out: &mut impl Write) -> io::Result<()> {
+        write!(out, "{} + {}", 1, 2)?;
+        writeln!(out, " = {}", 3)
+    }
+
+    #[test]
+    fn test_capture() {
+        assert_eq!(capture(run), "1 + 2 = 3\n");
+        let output = capture(|out| {
+            write!(out, "halfway")?;
+            panic!("done");
+        });
+        assert_eq!(output, "halfway\npanicked: done\n");
+        let output = capture(|out| {
+            writeln!(out, "Ferris")?;
+            Err(io::Error::other("no more room"))
+        });
+        assert_eq!(output, "Ferris\nfailed: no more room\n");
+    }
+}
@@ -0,0 +1,905 @@
+// Property-based testing, with nothing but std.
+//
+// Instead of a handful of hand-picked values, a property is checked against
+// many values from a generator:
+//
+//     prop::check("add_commutes", (ints(-100..=100), ints(-100..=100)), |(a, b)| {
+//         assert_eq!(add(a, b), add(b, a));
+//     });
+//
+// Generators don't make values directly, they make them from a `Source` of
+// random choices. That record of choices is what gets shrunk when a property
+// fails: choices are deleted and made smaller, and the value is made again,
+// for as long as the property keeps failing the same way. Every generator,
+// including ones built with `map`, `filter` and `flat_map`, shrinks for free,
+// and smaller choices are made to give simpler values, so the failure that
+// is reported is close to minimal.
+//
+// Runs are deterministic for a seed. A failure is reported with the seed of
+// its case, which can be replayed with `PROP_REPLAY=<seed>`, and `check`
+// keeps the seeds in `prop-regressions/<name>.txt` to try them first next
+// time.
+use std::env;
+use std::fmt::{self, Debug};
+use std::fs;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use panics::boundary::{run_guarded, PanicLocation, PanicReport};
+
+// SplitMix64, which is small, fast and good enough for testing
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // A number in `0..=max`
+    pub fn below(&mut self, max: u64) -> u64 {
+        match max.checked_add(1) {
+            Some(n) => ((self.next_u64() as u128 * n as u128) >> 64) as u64,
+            None => self.next_u64(),
+        }
+    }
+}
+
+// Generating a value shouldn't take forever, so values that need more
+// choices are rejected
+const MAX_CHOICES: usize = 10_000;
+
+// Where the choices of a generator come from: a record to replay, and after
+// it, random choices or, when shrinking, zeros
+pub struct Source {
+    prefix: Vec<u64>,
+    rng: Option<Rng>,
+    choices: Vec<u64>,
+    rejected: bool,
+}
+
+impl Source {
+    fn random(seed: u64) -> Source {
+        Source {
+            prefix: Vec::new(),
+            rng: Some(Rng::new(seed)),
+            choices: Vec::new(),
+            rejected: false,
+        }
+    }
+
+    fn replay(prefix: Vec<u64>) -> Source {
+        Source {
+            prefix,
+            rng: None,
+            choices: Vec::new(),
+            rejected: false,
+        }
+    }
+
+    // A choice in `0..=max`. Generators should make smaller choices give
+    // simpler values, as that is what shrinking goes for.
+    pub fn draw(&mut self, max: u64) -> u64 {
+        let i = self.choices.len();
+        if i >= MAX_CHOICES {
+            self.rejected = true;
+            return 0;
+        }
+        let choice = match (self.prefix.get(i), &mut self.rng) {
+            (Some(&choice), _) => choice.min(max),
+            (None, Some(rng)) => rng.below(max),
+            (None, None) => 0,
+        };
+        self.choices.push(choice);
+        choice
+    }
+
+    // Give up on this value, it doesn't count as a case
+    pub fn reject(&mut self) {
+        self.rejected = true;
+    }
+}
+
+pub trait Gen {
+    type Value;
+
+    fn generate(&self, source: &mut Source) -> Self::Value;
+
+    fn map<U, F>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Value) -> U,
+    {
+        Map { gen: self, f }
+    }
+
+    // Only values for which `f` is true. Values that are rarely kept slow
+    // everything down, so it is better to generate the right ones to begin
+    // with.
+    fn filter<F>(self, f: F) -> Filter<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&Self::Value) -> bool,
+    {
+        Filter { gen: self, f }
+    }
+
+    // A generator that depends on a generated value, like a `Vec` and an
+    // index into it
+    fn flat_map<G, F>(self, f: F) -> FlatMap<Self, F>
+    where
+        Self: Sized,
+        G: Gen,
+        F: Fn(Self::Value) -> G,
+    {
+        FlatMap { gen: self, f }
+    }
+
+    fn boxed<'a>(self) -> Box<dyn Gen<Value = Self::Value> + 'a>
+    where
+        Self: Sized + 'a,
+    {
+        Box::new(self)
+    }
+}
+
+impl<T> Gen for Box<dyn Gen<Value = T> + '_> {
+    type Value = T;
+
+    fn generate(&self, source: &mut Source) -> T {
+        (**self).generate(source)
+    }
+}
+
+pub struct Map<G, F> {
+    gen: G,
+    f: F,
+}
+
+impl<G: Gen, U, F: Fn(G::Value) -> U> Gen for Map<G, F> {
+    type Value = U;
+
+    fn generate(&self, source: &mut Source) -> U {
+        (self.f)(self.gen.generate(source))
+    }
+}
+
+pub struct Filter<G, F> {
+    gen: G,
+    f: F,
+}
+
+impl<G: Gen, F: Fn(&G::Value) -> bool> Gen for Filter<G, F> {
+    type Value = G::Value;
+
+    fn generate(&self, source: &mut Source) -> G::Value {
+        for _ in 0..100 {
+            let value = self.gen.generate(source);
+            if (self.f)(&value) {
+                return value;
+            }
+        }
+        source.reject();
+        self.gen.generate(source)
+    }
+}
+
+pub struct FlatMap<G, F> {
+    gen: G,
+    f: F,
+}
+
+impl<G: Gen, H: Gen, F: Fn(G::Value) -> H> Gen for FlatMap<G, F> {
+    type Value = H::Value;
+
+    fn generate(&self, source: &mut Source) -> H::Value {
+        (self.f)(self.gen.generate(source)).generate(source)
+    }
+}
+
+// Always `value`
+pub struct Just<T>(T);
+
+pub fn just<T: Clone>(value: T) -> Just<T> {
+    Just(value)
+}
+
+impl<T: Clone> Gen for Just<T> {
+    type Value = T;
+
+    fn generate(&self, _: &mut Source) -> T {
+        self.0.clone()
+    }
+}
+
+// A value of one of `gens`, which shrinks towards the first
+pub struct OneOf<'a, T>(Vec<Box<dyn Gen<Value = T> + 'a>>);
+
+pub fn one_of<T>(gens: Vec<Box<dyn Gen<Value = T> + '_>>) -> OneOf<'_, T> {
+    assert!(!gens.is_empty(), "`one_of` needs at least one generator");
+    OneOf(gens)
+}
+
+impl<T> Gen for OneOf<'_, T> {
+    type Value = T;
+
+    fn generate(&self, source: &mut Source) -> T {
+        let i = source.draw(self.0.len() as u64 - 1);
+        self.0[i as usize].generate(source)
+    }
+}
+
+pub struct Bools;
+
+pub fn bools() -> Bools {
+    Bools
+}
+
+impl Gen for Bools {
+    type Value = bool;
+
+    fn generate(&self, source: &mut Source) -> bool {
+        source.draw(1) == 1
+    }
+}
+
+// The integer types, through `i128`, which holds all their values
+pub trait Int: Copy + Debug {
+    fn to_i128(self) -> i128;
+    fn from_i128(n: i128) -> Self;
+}
+
+macro_rules! int {
+    ($($t:ty)*) => {$(
+        impl Int for $t {
+            fn to_i128(self) -> i128 {
+                self as i128
+            }
+
+            fn from_i128(n: i128) -> $t {
+                n as $t
+            }
+        }
+    )*};
+}
+
+int!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize);
+
+pub struct Ints<T> {
+    lo: i128,
+    hi: i128,
+    marker: std::marker::PhantomData<T>,
+}
+
+// Integers in `range`, which shrink towards 0, or the end of the range
+// closest to it. Small numbers and the ends of the range come up more often
+// than they would by chance, as that is where the bugs are.
+pub fn ints<T: Int>(range: RangeInclusive<T>) -> Ints<T> {
+    let (lo, hi) = (range.start().to_i128(), range.end().to_i128());
+    assert!(lo <= hi, "empty range {:?}", range);
+    Ints {
+        lo,
+        hi,
+        marker: std::marker::PhantomData,
+    }
+}
+
+// An integer in `lo..=hi`, from a size, a sign and a magnitude. The ends of
+// the range, size 3, take as many choices as any other number, so that
+// shrinking doesn't see them as simpler, and record their magnitude, so that
+// shrinking can make it smaller from there.
+fn draw_int(source: &mut Source, lo: i128, hi: i128) -> i128 {
+    let origin = 0.clamp(lo, hi);
+    let size = source.draw(3);
+    let (up, down) = (hi - origin, origin - lo);
+    let negative = match (up, down) {
+        (_, 0) => false,
+        (0, _) => true,
+        _ => source.draw(1) == 1,
+    };
+    let most = if negative { down } else { up };
+    let limit: i128 = match size {
+        0 => 16,
+        1 => 1 << 16,
+        _ => u64::MAX as i128,
+    };
+    let mut magnitude = source.draw(most.min(limit) as u64) as i128;
+    if size == 3 {
+        magnitude = most;
+        *source.choices.last_mut().unwrap() = most as u64;
+    }
+    if negative {
+        origin - magnitude
+    } else {
+        origin + magnitude
+    }
+}
+
+impl<T: Int> Gen for Ints<T> {
+    type Value = T;
+
+    fn generate(&self, source: &mut Source) -> T {
+        T::from_i128(draw_int(source, self.lo, self.hi))
+    }
+}
+
+pub struct Floats {
+    lo: f64,
+    hi: f64,
+}
+
+// Finite floats in `range`, a whole number, which shrinks like `ints`, and
+// a fraction, which shrinks away
+pub fn floats(range: RangeInclusive<f64>) -> Floats {
+    let (lo, hi) = range.into_inner();
+    assert!(
+        lo.is_finite() && hi.is_finite() && lo <= hi,
+        "`floats` needs a finite, non-empty range"
+    );
+    Floats { lo, hi }
+}
+
+impl Gen for Floats {
+    type Value = f64;
+
+    fn generate(&self, source: &mut Source) -> f64 {
+        const BITS: u64 = 1 << 53;
+        let (lo, hi) = (self.lo.ceil(), self.hi.floor());
+        // Past `i64` every float is a whole number anyway
+        if lo > hi || lo < i64::MIN as f64 || hi > i64::MAX as f64 {
+            let fraction = source.draw(BITS) as f64 / BITS as f64;
+            return (self.lo + fraction * (self.hi - self.lo)).clamp(self.lo, self.hi);
+        }
+        let whole = draw_int(source, lo as i128, hi as i128) as f64;
+        if source.draw(1) == 0 {
+            return whole;
+        }
+        let fraction = source.draw(BITS) as f64 / BITS as f64;
+        // Away from 0, unless that is out of range
+        let (away, back) = if whole < 0.0 {
+            (whole - fraction, whole + fraction)
+        } else {
+            (whole + fraction, whole - fraction)
+        };
+        let x = if (self.lo..=self.hi).contains(&away) {
+            away
+        } else {
+            back
+        };
+        x.clamp(self.lo, self.hi)
+    }
+}
+
+pub struct Chars {
+    start: u32,
+    end: u32,
+}
+
+// Chars in `range`, which shrink towards its start
+pub fn chars(range: RangeInclusive<char>) -> Chars {
+    let (start, end) = range.into_inner();
+    assert!(start <= end, "empty range {:?}..={:?}", start, end);
+    Chars {
+        start: start as u32,
+        end: end as u32,
+    }
+}
+
+impl Gen for Chars {
+    type Value = char;
+
+    fn generate(&self, source: &mut Source) -> char {
+        let offset = source.draw((self.end - self.start) as u64) as u32;
+        // The surrogates in the middle of the range aren't chars
+        char::from_u32(self.start + offset).unwrap_or(char::from_u32(self.start).unwrap())
+    }
+}
+
+pub struct Vecs<G> {
+    gen: G,
+    min: usize,
+    max: usize,
+}
+
+// `Vec`s of values from `gen`, with a length in `len`, which shrink by
+// losing elements as well as by shrinking them
+pub fn vecs<G: Gen>(gen: G, len: RangeInclusive<usize>) -> Vecs<G> {
+    let (min, max) = len.into_inner();
+    assert!(min <= max, "empty range {}..={}", min, max);
+    Vecs { gen, min, max }
+}
+
+impl<G: Gen> Gen for Vecs<G> {
+    type Value = Vec<G::Value>;
+
+    fn generate(&self, source: &mut Source) -> Vec<G::Value> {
+        let mut values = Vec::new();
+        while values.len() < self.max {
+            // Each element past the minimum has a choice of its own before
+            // it, so that it can be deleted with it. 0, which shrinking
+            // goes for, is the end.
+            if values.len() >= self.min && source.draw(7) == 0 {
+                break;
+            }
+            values.push(self.gen.generate(source));
+        }
+        values
+    }
+}
+
+pub struct Strings<G> {
+    chars: Vecs<G>,
+}
+
+// Strings of chars from `chars`, with a length in `len`
+pub fn strings<G: Gen<Value = char>>(chars: G, len: RangeInclusive<usize>) -> Strings<G> {
+    Strings {
+        chars: vecs(chars, len),
+    }
+}
+
+impl<G: Gen<Value = char>> Gen for Strings<G> {
+    type Value = String;
+
+    fn generate(&self, source: &mut Source) -> String {
+        self.chars.generate(source).into_iter().collect()
+    }
+}
+
+macro_rules! tuple {
+    ($($g:ident)*) => {
+        impl<$($g: Gen),*> Gen for ($($g,)*) {
+            type Value = ($($g::Value,)*);
+
+            #[allow(non_snake_case)]
+            fn generate(&self, source: &mut Source) -> Self::Value {
+                let ($($g,)*) = self;
+                ($($g.generate(source),)*)
+            }
+        }
+    };
+}
+
+tuple!(A);
+tuple!(A B);
+tuple!(A B C);
+tuple!(A B C D);
+tuple!(A B C D E);
+
+// What a property returns: nothing, as it panics to fail, or whether it held
+pub trait Outcome {
+    fn check(self);
+}
+
+impl Outcome for () {
+    fn check(self) {}
+}
+
+impl Outcome for bool {
+    fn check(self) {
+        assert!(self, "property returned false");
+    }
+}
+
+impl<E: Debug> Outcome for Result<(), E> {
+    fn check(self) {
+        if let Err(e) = self {
+            panic!("property returned an error: {:?}", e);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    // How many values to try
+    pub cases: u32,
+    pub seed: u64,
+    // How many times a failing value may be made again while shrinking
+    pub max_shrinks: u32,
+    // Only run the case with this seed
+    pub replay: Option<u64>,
+    // Where failing seeds are kept
+    pub regressions: Option<PathBuf>,
+}
+
+fn env_number(name: &str) -> Option<u64> {
+    let value = env::var(name).ok()?;
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    Some(parsed.unwrap_or_else(|_| panic!("{}={} isn't a number", name, value)))
+}
+
+impl Default for Config {
+    // 256 cases with a new seed each time, unless `PROP_CASES`, `PROP_SEED`
+    // or `PROP_REPLAY` say otherwise
+    fn default() -> Config {
+        let seed = env_number("PROP_SEED").unwrap_or_else(|| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            Rng::new(now.as_nanos() as u64 ^ std::process::id() as u64).next_u64()
+        });
+        Config {
+            cases: env_number("PROP_CASES").map_or(256, |cases| cases as u32),
+            seed,
+            max_shrinks: 10_000,
+            replay: env_number("PROP_REPLAY"),
+            regressions: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Failure<T> {
+    // The shrunk value
+    pub value: T,
+    // The seed of the case that first failed, for `PROP_REPLAY`
+    pub seed: u64,
+    pub message: String,
+    pub location: Option<PanicLocation>,
+    // How many smaller values were found on the way
+    pub shrinks: u32,
+}
+
+#[derive(Debug)]
+pub struct Report<T> {
+    pub seed: u64,
+    pub passed: u32,
+    pub rejected: u32,
+    // One for each different way the property failed, by where it panicked
+    pub failures: Vec<Failure<T>>,
+}
+
+impl<T> Report<T> {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl<T: Debug> fmt::Display for Report<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} passed, {} rejected, {} failed (seed {:#x})",
+            self.passed,
+            self.rejected,
+            self.failures.len(),
+            self.seed
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n\nminimal failing input: {:?}", failure.value)?;
+            match &failure.location {
+                Some(location) => write!(f, "\npanicked at {}:", location)?,
+                None => write!(f, "\npanicked:")?,
+            }
+            write!(f, "\n{}", failure.message)?;
+            write!(
+                f,
+                "\nafter {} shrinks, replay with PROP_REPLAY={:#x}",
+                failure.shrinks, failure.seed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Failures are the same when they panic in the same place, whatever the
+// message says about the value
+fn same_failure(a: &PanicReport, b: &PanicReport) -> bool {
+    match (&a.location, &b.location) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.message == b.message,
+    }
+}
+
+// Smaller records first by length, then by their choices
+fn simpler(a: &[u64], b: &[u64]) -> bool {
+    (a.len(), a) < (b.len(), b)
+}
+
+struct Runner<'a, G, P> {
+    gen: &'a G,
+    prop: &'a P,
+    shrinks_left: u32,
+}
+
+impl<G, R, P> Runner<'_, G, P>
+where
+    G: Gen,
+    R: Outcome,
+    P: Fn(G::Value) -> R,
+{
+    // Make a value from `source` and check it, unless it was rejected
+    fn run(&self, mut source: Source) -> Option<(Vec<u64>, Result<(), PanicReport>)> {
+        let value = self.gen.generate(&mut source);
+        if source.rejected {
+            return None;
+        }
+        let result = run_guarded(AssertUnwindSafe(|| (self.prop)(value).check()));
+        Some((source.choices, result))
+    }
+
+    // A simpler record than `best` that fails like `failure`, if `candidate`
+    // is one
+    fn attempt(
+        &mut self,
+        candidate: Vec<u64>,
+        best: &[u64],
+        failure: &PanicReport,
+    ) -> Option<(Vec<u64>, PanicReport)> {
+        if self.shrinks_left == 0 {
+            return None;
+        }
+        self.shrinks_left -= 1;
+        match self.run(Source::replay(candidate))? {
+            (choices, Err(report)) if same_failure(&report, failure) && simpler(&choices, best) => {
+                Some((choices, report))
+            }
+            _ => None,
+        }
+    }
+
+    fn shrink(
+        &mut self,
+        mut best: Vec<u64>,
+        mut failure: PanicReport,
+    ) -> (Vec<u64>, PanicReport, u32) {
+        let mut shrinks = 0;
+        let mut improved = true;
+        while improved && self.shrinks_left > 0 {
+            improved = false;
+
+            // Delete runs of choices, like an element of a `Vec` and the
+            // choice before it
+            for size in [8, 4, 2, 1] {
+                let mut i = best.len();
+                while i > 0 {
+                    i -= 1;
+                    if i + size > best.len() {
+                        continue;
+                    }
+                    let mut candidate = best.clone();
+                    candidate.drain(i..i + size);
+                    if let Some((choices, report)) = self.attempt(candidate, &best, &failure) {
+                        (best, failure) = (choices, report);
+                        shrinks += 1;
+                        improved = true;
+                    }
+                }
+            }
+
+            // Make each choice as small as it can be, by bisection
+            let mut i = 0;
+            while i < best.len() {
+                let mut lo = 0;
+                while i < best.len() && lo < best[i] {
+                    let mid = lo + (best[i] - lo) / 2;
+                    let mut candidate = best.clone();
+                    candidate[i] = mid;
+                    match self.attempt(candidate, &best, &failure) {
+                        Some((choices, report)) => {
+                            (best, failure) = (choices, report);
+                            shrinks += 1;
+                            improved = true;
+                        }
+                        None if self.shrinks_left == 0 => break,
+                        None => lo = mid + 1,
+                    }
+                }
+                i += 1;
+            }
+        }
+        (best, failure, shrinks)
+    }
+}
+
+// The case seeds kept in a regressions file, one per line, with a comment
+fn load_seeds(path: &Path) -> Vec<u64> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| {
+            let seed = line.split('#').next()?.trim().strip_prefix("0x")?;
+            u64::from_str_radix(seed, 16).ok()
+        })
+        .collect()
+}
+
+fn save_seed<T: Debug>(path: &Path, failure: &Failure<T>) {
+    if load_seeds(path).contains(&failure.seed) {
+        return;
+    }
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let file = fs::OpenOptions::new().create(true).append(true).open(path);
+    if let Ok(mut file) = file {
+        let _ = writeln!(file, "{:#x} # shrinks to {:?}", failure.seed, failure.value);
+    }
+}
+
+// Check `prop` against values from `gen`, and report every different way it
+// failed, each shrunk on its own
+pub fn run<G, R, P>(config: &Config, gen: &G, prop: P) -> Report<G::Value>
+where
+    G: Gen,
+    G::Value: Debug,
+    R: Outcome,
+    P: Fn(G::Value) -> R,
+{
+    let mut runner = Runner {
+        gen,
+        prop: &prop,
+        shrinks_left: config.max_shrinks,
+    };
+    let mut report = Report {
+        seed: config.seed,
+        passed: 0,
+        rejected: 0,
+        failures: Vec::new(),
+    };
+    let mut seen: Vec<PanicReport> = Vec::new();
+
+    // Known failures first, then new cases, giving up on values that keep
+    // being rejected
+    let mut seeds: Vec<u64> = match (config.replay, &config.regressions) {
+        (Some(seed), _) => vec![seed],
+        (None, Some(path)) => load_seeds(path),
+        (None, None) => Vec::new(),
+    };
+    let known = seeds.len();
+    if config.replay.is_none() {
+        let mut rng = Rng::new(config.seed);
+        seeds.extend((0..config.cases.saturating_mul(10)).map(|_| rng.next_u64()));
+    }
+    for (i, &seed) in seeds.iter().enumerate() {
+        if i >= known && report.passed + seen.len() as u32 >= config.cases {
+            break;
+        }
+        let Some((choices, result)) = runner.run(Source::random(seed)) else {
+            report.rejected += 1;
+            continue;
+        };
+        let Err(failure) = result else {
+            report.passed += 1;
+            continue;
+        };
+        if seen.iter().any(|seen| same_failure(seen, &failure)) {
+            continue;
+        }
+        seen.push(PanicReport {
+            message: failure.message.clone(),
+            location: failure.location.clone(),
+            thread: None,
+            backtrace: None,
+        });
+        let (choices, failure, shrinks) = runner.shrink(choices, failure);
+        let failure = Failure {
+            value: gen.generate(&mut Source::replay(choices)),
+            seed,
+            message: failure.message,
+            location: failure.location,
+            shrinks,
+        };
+        if let Some(path) = &config.regressions {
+            save_seed(path, &failure);
+        }
+        report.failures.push(failure);
+    }
+    report
+}
+
+// Check a property in a test, and panic with the report if it fails.
+// Failing seeds are kept in `prop-regressions/<name>.txt` of the crate.
+pub fn check<G, R, P>(name: &str, gen: G, prop: P)
+where
+    G: Gen,
+    G::Value: Debug,
+    R: Outcome,
+    P: Fn(G::Value) -> R,
+{
+    let dir = env::var_os("CARGO_MANIFEST_DIR").map_or_else(PathBuf::new, PathBuf::from);
+    let config = Config {
+        regressions: Some(dir.join("prop-regressions").join(format!("{}.txt", name))),
+        ..Config::default()
+    };
+    let report = run(&config, &gen, prop);
+    if !report.is_ok() {
+        panic!("property `{}` failed: {}", name, report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64) -> Config {
+        Config {
+            cases: 256,
+            seed,
+            max_shrinks: 10_000,
+            replay: None,
+            regressions: None,
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let gen = (
+            ints(0..=u32::MAX),
+            floats(-1.0..=1.0),
+            strings(chars('a'..='z'), 0..=8),
+        );
+        let values = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..20)
+                .map(|_| gen.generate(&mut Source::random(rng.next_u64())))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(7), values(7));
+        assert_ne!(values(7), values(8));
+        for (_, x, s) in values(7) {
+            assert!((-1.0..=1.0).contains(&x));
+            assert!(s.len() <= 8 && s.chars().all(|c| c.is_ascii_lowercase()));
+        }
+    }
+
+    #[test]
+    fn test_shrinks_vec() {
+        let gen = vecs(ints(0..=1000u32), 0..=20);
+        let report = run(&config(1), &gen, |xs| xs.iter().sum::<u32>() < 100);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].value, [100]);
+    }
+
+    #[test]
+    fn test_filter_and_flat_map() {
+        // A non-empty `Vec` and an index into it
+        let gen = vecs(ints(-5..=5i8), 1..=10)
+            .flat_map(|xs| (ints(0..=xs.len() - 1), just(xs)))
+            .filter(|(i, _)| i % 2 == 0);
+        let report = run(&config(2), &gen, |(i, xs)| xs[i] != 3);
+        assert_eq!(report.failures[0].value, (0, vec![3]));
+        assert_eq!(report.failures[0].message, "property returned false");
+    }
+
+    #[test]
+    fn test_regressions() {
+        let path = env::temp_dir().join(format!("prop-regressions-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let gen = ints(0..=1_000_000u32);
+        let mut config = Config {
+            regressions: Some(path.clone()),
+            ..config(5)
+        };
+        let report = run(&config, &gen, |n| n < 500_000);
+        let seed = report.failures[0].seed;
+        assert_eq!(load_seeds(&path), [seed]);
+
+        // Without any new cases, the kept seed still finds it, and so does
+        // replaying it
+        config.cases = 0;
+        let report = run(&config, &gen, |n| n < 500_000);
+        assert_eq!(report.failures[0].value, 500_000);
+        config.regressions = None;
+        config.replay = Some(seed);
+        assert_eq!(run(&config, &gen, |n| n < 500_000).failures[0].seed, seed);
+        fs::remove_file(&path).unwrap();
+    }
+}
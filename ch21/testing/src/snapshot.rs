@@ -0,0 +1,296 @@
+// Snapshots: output checked against a file kept next to the tests.
+//
+//     let output = output::capture(run);
+//     snapshot!("main").assert(&output);
+//
+// compares the output with `snapshots/main.snap` of the crate, and fails with
+// a line diff if they differ. Running the tests with `UPDATE_SNAPSHOTS=1`
+// writes the snapshots instead, new ones included; review them with
+// `git diff` before committing.
+//
+// Output that changes from run to run is made stable first. `redact` replaces
+// text, `filter` does anything to it, and `sort_lines`, `sort_runs` and
+// `sort_items` put what threads or hash maps print in an order that doesn't
+// depend on which thread ran first or on the hashes.
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+type Filter = Box<dyn Fn(&str) -> String>;
+
+pub struct Snapshot {
+    path: PathBuf,
+    filters: Vec<Filter>,
+}
+
+// The snapshot called `name` in the `snapshots` directory of the crate
+#[macro_export]
+macro_rules! snapshot {
+    ($name:expr) => {
+        $crate::snapshot::Snapshot::new(
+            ::std::concat!(::std::env!("CARGO_MANIFEST_DIR"), "/snapshots"),
+            $name,
+        )
+    };
+}
+
+impl Snapshot {
+    pub fn new<P: AsRef<Path>>(dir: P, name: &str) -> Snapshot {
+        Snapshot {
+            path: dir.as_ref().join(format!("{}.snap", name)),
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Replace every `from` with `to`
+    pub fn redact(self, from: &str, to: &str) -> Snapshot {
+        let (from, to) = (from.to_owned(), to.to_owned());
+        self.filter(move |output| output.replace(&from, &to))
+    }
+
+    // Filters run in the order they were added
+    pub fn filter<F: Fn(&str) -> String + 'static>(mut self, filter: F) -> Snapshot {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    pub fn sort_lines(self) -> Snapshot {
+        self.sort_runs(|_| true)
+    }
+
+    // Sort each run of lines for which `pred` is true, like the lines of a
+    // `HashMap` printed in a loop. The line endings stay where they were.
+    pub fn sort_runs<P: Fn(&str) -> bool + 'static>(self, pred: P) -> Snapshot {
+        self.filter(move |output| {
+            fn push_sorted(sorted: &mut String, run: &mut Vec<(&str, &str)>) {
+                let mut texts: Vec<_> = run.iter().map(|&(text, _)| text).collect();
+                texts.sort_unstable();
+                for (text, &(_, end)) in texts.into_iter().zip(run.iter()) {
+                    sorted.push_str(text);
+                    sorted.push_str(end);
+                }
+                run.clear();
+            }
+
+            let mut sorted = String::new();
+            let mut run = Vec::new();
+            for (text, end) in split_lines(output) {
+                if pred(text) {
+                    run.push((text, end));
+                    continue;
+                }
+                push_sorted(&mut sorted, &mut run);
+                sorted.push_str(text);
+                sorted.push_str(end);
+            }
+            push_sorted(&mut sorted, &mut run);
+            sorted
+        })
+    }
+
+    // Sort the comma separated items between the brackets of the lines for
+    // which `pred` is true, like a `HashSet` printed with `{:?}`, so
+    // `A: {3, 1, 2}` becomes `A: {1, 2, 3}`
+    pub fn sort_items<P: Fn(&str) -> bool + 'static>(self, pred: P) -> Snapshot {
+        self.filter(move |output| {
+            split_lines(output)
+                .map(
+                    |(line, end)| match (line.find(['[', '{']), line.rfind([']', '}'])) {
+                        (Some(open), Some(close)) if open < close && pred(line) => {
+                            let mut items: Vec<_> = line[open + 1..close].split(", ").collect();
+                            items.sort_unstable();
+                            format!(
+                                "{}{}{}{}",
+                                &line[..=open],
+                                items.join(", "),
+                                &line[close..],
+                                end
+                            )
+                        }
+                        _ => format!("{}{}", line, end),
+                    },
+                )
+                .collect()
+        })
+    }
+
+    // The output with the filters applied
+    fn filtered(&self, output: &str) -> String {
+        self.filters
+            .iter()
+            .fold(output.to_owned(), |output, filter| filter(&output))
+    }
+
+    #[track_caller]
+    pub fn assert(&self, actual: &str) {
+        let actual = self.filtered(actual);
+        let update = env::var_os("UPDATE_SNAPSHOTS").is_some_and(|value| value != "0");
+
+        let expected = match fs::read_to_string(&self.path) {
+            Ok(expected) => Some(expected),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !update => panic!(
+                "there is no snapshot {}, run the tests with UPDATE_SNAPSHOTS=1 to write it",
+                self.path.display()
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => panic!("can't read {}: {}", self.path.display(), e),
+        };
+        if expected.as_deref() == Some(&actual) {
+            return;
+        }
+        if update {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)
+                    .unwrap_or_else(|e| panic!("can't create {}: {}", parent.display(), e));
+            }
+            fs::write(&self.path, &actual)
+                .unwrap_or_else(|e| panic!("can't write {}: {}", self.path.display(), e));
+            eprintln!("updated {}", self.path.display());
+            return;
+        }
+        panic!(
+            "the output doesn't match {}, run the tests with UPDATE_SNAPSHOTS=1 to update it\n{}",
+            self.path.display(),
+            diff(&expected.unwrap(), &actual)
+        );
+    }
+}
+
+// The lines of `output`, each with its line ending apart: `"\n"`, `"\r\n"`, or
+// `""` for a last line without one
+fn split_lines(output: &str) -> impl Iterator<Item = (&str, &str)> {
+    output.split_inclusive('\n').map(|line| {
+        let text = match line.strip_suffix('\n') {
+            Some(text) => text.strip_suffix('\r').unwrap_or(text),
+            None => line,
+        };
+        (text, &line[text.len()..])
+    })
+}
+
+// How many unchanged lines are shown around a change
+const CONTEXT: usize = 2;
+
+// A line diff of the snapshot and the output: `-` for lines only in the
+// snapshot, `+` for lines only in the output. Long runs of unchanged lines
+// are left out.
+pub fn diff(expected: &str, actual: &str) -> String {
+    // Split on '\n' only, so that a missing last newline or a '\r' shows
+    let expected: Vec<_> = expected.split('\n').collect();
+    let actual: Vec<_> = actual.split('\n').collect();
+
+    // The longest common subsequence of every pair of suffixes
+    let mut common = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            lines.push((' ', expected[i]));
+            i += 1;
+            j += 1;
+        } else if j == actual.len() || (i < expected.len() && common[i + 1][j] >= common[i][j + 1])
+        {
+            lines.push(('-', expected[i]));
+            i += 1;
+        } else {
+            lines.push(('+', actual[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<_> = lines.iter().map(|&(sign, _)| sign != ' ').collect();
+    let near_change = |k: usize| {
+        let from = k.saturating_sub(CONTEXT);
+        let to = (k + CONTEXT + 1).min(lines.len());
+        changed[from..to].iter().any(|&changed| changed)
+    };
+    let mut output = String::new();
+    let mut skipped = 0;
+    for (k, (sign, line)) in lines.iter().enumerate() {
+        if !near_change(k) {
+            skipped += 1;
+            continue;
+        }
+        if skipped > 0 {
+            output.push_str(&format!("  ... {} unchanged lines\n", skipped));
+            skipped = 0;
+        }
+        output.push_str(&format!("{} {:?}\n", sign, line));
+    }
+    if skipped > 0 {
+        output.push_str(&format!("  ... {} unchanged lines\n", skipped));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn test_diff() {
+        let expected = "a\nb\nc\nd\ne\nf\ng\n";
+        let actual = "a\nb\nc\nd\nE\nf\ng\nh\n";
+        assert_eq!(
+            diff(expected, actual),
+            "  ... 2 unchanged lines\n  \"c\"\n  \"d\"\n- \"e\"\n+ \"E\"\n  \"f\"\n  \"g\"\n+ \"h\"\n  \"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let dir = TempDir::new();
+        dir.write("threads.snap", "thread 0\nthread 1\nthread id ?\n");
+        Snapshot::new(dir.path(), "threads")
+            .redact("ThreadId(7)", "?")
+            .sort_lines()
+            .assert("thread id ThreadId(7)\nthread 1\nthread 0\n");
+    }
+
+    #[test]
+    fn test_sorting() {
+        let dir = TempDir::new();
+        dir.write("sets.snap", "A: {1, 2, 3}\nCalling a\nCalling b\nend\n");
+        Snapshot::new(dir.path(), "sets")
+            .sort_items(|line| line.starts_with("A: "))
+            .sort_runs(|line| line.starts_with("Calling "))
+            .assert("A: {3, 1, 2}\nCalling b\nCalling a\nend\n");
+
+        // The line endings are kept as they were
+        let sort = Snapshot::new(dir.path(), "sets")
+            .sort_items(|line| line.starts_with("A: "))
+            .sort_runs(|line| line.starts_with("Calling "));
+        assert_eq!(
+            sort.filtered("A: {3, 1, 2}\r\nCalling b\r\nCalling a"),
+            "A: {1, 2, 3}\r\nCalling a\r\nCalling b"
+        );
+        assert_eq!(
+            sort.filtered("Calling b\nCalling a\r\n"),
+            "Calling a\nCalling b\r\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "- \"thread 1\"\n+ \"thread 2\"")]
+    fn test_mismatch() {
+        let dir = TempDir::new();
+        dir.write("threads.snap", "thread 0\nthread 1\n");
+        Snapshot::new(dir.path(), "threads").assert("thread 0\nthread 2\n");
+    }
+}
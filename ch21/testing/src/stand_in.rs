@@ -0,0 +1,152 @@
+// A local TCP server that stands in for a real one in tests. It is scripted
+// with request/response pairs, a line each:
+//
+//     let server = StandIn::start(&[("GET /users/1", "ferris"), ("PING", "PONG")])?;
+//     let mut stream = TcpStream::connect(server.addr())?;
+//
+// It listens on an ephemeral port of 127.0.0.1, so tests running at the same
+// time don't fight over ports. Each connection gets a thread, and may send
+// any number of requests. A request that isn't in the script gets
+// `ERR unknown request: ...` back. Every request is logged, for tests to
+// check what was sent.
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+struct Shared {
+    script: Vec<(String, String)>,
+    log: Mutex<Vec<String>>,
+    stopping: AtomicBool,
+}
+
+impl Shared {
+    fn respond(&self, request: &str) -> String {
+        self.log.lock().unwrap().push(request.to_owned());
+        match self.script.iter().find(|(expected, _)| expected == request) {
+            Some((_, response)) => response.clone(),
+            None => format!("ERR unknown request: {}", request),
+        }
+    }
+}
+
+pub struct StandIn {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    acceptor: Option<JoinHandle<()>>,
+}
+
+fn serve(shared: &Shared, stream: TcpStream) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        let request = line.trim_end_matches('\r');
+        writeln!(writer, "{}", shared.respond(request))?;
+    }
+    Ok(())
+}
+
+impl StandIn {
+    pub fn start(script: &[(&str, &str)]) -> io::Result<StandIn> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            script: script
+                .iter()
+                .map(|&(request, response)| (request.to_owned(), response.to_owned()))
+                .collect(),
+            log: Mutex::new(Vec::new()),
+            stopping: AtomicBool::new(false),
+        });
+
+        let acceptor = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name(format!("stand-in {}", addr))
+                .spawn(move || {
+                    for stream in listener.incoming() {
+                        if shared.stopping.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        let Ok(stream) = stream else { continue };
+                        let shared = Arc::clone(&shared);
+                        // A client that hangs up early is its own problem
+                        thread::spawn(move || serve(&shared, stream));
+                    }
+                })?
+        };
+        Ok(StandIn {
+            addr,
+            shared,
+            acceptor: Some(acceptor),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // Send one request on a new connection, and return the response
+    pub fn request(&self, request: &str) -> io::Result<String> {
+        let mut stream = TcpStream::connect(self.addr)?;
+        writeln!(stream, "{}", request)?;
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response)?;
+        Ok(response.trim_end_matches('\n').to_owned())
+    }
+
+    // The requests received so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.shared.log.lock().unwrap().clone()
+    }
+}
+
+// Stop accepting connections. Ones that are open are served until the client
+// hangs up.
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        self.shared.stopping.store(true, Ordering::SeqCst);
+        // Wake the acceptor up, so it sees it is stopping
+        let _ = TcpStream::connect(self.addr);
+        if let Some(acceptor) = self.acceptor.take() {
+            let _ = acceptor.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script() -> io::Result<()> {
+        let server = StandIn::start(&[("PING", "PONG"), ("GET /users/1", "ferris")])?;
+
+        // Several requests on one connection, with any line endings
+        let mut stream = TcpStream::connect(server.addr())?;
+        write!(stream, "GET /users/1\r\nPING\nDELETE /users/1\n")?;
+        stream.shutdown(std::net::Shutdown::Write)?;
+        let lines: Vec<String> = BufReader::new(stream).lines().collect::<io::Result<_>>()?;
+        assert_eq!(
+            lines,
+            ["ferris", "PONG", "ERR unknown request: DELETE /users/1"]
+        );
+
+        assert_eq!(server.request("PING")?, "PONG");
+        assert_eq!(
+            server.requests(),
+            ["GET /users/1", "PING", "DELETE /users/1", "PING"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_stops() -> io::Result<()> {
+        let server = StandIn::start(&[])?;
+        let addr = server.addr();
+        drop(server);
+        assert!(TcpStream::connect(addr).is_err());
+        Ok(())
+    }
+}
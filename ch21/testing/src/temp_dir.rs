@@ -0,0 +1,237 @@
+// A temporary directory for each test, so that tests running in parallel
+// don't write over each other's files:
+//
+//     let dir = TempDir::new();
+//     fs::write(dir.join("ferris.txt"), "Ferris\n")?;
+//     dir.assert_file("ferris.txt", "Ferris\n");
+//
+// The directory is removed when the `TempDir` is dropped, even when the test
+// fails. To look at what a failing test left behind, set `KEEP_TEST_DIRS`:
+// any value keeps the directories of failing tests, and `all` keeps every
+// one. The ones that are kept are printed.
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl Keep {
+    // What `KEEP_TEST_DIRS` asks for
+    pub fn from_env() -> Keep {
+        match env::var("KEEP_TEST_DIRS") {
+            Err(_) => Keep::Never,
+            Ok(value) if value == "all" => Keep::Always,
+            Ok(_) => Keep::OnFailure,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+    keep: Keep,
+}
+
+// Test names like `tests::test_file`, as a directory name
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl TempDir {
+    // A directory named after the test, which is the name of the thread it
+    // runs on
+    #[track_caller]
+    pub fn new() -> TempDir {
+        let name = thread::current().name().unwrap_or("test").to_owned();
+        TempDir::with_name(&name).unwrap_or_else(|e| panic!("can't create a temp dir: {}", e))
+    }
+
+    // A directory named after `name`, made unique with the process id and a
+    // counter
+    pub fn with_name(name: &str) -> io::Result<TempDir> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        loop {
+            let path = env::temp_dir().join(format!(
+                "{}-{}-{}",
+                sanitize(name),
+                process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            // Left over from an earlier process with the same id
+            match fs::create_dir(&path) {
+                Ok(()) => {
+                    return Ok(TempDir {
+                        path,
+                        keep: Keep::from_env(),
+                    })
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.path.join(path)
+    }
+
+    // Overrides `KEEP_TEST_DIRS`
+    pub fn set_keep(&mut self, keep: Keep) {
+        self.keep = keep;
+    }
+
+    // Write a file, with any directories it needs
+    #[track_caller]
+    pub fn write<P: AsRef<Path>>(&self, path: P, contents: &str) {
+        let path = self.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .unwrap_or_else(|e| panic!("can't create {}: {}", parent.display(), e));
+        }
+        fs::write(&path, contents)
+            .unwrap_or_else(|e| panic!("can't write {}: {}", path.display(), e));
+    }
+
+    #[track_caller]
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> String {
+        let path = self.join(path);
+        fs::read_to_string(&path).unwrap_or_else(|e| panic!("can't read {}: {}", path.display(), e))
+    }
+
+    // The file must hold exactly `expected`. The panic shows the first line
+    // that differs.
+    #[track_caller]
+    pub fn assert_file<P: AsRef<Path>>(&self, path: P, expected: &str) {
+        let actual = self.read(&path);
+        if actual == expected {
+            return;
+        }
+        // Split on '\n' only, so that a missing last newline or a '\r' shows
+        let actual_lines: Vec<_> = actual.split('\n').collect();
+        let expected_lines: Vec<_> = expected.split('\n').collect();
+        let i = (0..)
+            .find(|&i| actual_lines.get(i) != expected_lines.get(i))
+            .unwrap();
+        panic!(
+            "{} differs at line {}\n  expected: {:?}\n     found: {:?}",
+            path.as_ref().display(),
+            i + 1,
+            expected_lines.get(i).copied().unwrap_or("<end of file>"),
+            actual_lines.get(i).copied().unwrap_or("<end of file>")
+        );
+    }
+
+    #[track_caller]
+    pub fn assert_contains<P: AsRef<Path>>(&self, path: P, needle: &str) {
+        let actual = self.read(&path);
+        assert!(
+            actual.contains(needle),
+            "{} doesn't contain {:?}, it is:\n{}",
+            path.as_ref().display(),
+            needle,
+            actual
+        );
+    }
+
+    #[track_caller]
+    pub fn assert_missing<P: AsRef<Path>>(&self, path: P) {
+        let path = self.join(path);
+        assert!(!path.exists(), "{} shouldn't exist", path.display());
+    }
+}
+
+impl Default for TempDir {
+    fn default() -> TempDir {
+        TempDir::new()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let keep = match self.keep {
+            Keep::Never => false,
+            Keep::OnFailure => thread::panicking(),
+            Keep::Always => true,
+        };
+        if keep {
+            eprintln!("kept {}", self.path.display());
+        } else {
+            // Nothing a failing test could do about it
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_and_removed() {
+        let (a, b) = (TempDir::new(), TempDir::new());
+        assert_ne!(a.path(), b.path());
+        assert!(a
+            .path()
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("temp_dir__tests__test_unique_and_removed-"));
+
+        a.write("sub/ferris.txt", "Ferris\n");
+        a.assert_file("sub/ferris.txt", "Ferris\n");
+        a.assert_contains("sub/ferris.txt", "rris");
+        a.assert_missing("corro.txt");
+
+        let path = a.path().to_owned();
+        drop(a);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_kept_on_failure() {
+        let path = thread::spawn(|| {
+            let mut dir = TempDir::new();
+            dir.set_keep(Keep::OnFailure);
+            let path = dir.path().to_owned();
+            // Panicking with `dir` still alive
+            std::panic::resume_unwind(Box::new(path));
+        })
+        .join()
+        .unwrap_err();
+        let path = path.downcast::<PathBuf>().unwrap();
+        assert!(path.exists());
+        fs::remove_dir_all(*path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "ferris.txt differs at line 2\n  expected: \"Ferris\"\n     found: \"Corro\""
+    )]
+    fn test_assert_file() {
+        let dir = TempDir::new();
+        dir.write("ferris.txt", "Ferris\nCorro\n");
+        dir.assert_file("ferris.txt", "Ferris\nFerris\n");
+    }
+}
@@ -1,4 +1,40 @@
-pub fn setup() {
-    // some setup code, like creating required files/directories, starting
-    // servers, etc.
+// The fixtures of the integration tests. A test asks for them with
+// `fixture_test!`, or calls `setup()` for the global setup alone.
+use std::fs;
+use std::path::PathBuf;
+
+use testing::fixture::{self, SharedFixture};
+use testing::stand_in::StandIn;
+
+// Set up once per test binary: the files every test reads
+pub struct Global {
+    pub data_dir: PathBuf,
+}
+
+impl SharedFixture for Global {
+    fn setup() -> Global {
+        // Statics aren't dropped, so this isn't a `TempDir`, but a directory
+        // Cargo keeps for integration tests, which is written over each run
+        let data_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("data");
+        fs::create_dir_all(&data_dir).unwrap();
+        fs::write(data_dir.join("numbers.txt"), "3 2\n100 2\n-4 4\n").unwrap();
+        Global { data_dir }
+    }
+}
+
+pub fn setup() -> &'static Global {
+    fixture::shared::<Global>()
+}
+
+// A stand-in for a calculator service, shared by all the tests
+pub struct Calculator {
+    pub server: StandIn,
+}
+
+impl SharedFixture for Calculator {
+    fn setup() -> Calculator {
+        let server = StandIn::start(&[("ADD 3 2", "5"), ("ADD 100 2", "102"), ("PING", "PONG")])
+            .expect("can't start the calculator stand-in");
+        Calculator { server }
+    }
 }
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use testing::exercises::{self, Checker};
+
+// Every exercise of the book, uncommented, still fails the way its marker
+// says. Slow, as each one is compiled on its own:
+//
+//     cargo test --test exercises -- --ignored
+#[test]
+#[ignore]
+fn test_exercises() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let root = root.canonicalize().unwrap();
+    let exercises = exercises::find(&root).unwrap();
+    assert!(
+        !exercises.is_empty(),
+        "no exercises under {}",
+        root.display()
+    );
+
+    let checker = Checker::new(Path::new(env!("CARGO_TARGET_TMPDIR")).join("exercises"));
+    let problems = checker.check(&exercises).unwrap();
+    let report: Vec<String> = problems
+        .iter()
+        .map(|(exercise, problem)| {
+            let file = exercise.to_string();
+            let file = file
+                .strip_prefix(&format!("{}/", root.display()))
+                .unwrap_or(&file)
+                .to_owned();
+            format!("{}: {}", file, problem)
+        })
+        .collect();
+    assert!(
+        report.is_empty(),
+        "{} of {} exercises don't fail as they should:\n{}",
+        report.len(),
+        exercises.len(),
+        report.join("\n")
+    );
+}
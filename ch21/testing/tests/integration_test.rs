@@ -1,8 +1,53 @@
 mod common;
 
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+use common::{Calculator, Global};
+use testing::fixture::guard;
+use testing::fixture_test;
+use testing::stand_in::StandIn;
+use testing::temp_dir::TempDir;
+
 #[test]
 fn test_add() {
     // using common code.
     common::setup();
     assert_eq!(testing::add(3, 2), 5);
 }
+
+// Each test says which fixtures it needs
+fixture_test! {
+    // The global setup, shared and read-only
+    fn test_add_from_file(global: &Global) {
+        let numbers = fs::read_to_string(global.data_dir.join("numbers.txt")).unwrap();
+        for line in numbers.lines() {
+            let (a, b) = line.split_once(' ').unwrap();
+            let (a, b): (i32, i32) = (a.parse().unwrap(), b.parse().unwrap());
+            assert_eq!(testing::add(a, b), a + b);
+        }
+    }
+
+    // The remote calculator must agree with `add`
+    fn test_add_remote(calculator: &Calculator) {
+        let mut stream = TcpStream::connect(calculator.server.addr()).unwrap();
+        writeln!(stream, "ADD 3 2").unwrap();
+        let mut response = String::new();
+        BufReader::new(&stream).read_line(&mut response).unwrap();
+        assert_eq!(response.trim_end().parse::<i32>().unwrap(), testing::add(3, 2));
+    }
+
+    // A directory for this test alone, and a server started only for it,
+    // which is stopped when the test ends
+    fn test_results_saved(dir: TempDir) {
+        // On the way out, even when failing, what the server got is saved
+        // next to the results, for `KEEP_TEST_DIRS` to keep
+        let server = guard(StandIn::start(&[("ADD 100 2", "102")]).unwrap(), |server| {
+            fs::write(dir.join("requests.log"), server.requests().join("\n")).unwrap();
+        });
+        let result = server.request("ADD 100 2").unwrap();
+        dir.write("results.txt", &result);
+        dir.assert_file("results.txt", &testing::add(100, 2).to_string());
+    }
+}